@@ -33,10 +33,37 @@ struct FluentArgs {
         long,
         short,
         value_name = "TERMS",
-        help = "Comma-separated list of metadata terms (for upsert)"
+        help = "Comma-separated list of metadata terms (for upsert, or to filter --rag sources)"
     )]
     metadata: Option<String>,
 
+    #[arg(long, value_name = "STRATEGY", help = "How upserted documents are split into chunks (default: the neo4j config, else tokens)", value_parser = ["tokens", "sentences", "markdown", "code"], requires = "upsert", conflicts_with = "request")]
+    chunk_strategy: Option<String>,
+
+    #[arg(long, value_name = "TOKENS", help = "Most tokens in an upserted chunk", requires = "upsert", conflicts_with = "request")]
+    chunk_size: Option<usize>,
+
+    #[arg(long, value_name = "TOKENS", help = "Tokens repeated between consecutive upserted chunks", requires = "upsert", conflicts_with = "request")]
+    chunk_overlap: Option<usize>,
+
+    #[arg(long, value_name = "GLOB", help = "Upsert only files matching this glob, e.g. '**/*.md' (repeatable)", requires = "upsert", conflicts_with = "request")]
+    include: Vec<String>,
+
+    #[arg(long, value_name = "GLOB", help = "Skip files and directories matching this glob (repeatable)", requires = "upsert", conflicts_with = "request")]
+    exclude: Vec<String>,
+
+    #[arg(long, value_name = "MB", help = "Skip upserting files larger than this (default 25, 0 for no limit)", requires = "upsert", conflicts_with = "request")]
+    max_file_size: Option<u64>,
+
+    #[arg(long, help = "Upsert files that .gitignore files exclude", requires = "upsert", conflicts_with = "request")]
+    no_gitignore: bool,
+
+    #[arg(long, help = "Answer from upserted documents, citing the chunks retrieved from Neo4j", conflicts_with = "upsert")]
+    rag: bool,
+
+    #[arg(long, value_name = "N", help = "Number of document chunks to retrieve for --rag (default 5)", requires = "rag")]
+    top_k: Option<usize>,
+
     #[arg(short, long, value_name = "FILE", help = "Upload a media file")]
     upload_image_file: Option<String>,
 
@@ -57,6 +84,30 @@ struct FluentArgs {
     #[arg(short, long, help = "Format output as markdown")]
     markdown: bool,
 
+    #[arg(long, value_name = "FILE", help = "JSON file with prior conversation turns, each with a role (system, user or assistant) and content", value_hint = clap::ValueHint::FilePath)]
+    messages: Option<String>,
+
+    #[arg(long, help = "Print the response as it is generated")]
+    stream: bool,
+
+    #[arg(long, value_name = "FILE", help = "JSON file of shell-command tools the model may call, each with a name, description, JSON Schema parameters and command", value_hint = clap::ValueHint::FilePath, conflicts_with = "stream")]
+    tools: Option<String>,
+
+    #[arg(long, value_name = "FILE", help = "JSON Schema file the response must match; invalid replies are sent back for repair", value_hint = clap::ValueHint::FilePath, conflicts_with_all = ["stream", "tools"])]
+    json_schema: Option<String>,
+
+    #[arg(long, value_name = "N", help = "Times to re-prompt when the response does not match --json-schema (default 2)", requires = "json_schema")]
+    max_repairs: Option<usize>,
+
+    #[arg(long, help = "Bypass the response cache configured for the engine")]
+    no_cache: bool,
+
+    #[arg(long, value_name = "FILE", help = "Record requests and responses to a cassette file for the replay engine", value_hint = clap::ValueHint::FilePath)]
+    record: Option<String>,
+
+    #[arg(long, help = "Show the prompt token estimate and any trimming without sending the request")]
+    dry_run: bool,
+
     #[arg(
         long,
         value_name = "QUERY",
@@ -15,45 +15,49 @@ use serde_json::Value;
 
 pub mod cli {
     use anyhow::{anyhow, Error, Result};
-    use clap::{Arg, ArgAction, ArgMatches, Command};
+    use clap::{Arg, ArgAction, ArgGroup, ArgMatches, Command};
     use fluent_core::config::{load_config, Config, EngineConfig};
+    use fluent_core::error::EngineError;
+    use fluent_core::pricing::{format_cost, log_cost, response_cost};
+    use fluent_core::rag::{add_sources, page_label, source_list, DEFAULT_TOP_K};
+    use fluent_core::search::{SearchHit, SearchScope, DEFAULT_SEARCH_LIMIT};
+    use fluent_core::tokenizer::{self, fit_request, PromptEstimate};
     use fluent_core::traits::Engine;
-    use fluent_core::types::{Request, Response};
-    use fluent_engines::anthropic::AnthropicEngine;
+    use fluent_core::types::{Message, Request, Response};
+    use fluent_core::types::UpsertResponse;
+    use fluent_core::utils::chunking::ChunkingConfig;
+    use fluent_core::utils::files::{
+        check_file_size, collect_files, Glob, WalkOptions, DEFAULT_MAX_FILE_SIZE_MB,
+    };
+    use fluent_engines::cache::ResponseCache;
     use fluent_engines::create_engine;
-    use fluent_engines::openai::OpenAIEngine;
     use indicatif::{ProgressBar, ProgressStyle};
     use owo_colors::OwoColorize;
     use std::collections::{HashMap, HashSet};
     use std::fs;
-    use std::io::IsTerminal;
+    use std::io::{IsTerminal, Write};
     use std::path::{Path, PathBuf};
     use std::pin::Pin;
     use std::time::Duration;
     use std::{env, io};
 
-    use log::{debug, error, info};
+    use log::{debug, error, info, warn};
     use serde_json::Value;
     use tokio::io::AsyncReadExt;
 
     use crate::{create_llm_engine, generate_and_execute_cypher};
-    use fluent_core::neo4j_client::{InteractionStats, Neo4jClient};
+    use fluent_core::neo4j_client::{
+        DocumentDetails, DocumentList, InteractionStats, Neo4jClient, UpsertOutcome, UpsertStatus,
+    };
     use fluent_core::output_processor::OutputProcessor;
-    use fluent_engines::cohere::CohereEngine;
-    use fluent_engines::dalle::DalleEngine;
-    use fluent_engines::flowise_chain::FlowiseChainEngine;
-    use fluent_engines::google_gemini::GoogleGeminiEngine;
-    use fluent_engines::groqlpu::GroqLPUEngine;
-    use fluent_engines::imagepro::ImagineProEngine;
-    use fluent_engines::langflow::LangflowEngine;
-    use fluent_engines::leonardoai::LeonardoAIEngine;
-    use fluent_engines::mistral::MistralEngine;
-    use fluent_engines::perplexity::PerplexityEngine;
     use fluent_engines::pipeline_executor::{
         FileStateStore, Pipeline, PipelineExecutor, StateStore,
     };
-    use fluent_engines::stabilityai::StabilityAIEngine;
-    use fluent_engines::webhook::WebhookEngine;
+    use fluent_engines::streaming::collect_stream;
+    use fluent_engines::structured::{
+        execute_structured, with_schema, SchemaValidationError, DEFAULT_MAX_REPAIRS,
+    };
+    use fluent_engines::tools::{execute_with_tools, ToolRegistry, DEFAULT_MAX_TOOL_ROUNDS};
     use tokio::time::Instant;
     use uuid::Uuid;
 
@@ -105,6 +109,7 @@ pub mod cli {
         let request = Request {
             flowname: "default".to_string(),
             payload: format!("File ID: {}. {}", file_id, request_content),
+            ..Default::default()
         };
 
         Pin::from(engine.execute(&request)).await
@@ -114,6 +119,7 @@ pub mod cli {
         let request = Request {
             flowname: "default".to_string(),
             payload: request_content.to_string(),
+            ..Default::default()
         };
 
         Pin::from(engine.execute(&request)).await
@@ -132,11 +138,403 @@ pub mod cli {
         }
     }
 
+    /// The model for the status line, with the engine that answered when a
+    /// fallback chain picked one.
+    fn model_label(response: &Response) -> String {
+        match &response.engine {
+            Some(engine) => format!("{} (via {})", response.model, engine),
+            None => response.model.clone(),
+        }
+    }
+
+    /// The footer's cost field, empty when the price is unknown.
+    fn cost_label(cost: Option<f64>, use_colors: bool) -> String {
+        match cost.map(format_cost) {
+            Some(cost) if use_colors => format!(" | Cost: {}", cost.yellow()),
+            Some(cost) => format!(" | Cost: {}", cost),
+            None => String::new(),
+        }
+    }
+
+    fn config_path(matches: &ArgMatches) -> Result<String> {
+        matches.get_one::<String>("config")
+            .map(|s| s.to_string())
+            .or_else(|| env::var("FLUENT_CLI_V2_CONFIG_PATH").ok())
+            .ok_or_else(|| anyhow!("No config file specified and FLUENT_CLI_V2_CONFIG_PATH environment variable not set"))
+    }
+
+    /// The name of the first engine in a config file with a `neo4j` section.
+    fn neo4j_engine_name(config_path: &str) -> Result<String> {
+        let config: Value = serde_json::from_str(&fs::read_to_string(config_path)?)?;
+        config["engines"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|engine| engine["neo4j"].is_object())
+            .and_then(|engine| engine["name"].as_str())
+            .map(String::from)
+            .ok_or_else(|| anyhow!("No engine with a neo4j configuration in {}", config_path))
+    }
+
+    /// Connects to the Neo4j database of the subcommand's `--engine`, the
+    /// top-level engine, or the first engine with a `neo4j` section.
+    async fn connect_neo4j(matches: &ArgMatches, sub_matches: &ArgMatches) -> Result<Neo4jClient> {
+        let config_path = config_path(matches)?;
+        let engine_name = match sub_matches
+            .get_one::<String>("engine")
+            .or_else(|| matches.get_one::<String>("engine"))
+        {
+            Some(name) => name.clone(),
+            None => neo4j_engine_name(&config_path)?,
+        };
+        let config = load_config(&config_path, &engine_name, &HashMap::new())?;
+        let neo4j_config = config.engines[0]
+            .neo4j
+            .as_ref()
+            .ok_or_else(|| anyhow!("Neo4j configuration not found for engine {}", engine_name))?;
+        Neo4jClient::new(neo4j_config).await
+    }
+
+    async fn handle_search(matches: &ArgMatches, sub_matches: &ArgMatches) -> Result<()> {
+        let search = sub_matches.get_one::<String>("query").unwrap();
+        let limit = sub_matches
+            .get_one::<usize>("limit")
+            .copied()
+            .unwrap_or(DEFAULT_SEARCH_LIMIT);
+        let scope = match sub_matches.get_one::<String>("scope").map(String::as_str) {
+            Some("documents") => SearchScope::Documents,
+            Some("interactions") => SearchScope::Interactions,
+            _ => SearchScope::All,
+        };
+
+        let neo4j_client = connect_neo4j(matches, sub_matches).await?;
+        neo4j_client.ensure_search_indexes().await?;
+        let hits = neo4j_client.hybrid_search(search, scope, limit).await?;
+
+        if sub_matches.get_flag("json") {
+            println!("{}", serde_json::to_string_pretty(&hits)?);
+        } else {
+            print_search_hits(&hits);
+        }
+        Ok(())
+    }
+
+    fn print_search_hits(hits: &[SearchHit]) {
+        if hits.is_empty() {
+            eprintln!("No results");
+            return;
+        }
+        println!(
+            "{:>3}  {:<8}  {:<9}  {:<11}  {:<30}  TEXT",
+            "#", "SCORE", "KIND", "MATCHED BY", "SOURCE"
+        );
+        for (i, hit) in hits.iter().enumerate() {
+            let matched_by = match (hit.text_rank, hit.vector_rank) {
+                (Some(_), Some(_)) => "text+vector",
+                (Some(_), None) => "text",
+                _ => "vector",
+            };
+            let mut source = match hit.chunk_index {
+                Some(index) => format!("{} #{}", hit.source, index),
+                None => hit.source.clone(),
+            };
+            if let Some(pages) = page_label(hit.page_start, hit.page_end) {
+                source = format!("{} ({})", source, pages);
+            }
+            println!(
+                "{:>3}  {:<8.5}  {:<9}  {:<11}  {:<30}  {}",
+                i + 1,
+                hit.score,
+                hit.kind.as_str(),
+                matched_by,
+                ellipsize(&source, 30),
+                ellipsize(&hit.content.split_whitespace().collect::<Vec<_>>().join(" "), 60)
+            );
+        }
+    }
+
+    async fn handle_docs(matches: &ArgMatches, sub_matches: &ArgMatches) -> Result<()> {
+        let (command, docs_matches) = sub_matches
+            .subcommand()
+            .expect("clap requires a docs subcommand");
+        let neo4j_client = connect_neo4j(matches, docs_matches).await?;
+        let json = matches!(command, "list" | "show") && docs_matches.get_flag("json");
+
+        match command {
+            "list" => {
+                let offset = *docs_matches.get_one::<usize>("offset").unwrap();
+                let limit = *docs_matches.get_one::<usize>("limit").unwrap();
+                let list = neo4j_client
+                    .list_documents(&metadata_terms(docs_matches), offset, limit)
+                    .await?;
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&list)?);
+                } else {
+                    print_document_list(&list);
+                }
+            }
+            "show" => {
+                let reference = docs_matches.get_one::<String>("document").unwrap();
+                let document = neo4j_client
+                    .find_document(reference)
+                    .await?
+                    .ok_or_else(|| anyhow!("No document found for {}", reference))?;
+                let details = neo4j_client
+                    .document_details(&document.id)
+                    .await?
+                    .ok_or_else(|| anyhow!("No document found for {}", reference))?;
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&details)?);
+                } else {
+                    print_document_details(&details);
+                }
+            }
+            "delete" => {
+                let deleted = match docs_matches.get_one::<String>("document") {
+                    Some(reference) => {
+                        let document = neo4j_client
+                            .find_document(reference)
+                            .await?
+                            .ok_or_else(|| anyhow!("No document found for {}", reference))?;
+                        neo4j_client.delete_document(&document.id).await?;
+                        vec![document]
+                    }
+                    None => {
+                        let terms = metadata_terms(docs_matches);
+                        let unsourced = docs_matches.get_flag("unsourced");
+                        if !docs_matches.get_flag("yes") {
+                            let matched = if unsourced {
+                                neo4j_client.unsourced_documents().await?
+                            } else {
+                                neo4j_client
+                                    .list_documents(&terms, 0, usize::MAX)
+                                    .await?
+                                    .documents
+                            };
+                            if matched.is_empty() {
+                                eprintln!("No documents matched");
+                                return Ok(());
+                            }
+                            for document in &matched {
+                                eprintln!("{} (document ID: {})", document.source, document.id);
+                            }
+                            return Err(anyhow!(
+                                "Pass --yes to delete these {} documents",
+                                matched.len()
+                            ));
+                        }
+                        if unsourced {
+                            neo4j_client.delete_unsourced_documents().await?
+                        } else {
+                            neo4j_client.delete_documents(&terms).await?
+                        }
+                    }
+                };
+                for document in &deleted {
+                    eprintln!("Deleted {} (document ID: {})", document.source, document.id);
+                }
+                if deleted.is_empty() {
+                    eprintln!("No documents matched");
+                }
+            }
+            "reindex" => {
+                let pb = ProgressBar::new_spinner();
+                pb.enable_steady_tick(Duration::from_millis(100));
+                pb.set_message("Reindexing documents...");
+                let on_progress = |source: &str, done: usize, total: usize| {
+                    pb.set_message(format!("Embedding {}: {}/{}", source, done, total));
+                };
+                let summary = neo4j_client
+                    .reindex_documents(
+                        &metadata_terms(docs_matches),
+                        docs_matches.get_flag("force"),
+                        &on_progress,
+                    )
+                    .await?;
+                pb.finish_and_clear();
+                if summary.index_rebuilt {
+                    eprintln!("Rebuilt the vector index for the new embedding size");
+                }
+                eprintln!(
+                    "Embedded {} chunks in {} documents and {} questions and responses with {}",
+                    summary.chunks, summary.documents, summary.interactions, summary.model
+                );
+            }
+            _ => unreachable!("clap requires a docs subcommand"),
+        }
+        Ok(())
+    }
+
+    fn print_document_list(list: &DocumentList) {
+        if list.documents.is_empty() {
+            eprintln!("No documents");
+            return;
+        }
+        println!(
+            "{:<36}  {:>6}  {:<19}  {:<40}  METADATA",
+            "ID", "CHUNKS", "UPDATED", "SOURCE"
+        );
+        for document in &list.documents {
+            let updated = document
+                .updated_at
+                .as_ref()
+                .or(document.created_at.as_ref())
+                .map_or("", |date| date.get(..19).unwrap_or(date));
+            println!(
+                "{:<36}  {:>6}  {:<19}  {:<40}  {}",
+                document.id,
+                document.chunk_count,
+                updated,
+                ellipsize(&document.source, 40),
+                ellipsize(&document.metadata.join(", "), 40)
+            );
+        }
+        eprintln!(
+            "Showing {}-{} of {} documents",
+            list.offset + 1,
+            list.offset + list.documents.len(),
+            list.total
+        );
+    }
+
+    fn print_document_details(details: &DocumentDetails) {
+        let document = &details.summary;
+        let field = |name: &str, value: &str| {
+            if !value.is_empty() {
+                println!("{:<10} {}", format!("{}:", name), value);
+            }
+        };
+        field("ID", &document.id);
+        field("Source", &document.source);
+        let date = |date: &Option<String>| date.clone().unwrap_or_default();
+        field("Created", &date(&document.created_at));
+        field("Updated", &date(&document.updated_at));
+        field("Metadata", &document.metadata.join(", "));
+        field("Themes", &details.themes.join(", "));
+        field("Keywords", &details.keywords.join(", "));
+        field("Clusters", &details.clusters.join(", "));
+        field(
+            "Content",
+            &format!(
+                "{} characters in {} chunks",
+                details.content_length, document.chunk_count
+            ),
+        );
+
+        if details.chunks.is_empty() {
+            return;
+        }
+        println!();
+        println!(
+            "{:>5}  {:<15}  {:<15}  {:<24}  TEXT",
+            "#", "CHARACTERS", "PAGES", "EMBEDDING MODEL"
+        );
+        for chunk in &details.chunks {
+            let characters = match (chunk.start, chunk.end) {
+                (Some(start), Some(end)) => format!("{}-{}", start, end),
+                _ => String::new(),
+            };
+            let pages = page_label(chunk.page_start, chunk.page_end).unwrap_or_default();
+            println!(
+                "{:>5}  {:<15}  {:<15}  {:<24}  {}",
+                chunk.index,
+                characters,
+                pages,
+                ellipsize(chunk.embedding_model.as_deref().unwrap_or("-"), 24),
+                ellipsize(&chunk.content.split_whitespace().collect::<Vec<_>>().join(" "), 60)
+            );
+        }
+    }
+
+    /// Cuts `text` to at most `width` characters, ending with `…` if cut.
+    fn ellipsize(text: &str, width: usize) -> String {
+        if text.chars().count() <= width {
+            text.to_string()
+        } else {
+            let kept: String = text.chars().take(width.saturating_sub(1)).collect();
+            format!("{}…", kept)
+        }
+    }
+
+    /// The client's chunking settings with any `--chunk-*` overrides.
+    fn chunking(neo4j_client: &Neo4jClient, matches: &ArgMatches) -> Result<ChunkingConfig> {
+        let mut chunking = *neo4j_client.chunking();
+        if let Some(strategy) = matches.get_one::<String>("chunk-strategy") {
+            chunking.strategy = strategy.parse()?;
+        }
+        if let Some(&size) = matches.get_one::<usize>("chunk-size") {
+            chunking.size = size;
+        }
+        if let Some(&overlap) = matches.get_one::<usize>("chunk-overlap") {
+            chunking.overlap = overlap;
+        }
+        Ok(chunking)
+    }
+
+    /// The files `--upsert` picks from a directory, from `--include`,
+    /// `--exclude`, `--max-file-size` and `--no-gitignore`.
+    fn walk_options(matches: &ArgMatches) -> Result<WalkOptions> {
+        let globs = |id: &str| -> Result<Vec<Glob>> {
+            matches
+                .get_many::<String>(id)
+                .into_iter()
+                .flatten()
+                .map(|pattern| Glob::new(pattern))
+                .collect()
+        };
+        let max_file_size = matches
+            .get_one::<u64>("max-file-size")
+            .copied()
+            .unwrap_or(DEFAULT_MAX_FILE_SIZE_MB);
+        Ok(WalkOptions {
+            include: globs("include")?,
+            exclude: globs("exclude")?,
+            // 0 turns the limit off.
+            max_file_size: (max_file_size > 0).then(|| max_file_size * 1024 * 1024),
+            gitignore: !matches.get_flag("no-gitignore"),
+        })
+    }
+
+    /// The comma-separated `--metadata` terms.
+    fn metadata_terms(matches: &ArgMatches) -> Vec<String> {
+        matches
+            .get_one::<String>("metadata")
+            .map(|s| s.split(',').map(String::from).collect())
+            .unwrap_or_default()
+    }
+
+    /// Prints the token estimate for `--dry-run`.
+    fn print_dry_run(engine_config: &EngineConfig, estimate: &PromptEstimate) {
+        println!(
+            "Engine: {} ({})",
+            engine_config.name,
+            tokenizer::model_name(engine_config).unwrap_or(&engine_config.engine)
+        );
+        if estimate.exact {
+            println!("Prompt tokens: {}", estimate.prompt_tokens);
+        } else {
+            println!("Prompt tokens: ~{} (estimated)", estimate.prompt_tokens);
+        }
+        match &estimate.budget {
+            Some(budget) => println!(
+                "Context window: {} tokens, {} kept for the reply, {} left",
+                budget.context_window,
+                budget.completion_tokens,
+                budget.prompt_tokens().saturating_sub(estimate.prompt_tokens)
+            ),
+            None => println!("Context window: unknown (set context_window to enable trimming)"),
+        }
+        if let Some(summary) = estimate.trimmed() {
+            println!("Trimmed: {}", summary);
+        }
+    }
+
     pub fn build_cli() -> Command {
         Command::new("Fluent CLI")
             .version("2.0")
             .author("Your Name <your.email@example.com>")
             .about("A powerful CLI for interacting with various AI engines")
+            .subcommand_negates_reqs(true)
             .arg(
                 Arg::new("config")
                     .short('c')
@@ -193,7 +591,86 @@ pub mod cli {
                     .long("metadata")
                     .short('t')
                     .value_name("TERMS")
-                    .help("Comma-separated list of metadata terms (for upsert)")
+                    .help("Comma-separated list of metadata terms (for upsert, or to filter --rag sources)")
+                    .required(false),
+            )
+            .arg(
+                Arg::new("chunk-strategy")
+                    .long("chunk-strategy")
+                    .value_name("STRATEGY")
+                    .help("How upserted documents are split into chunks (default: the neo4j config, else tokens)")
+                    .value_parser(["tokens", "sentences", "markdown", "code"])
+                    .requires("upsert")
+                    .conflicts_with("request"),
+            )
+            .arg(
+                Arg::new("chunk-size")
+                    .long("chunk-size")
+                    .value_name("TOKENS")
+                    .help("Most tokens in an upserted chunk")
+                    .value_parser(clap::value_parser!(usize))
+                    .requires("upsert")
+                    .conflicts_with("request"),
+            )
+            .arg(
+                Arg::new("chunk-overlap")
+                    .long("chunk-overlap")
+                    .value_name("TOKENS")
+                    .help("Tokens repeated between consecutive upserted chunks")
+                    .value_parser(clap::value_parser!(usize))
+                    .requires("upsert")
+                    .conflicts_with("request"),
+            )
+            .arg(
+                Arg::new("include")
+                    .long("include")
+                    .value_name("GLOB")
+                    .help("Upsert only files matching this glob, e.g. '**/*.md' (repeatable)")
+                    .action(ArgAction::Append)
+                    .requires("upsert")
+                    .conflicts_with("request"),
+            )
+            .arg(
+                Arg::new("exclude")
+                    .long("exclude")
+                    .value_name("GLOB")
+                    .help("Skip files and directories matching this glob (repeatable)")
+                    .action(ArgAction::Append)
+                    .requires("upsert")
+                    .conflicts_with("request"),
+            )
+            .arg(
+                Arg::new("max-file-size")
+                    .long("max-file-size")
+                    .value_name("MB")
+                    .help("Skip upserting files larger than this (default 25, 0 for no limit)")
+                    .value_parser(clap::value_parser!(u64))
+                    .requires("upsert")
+                    .conflicts_with("request"),
+            )
+            .arg(
+                Arg::new("no-gitignore")
+                    .long("no-gitignore")
+                    .help("Upsert files that .gitignore files exclude")
+                    .action(ArgAction::SetTrue)
+                    .requires("upsert")
+                    .conflicts_with("request"),
+            )
+            .arg(
+                Arg::new("rag")
+                    .long("rag")
+                    .help("Answer from upserted documents, citing the chunks retrieved from Neo4j")
+                    .action(ArgAction::SetTrue)
+                    .conflicts_with("upsert"),
+            )
+            .arg(
+                Arg::new("top-k")
+                    .long("top-k")
+                    .value_name("N")
+                    .help("Number of document chunks to retrieve for --rag (default 5)")
+                    .action(ArgAction::Set)
+                    .value_parser(clap::value_parser!(usize))
+                    .requires("rag")
                     .required(false),
             )
             .arg(
@@ -235,6 +712,72 @@ pub mod cli {
                     .help("Format output as markdown")
                     .action(ArgAction::SetTrue),
             )
+            .arg(
+                Arg::new("messages")
+                    .long("messages")
+                    .value_name("FILE")
+                    .help("JSON file with prior conversation turns, each with a role (system, user or assistant) and content")
+                    .action(ArgAction::Set)
+                    .value_hint(clap::ValueHint::FilePath)
+                    .required(false),
+            )
+            .arg(
+                Arg::new("stream")
+                    .long("stream")
+                    .help("Print the response as it is generated")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("tools")
+                    .long("tools")
+                    .value_name("FILE")
+                    .help("JSON file of shell-command tools the model may call, each with a name, description, JSON Schema parameters and command")
+                    .action(ArgAction::Set)
+                    .value_hint(clap::ValueHint::FilePath)
+                    .conflicts_with_all(["stream", "upload-image-file"])
+                    .required(false),
+            )
+            .arg(
+                Arg::new("json-schema")
+                    .long("json-schema")
+                    .value_name("FILE")
+                    .help("JSON Schema file the response must match; invalid replies are sent back for repair")
+                    .action(ArgAction::Set)
+                    .value_hint(clap::ValueHint::FilePath)
+                    .conflicts_with_all(["stream", "tools", "upload-image-file"])
+                    .required(false),
+            )
+            .arg(
+                Arg::new("max-repairs")
+                    .long("max-repairs")
+                    .value_name("N")
+                    .help("Times to re-prompt when the response does not match --json-schema (default 2)")
+                    .action(ArgAction::Set)
+                    .value_parser(clap::value_parser!(usize))
+                    .requires("json-schema")
+                    .required(false),
+            )
+            .arg(
+                Arg::new("no-cache")
+                    .long("no-cache")
+                    .help("Bypass the response cache configured for the engine")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("record")
+                    .long("record")
+                    .value_name("FILE")
+                    .help("Record requests and responses to a cassette file for the replay engine")
+                    .action(ArgAction::Set)
+                    .value_hint(clap::ValueHint::FilePath)
+                    .required(false),
+            )
+            .arg(
+                Arg::new("dry-run")
+                    .long("dry-run")
+                    .help("Show the prompt token estimate and any trimming without sending the request")
+                    .action(ArgAction::SetTrue),
+            )
             .arg(
                 Arg::new("generate-cypher")
                     .long("generate-cypher")
@@ -278,6 +821,161 @@ pub mod cli {
                             .action(ArgAction::SetTrue),
                     ),
             )
+            .subcommand(
+                Command::new("search")
+                    .about("Search upserted documents and past interactions by keyword and meaning")
+                    .arg(
+                        Arg::new("query")
+                            .help("What to search for")
+                            .required(true),
+                    )
+                    .arg(
+                        Arg::new("limit")
+                            .long("limit")
+                            .short('n')
+                            .value_name("N")
+                            .help("Number of results (default 10)")
+                            .value_parser(clap::value_parser!(usize)),
+                    )
+                    .arg(
+                        Arg::new("scope")
+                            .long("scope")
+                            .help("What to search")
+                            .value_parser(["all", "documents", "interactions"])
+                            .default_value("all"),
+                    )
+                    .arg(
+                        Arg::new("engine")
+                            .long("engine")
+                            .value_name("NAME")
+                            .help("Engine whose neo4j config to use (default: the first engine with one)"),
+                    )
+                    .arg(
+                        Arg::new("json")
+                            .long("json")
+                            .help("Print results as JSON")
+                            .action(ArgAction::SetTrue),
+                    ),
+            )
+            .subcommand(
+                Command::new("docs")
+                    .about("List, show, delete and reindex upserted documents")
+                    .subcommand_required(true)
+                    .subcommand(
+                        Command::new("list")
+                            .about("List documents by source")
+                            .arg(docs_metadata_arg("Only list documents with all of these metadata terms"))
+                            .arg(
+                                Arg::new("limit")
+                                    .long("limit")
+                                    .short('n')
+                                    .value_name("N")
+                                    .help("Documents per page")
+                                    .value_parser(clap::value_parser!(usize))
+                                    .default_value("20"),
+                            )
+                            .arg(
+                                Arg::new("offset")
+                                    .long("offset")
+                                    .value_name("N")
+                                    .help("Documents to skip")
+                                    .value_parser(clap::value_parser!(usize))
+                                    .default_value("0"),
+                            )
+                            .arg(docs_engine_arg())
+                            .arg(docs_json_arg()),
+                    )
+                    .subcommand(
+                        Command::new("show")
+                            .about("Show a document with its chunks, themes, keywords and clusters")
+                            .arg(
+                                Arg::new("document")
+                                    .value_name("ID|PATH")
+                                    .help("Document ID, or the path it was upserted from")
+                                    .required(true),
+                            )
+                            .arg(docs_engine_arg())
+                            .arg(docs_json_arg()),
+                    )
+                    .subcommand(
+                        Command::new("delete")
+                            .about("Delete documents with their chunks and embeddings")
+                            .arg(
+                                Arg::new("document")
+                                    .value_name("ID|PATH")
+                                    .help("Document ID, or the path it was upserted from"),
+                            )
+                            .arg(docs_metadata_arg("Delete every document with all of these metadata terms"))
+                            .arg(
+                                Arg::new("unsourced")
+                                    .long("unsourced")
+                                    .help("Delete every document upserted before sources were recorded")
+                                    .action(ArgAction::SetTrue),
+                            )
+                            .arg(
+                                Arg::new("yes")
+                                    .long("yes")
+                                    .short('y')
+                                    .conflicts_with("document")
+                                    .help("Delete the documents matching --metadata or --unsourced; without it they are only listed")
+                                    .action(ArgAction::SetTrue),
+                            )
+                            .group(
+                                ArgGroup::new("target")
+                                    .args(["document", "metadata", "unsourced"])
+                                    .required(true),
+                            )
+                            .arg(docs_engine_arg()),
+                    )
+                    .subcommand(
+                        Command::new("reindex")
+                            .about("Embed document chunks, questions and responses again with the engine's current embedding model")
+                            .arg(docs_metadata_arg("Only reindex documents with all of these metadata terms"))
+                            .arg(
+                                Arg::new("force")
+                                    .long("force")
+                                    .help("Also embed chunks already embedded with the current model")
+                                    .action(ArgAction::SetTrue),
+                            )
+                            .arg(docs_engine_arg()),
+                    ),
+            )
+            .subcommand(
+                Command::new("cache")
+                    .about("Inspect or clear the response cache")
+                    .subcommand_required(true)
+                    .subcommand(Command::new("stats").about("Show cache size and entry counts"))
+                    .subcommand(
+                        Command::new("clear").about("Delete cached responses").arg(
+                            Arg::new("expired")
+                                .long("expired")
+                                .help("Only delete expired entries")
+                                .action(ArgAction::SetTrue),
+                        ),
+                    ),
+            )
+    }
+
+    fn docs_metadata_arg(help: &'static str) -> Arg {
+        Arg::new("metadata")
+            .long("metadata")
+            .short('t')
+            .value_name("TERMS")
+            .help(help)
+    }
+
+    fn docs_engine_arg() -> Arg {
+        Arg::new("engine")
+            .long("engine")
+            .value_name("NAME")
+            .help("Engine whose neo4j config to use (default: the first engine with one)")
+    }
+
+    fn docs_json_arg() -> Arg {
+        Arg::new("json")
+            .long("json")
+            .help("Print as JSON")
+            .action(ArgAction::SetTrue)
     }
 
     pub async fn get_neo4j_query_llm(config: &Config) -> Option<(Box<dyn Engine>, &EngineConfig)> {
@@ -288,6 +986,25 @@ pub mod cli {
         Some((engine, llm_config))
     }
 
+    /// Process exit code for a failed run: engine errors get one code per
+    /// kind so scripts can react to them, anything else exits with 1.
+    pub fn exit_code(error: &Error) -> i32 {
+        if error.chain().any(|cause| cause.is::<SchemaValidationError>()) {
+            return 18;
+        }
+        match EngineError::find(error) {
+            Some(EngineError::Auth { .. }) => 10,
+            Some(EngineError::RateLimited { .. }) => 11,
+            Some(EngineError::QuotaExceeded { .. }) => 12,
+            Some(EngineError::ContextTooLong { .. }) => 13,
+            Some(EngineError::ContentFiltered { .. }) => 14,
+            Some(EngineError::Transport { .. }) => 15,
+            Some(EngineError::MalformedResponse { .. }) => 16,
+            Some(EngineError::Provider { .. }) => 17,
+            None => 1,
+        }
+    }
+
     pub async fn run() -> Result<()> {
         let matches = build_cli().get_matches();
 
@@ -308,7 +1025,7 @@ pub mod cli {
                 };
                 let executor = PipelineExecutor::new(state_store.clone(), json_output);
 
-                executor
+                let summary = executor
                     .execute(&pipeline, input, force_fresh, run_id.clone())
                     .await?;
 
@@ -325,18 +1042,46 @@ pub mod cli {
                         eprintln!("No state file found for the given run ID.");
                         std::process::exit(1);
                     }
+                } else if let Some(cost) = serde_json::from_str::<Value>(&summary)?["cost"]
+                    .as_f64()
+                    .filter(|cost| *cost > 0.0)
+                {
+                    eprintln!("Pipeline cost: {}", format_cost(cost));
                 }
 
                 std::process::exit(0);
             }
+            Some(("search", sub_matches)) => {
+                handle_search(&matches, sub_matches).await?;
+                return Ok(());
+            }
+            Some(("docs", sub_matches)) => {
+                handle_docs(&matches, sub_matches).await?;
+                return Ok(());
+            }
+            Some(("cache", sub_matches)) => {
+                let cache = ResponseCache::new(ResponseCache::default_directory());
+                match sub_matches.subcommand() {
+                    Some(("stats", _)) => {
+                        let stats = cache.stats()?;
+                        println!("Directory: {}", cache.directory().display());
+                        println!("Entries: {}", stats.entries);
+                        println!("Expired: {}", stats.expired);
+                        println!("Size: {:.2} MB", stats.total_bytes as f64 / (1024.0 * 1024.0));
+                    }
+                    Some(("clear", clear_matches)) => {
+                        let removed = cache.clear(clear_matches.get_flag("expired"))?;
+                        println!("Removed {} cache entries", removed);
+                    }
+                    _ => unreachable!("clap requires a cache subcommand"),
+                }
+                return Ok(());
+            }
             // ... other commands ...
             _ => Ok(()), // Default case, do nothing
         };
 
-        let config_path = matches.get_one::<String>("config")
-            .map(|s| s.to_string())
-            .or_else(|| env::var("FLUENT_CLI_V2_CONFIG_PATH").ok())
-            .ok_or_else(|| anyhow!("No config file specified and FLUENT_CLI_V2_CONFIG_PATH environment variable not set"))?;
+        let config_path = config_path(&matches)?;
 
         let engine_name = matches.get_one::<String>("engine").unwrap();
 
@@ -345,7 +1090,18 @@ pub mod cli {
             .map(|values| values.filter_map(|s| parse_key_value_pair(s)).collect())
             .unwrap_or_default();
 
-        let config = load_config(&config_path, engine_name, &overrides)?;
+        let mut config = load_config(&config_path, engine_name, &overrides)?;
+        let no_cache = matches.get_flag("no-cache");
+        if no_cache {
+            for engine in &mut config.engines {
+                engine.disable_cache();
+            }
+        }
+        if let Some(cassette) = matches.get_one::<String>("record") {
+            for engine in &mut config.engines {
+                engine.record = Some(cassette.to_string());
+            }
+        }
         let spinner_config = config.engines[0].spinner.clone().unwrap_or_default();
         let pb = ProgressBar::new_spinner();
         let engine_config = &config.engines[0];
@@ -373,7 +1129,10 @@ pub mod cli {
                 .ok_or_else(|| anyhow!("No query LLM specified for Neo4j"))?;
 
             // Load the configuration for the query LLM
-            let query_llm_config = load_config(&config_path, query_llm_name, &HashMap::new())?;
+            let mut query_llm_config = load_config(&config_path, query_llm_name, &HashMap::new())?;
+            if no_cache {
+                query_llm_config.engines[0].disable_cache();
+            }
             let query_llm_engine_config = &query_llm_config.engines[0];
 
             let query_llm_engine = create_llm_engine(query_llm_engine_config).await?;
@@ -391,31 +1150,39 @@ pub mod cli {
             } else {
                 let engine: Box<dyn Engine> = create_engine(engine_config).await?;
 
-                let max_tokens = engine_config
-                    .parameters
-                    .get("max_tokens")
-                    .and_then(|v| v.as_i64())
-                    .unwrap_or(-1);
-
                 let user_request = matches
                     .get_one::<String>("request")
                     .map(|s| s.to_string())
                     .unwrap_or_default();
 
-                let mut combined_request = format!(
-                    "Cypher query: {}\n\nCypher result:\n{}\n\nBased on the above Cypher query and its result, please provide an analysis or answer the following question: {}",
-                    cypher_query, cypher_result, user_request
-                );
+                let analysis_request = |cypher_result: &str| {
+                    format!(
+                        "Cypher query: {}\n\nCypher result:\n{}\n\nBased on the above Cypher query and its result, please provide an analysis or answer the following question: {}",
+                        cypher_query, cypher_result, user_request
+                    )
+                };
 
-                // Truncate the combined request if it exceeds the max tokens
-                if max_tokens > 0 && combined_request.len() > max_tokens as usize {
-                    combined_request.truncate(max_tokens as usize);
-                    combined_request += "... [truncated]";
-                }
+                // Cut the query result, not the question, if it does not fit the context window
+                let cypher_result = match tokenizer::TokenBudget::for_config(engine_config) {
+                    Some(budget) => {
+                        let tokenizer = tokenizer::Tokenizer::for_config(engine_config);
+                        let overhead = tokenizer.count_request(&Request {
+                            payload: analysis_request(""),
+                            ..Default::default()
+                        });
+                        tokenizer.truncate(
+                            &cypher_result,
+                            budget.prompt_tokens().saturating_sub(overhead),
+                        )
+                    }
+                    None => cypher_result,
+                };
+                let combined_request = analysis_request(&cypher_result);
                 info!("Combined request: {}", combined_request);
                 let request = Request {
                     flowname: engine_name.to_string(),
                     payload: combined_request,
+                    ..Default::default()
                 };
 
                 let response = Pin::from(engine.execute(&request)).await?;
@@ -446,6 +1213,12 @@ pub mod cli {
                 }
 
                 let response_time = start_time.elapsed().as_secs_f64();
+                let cost = response_cost(engine_config, &response);
+                if let Some(cost) = cost {
+                    if let Err(e) = log_cost(cost) {
+                        warn!("Failed to log request cost: {}", e);
+                    }
+                }
 
                 if let Some(neo4j_client) = engine.get_neo4j_client() {
                     let session_id = engine
@@ -461,6 +1234,7 @@ pub mod cli {
                             .finish_reason
                             .clone()
                             .unwrap_or_else(|| "unknown".to_string()),
+                        cost,
                     };
 
                     debug!("Attempting to create interaction in Neo4j");
@@ -493,12 +1267,12 @@ pub mod cli {
                 let response_time_str = format!("{:.2}s", response_time);
 
                 eprintln!(
-                    "{} | {} | Time: {} | Usage: {}↑ {}↓ {}Σ | {}\n",
+                    "{} | {} | Time: {} | Usage: {}↑ {}↓ {}Σ{} | {}\n",
                     spinner_config.success_symbol,
                     if use_colors {
-                        response.model.cyan().to_string()
+                        model_label(&response).cyan().to_string()
                     } else {
-                        response.model
+                        model_label(&response)
                     },
                     if use_colors {
                         response_time_str.bright_blue().to_string()
@@ -530,6 +1304,7 @@ pub mod cli {
                     } else {
                         response.usage.total_tokens.to_string()
                     },
+                    cost_label(cost, use_colors),
                     if use_colors {
                         response
                             .finish_reason
@@ -548,40 +1323,15 @@ pub mod cli {
             }
         } else if matches.get_flag("upsert") {
             debug!("Upsert mode enabled");
-            handle_upsert(engine_config, &matches).await?;
+            handle_upsert(engine_config, &matches, &pb).await?;
         } else {
             debug!("No mode specified, defaulting to interactive mode");
             let request = matches.get_one::<String>("request").unwrap();
 
-            let engine: Box<dyn Engine> = match engine_config.engine.as_str() {
-                "anthropic" => Box::new(AnthropicEngine::new(engine_config.clone()).await?),
-                "openai" => Box::new(OpenAIEngine::new(engine_config.clone()).await?),
-                "cohere" => Box::new(CohereEngine::new(engine_config.clone()).await?),
-                "google_gemini" => Box::new(GoogleGeminiEngine::new(engine_config.clone()).await?),
-                "mistral" => Box::new(MistralEngine::new(engine_config.clone()).await?),
-                "groq_lpu" => Box::new(GroqLPUEngine::new(engine_config.clone()).await?),
-                "perplexity" => Box::new(PerplexityEngine::new(engine_config.clone()).await?),
-                "webhook" => Box::new(WebhookEngine::new(engine_config.clone()).await?),
-                "flowise_chain" => Box::new(FlowiseChainEngine::new(engine_config.clone()).await?),
-                "langflow_chain" => Box::new(LangflowEngine::new(engine_config.clone()).await?),
-                "dalle" => Box::new(DalleEngine::new(engine_config.clone()).await?),
-                "stabilityai" => {
-                    let mut engine = Box::new(StabilityAIEngine::new(engine_config.clone()).await?);
-                    if let Some(download_dir) = matches.get_one::<String>("download-media") {
-                        engine.set_download_dir(download_dir.to_string());
-                    }
-                    engine
-                }
-                "leonardo_ai" => Box::new(LeonardoAIEngine::new(engine_config.clone()).await?),
-                "imagine_pro" => {
-                    let mut engine = Box::new(ImagineProEngine::new(engine_config.clone()).await?);
-                    if let Some(download_dir) = matches.get_one::<String>("download-media") {
-                        engine.set_download_dir(download_dir.to_string());
-                    }
-                    engine
-                }
-                _ => return Err(anyhow!("Unsupported engine: {}", engine_config.engine)),
-            };
+            let mut engine_config = engine_config.clone();
+            if let Some(download_dir) = matches.get_one::<String>("download-media") {
+                engine_config.download_dir = Some(download_dir.to_string());
+            }
 
             // Read context from stdin if available
             let context = if !io::stdin().is_terminal() {
@@ -617,16 +1367,110 @@ pub mod cli {
             let combined_request = combined_request_parts.join("\n\n----\n\n");
             debug!("Combined Request:\n{}", combined_request);
 
-            let request = Request {
+            // Load prior conversation turns if provided
+            let messages: Vec<Message> = match matches.get_one::<String>("messages") {
+                Some(file_path) => serde_json::from_str(&fs::read_to_string(file_path)?)?,
+                None => Vec::new(),
+            };
+
+            let mut request = Request {
                 flowname: engine_name.to_string(),
                 payload: combined_request,
+                messages,
+                ..Default::default()
+            };
+
+            let sources = if matches.get_flag("rag") {
+                let neo4j_config = engine_config
+                    .neo4j
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("--rag needs a Neo4j configuration for this engine"))?;
+                let top_k = matches
+                    .get_one::<usize>("top-k")
+                    .copied()
+                    .unwrap_or(DEFAULT_TOP_K);
+                pb.set_message("Retrieving sources...");
+                let neo4j_client = Neo4jClient::new(neo4j_config).await?;
+                neo4j_client.ensure_vector_index().await?;
+                let question = matches.get_one::<String>("request").unwrap();
+                let sources = neo4j_client
+                    .retrieve(question.trim(), top_k, &metadata_terms(&matches))
+                    .await?;
+                if sources.is_empty() {
+                    return Err(anyhow!(
+                        "No document chunks found for the question; upsert documents first"
+                    ));
+                }
+                add_sources(&mut request, &sources);
+                sources
+            } else {
+                Vec::new()
+            };
+
+            let upload_file = matches.get_one::<String>("upload-image-file");
+            let streamed = matches.get_flag("stream") && upload_file.is_none();
+
+            // The schema prompt and tool definitions count against the
+            // context window, so they are added before the request is fitted.
+            let schema = match matches.get_one::<String>("json-schema") {
+                Some(schema_path) => {
+                    let schema = fs::read_to_string(schema_path)?;
+                    Some(serde_json::from_str::<Value>(&schema)?)
+                }
+                _ => None,
             };
+            let registry = match matches.get_one::<String>("tools") {
+                Some(tools_path) => Some(ToolRegistry::from_file(Path::new(tools_path))?),
+                None => None,
+            };
+            if let Some(schema) = &schema {
+                request = with_schema(&request, schema)?;
+            }
+            if let Some(registry) = &registry {
+                registry.add_to(&mut request);
+            }
+
+            let estimate = fit_request(&mut request, &engine_config);
             debug!("Combined Request: {:?}", request);
 
-            let response = if let Some(file_path) = matches.get_one::<String>("upload-image-file") {
+            if matches.get_flag("dry-run") {
+                pb.finish_and_clear();
+                print_dry_run(&engine_config, &estimate);
+                if !sources.is_empty() {
+                    println!("Sources:\n{}", source_list(&sources));
+                }
+                return Ok(());
+            }
+            let engine = create_engine(&engine_config).await?;
+
+            let response = if let Some(file_path) = upload_file {
                 debug!("Processing request with file: {}", file_path);
                 pb.set_message("Processing request with file...");
                 Pin::from(engine.process_request_with_file(&request, Path::new(file_path))).await?
+            } else if streamed {
+                pb.set_message("Streaming request...");
+                let stream = Pin::from(engine.execute_stream(&request)).await?;
+                collect_stream(stream, |delta| {
+                    pb.finish_and_clear();
+                    print!("{}", delta);
+                    let _ = io::stdout().flush();
+                })
+                .await?
+            } else if let Some(schema) = &schema {
+                let max_repairs = matches
+                    .get_one::<usize>("max-repairs")
+                    .copied()
+                    .unwrap_or(DEFAULT_MAX_REPAIRS);
+                pb.set_message("Executing structured request...");
+                let structured =
+                    execute_structured(&*engine, &request, schema, max_repairs).await?;
+                Response {
+                    content: serde_json::to_string_pretty(&structured.value)?,
+                    ..structured.response
+                }
+            } else if let Some(registry) = &registry {
+                pb.set_message("Executing request with tools...");
+                execute_with_tools(&*engine, &request, registry, DEFAULT_MAX_TOOL_ROUNDS).await?
             } else {
                 pb.set_message("Executing request...");
                 Pin::from(engine.execute(&request)).await?
@@ -658,6 +1502,12 @@ pub mod cli {
             }
 
             let response_time = start_time.elapsed().as_secs_f64();
+            let cost = response_cost(&engine_config, &response);
+            if let Some(cost) = cost {
+                if let Err(e) = log_cost(cost) {
+                    warn!("Failed to log request cost: {}", e);
+                }
+            }
 
             if let Some(neo4j_client) = engine.get_neo4j_client() {
                 let session_id = engine
@@ -673,6 +1523,7 @@ pub mod cli {
                         .finish_reason
                         .clone()
                         .unwrap_or_else(|| "unknown".to_string()),
+                    cost,
                 };
 
                 debug!("Attempting to create interaction in Neo4j");
@@ -698,19 +1549,31 @@ pub mod cli {
             }
 
             pb.finish_and_clear();
-            eprintln!();
-            println!("{}", output);
+            if streamed {
+                // The raw content was already printed as it arrived; only
+                // print again if post-processing changed it.
+                println!();
+                if output != response.content {
+                    println!("{}", output);
+                }
+            } else {
+                eprintln!();
+                println!("{}", output);
+            }
+            if !sources.is_empty() {
+                println!("\nSources:\n{}", source_list(&sources));
+            }
 
             let use_colors = std::io::stderr().is_terminal();
             let response_time_str = format!("{:.2}s", response_time);
 
             eprintln!(
-                "{} | {} | Time: {} | Usage: {}↑ {}↓ {}Σ | {}\n",
+                "{} | {} | Time: {} | Usage: {}↑ {}↓ {}Σ{} | {}\n",
                 spinner_config.success_symbol,
                 if use_colors {
-                    response.model.cyan().to_string()
+                    model_label(&response).cyan().to_string()
                 } else {
-                    response.model
+                    model_label(&response)
                 },
                 if use_colors {
                     response_time_str.bright_blue().to_string()
@@ -742,6 +1605,7 @@ pub mod cli {
                 } else {
                     response.usage.total_tokens.to_string()
                 },
+                cost_label(cost, use_colors),
                 if use_colors {
                     response
                         .finish_reason
@@ -762,47 +1626,104 @@ pub mod cli {
         Ok(())
     }
 
-    async fn handle_upsert(engine_config: &EngineConfig, matches: &ArgMatches) -> Result<()> {
+    async fn handle_upsert(
+        engine_config: &EngineConfig,
+        matches: &ArgMatches,
+        pb: &ProgressBar,
+    ) -> Result<()> {
         if let Some(neo4j_config) = &engine_config.neo4j {
             let neo4j_client = Neo4jClient::new(neo4j_config).await?;
 
             let input = matches
                 .get_one::<String>("input")
                 .ok_or_else(|| anyhow!("Input is required for upsert mode"))?;
-            let metadata = matches
-                .get_one::<String>("metadata")
-                .map(|s| s.split(',').map(String::from).collect::<Vec<String>>())
-                .unwrap_or_default();
+            let metadata = metadata_terms(matches);
+            let chunking = chunking(&neo4j_client, matches)?;
+
+            let upsert = |path: PathBuf| {
+                let neo4j_client = &neo4j_client;
+                let metadata = &metadata;
+                let chunking = &chunking;
+                async move {
+                    let name = path.display().to_string();
+                    pb.set_message(format!("Embedding {}...", name));
+                    let on_progress = |done: usize, total: usize| {
+                        pb.set_message(format!("Embedding {}: {}/{} chunks", name, done, total));
+                    };
+                    neo4j_client
+                        .upsert_document_with_progress(&path, metadata, chunking, &on_progress)
+                        .await
+                }
+            };
+
+            let report = |outcome: &UpsertOutcome| {
+                let action = match outcome.status {
+                    UpsertStatus::Added => "Added",
+                    UpsertStatus::Updated => "Updated",
+                    UpsertStatus::Unchanged => "Unchanged",
+                };
+                pb.suspend(|| {
+                    eprintln!(
+                        "{} {} (document ID: {})",
+                        action, outcome.source, outcome.document_id
+                    )
+                });
+            };
 
             let input_path = Path::new(input);
+            let options = walk_options(matches)?;
+            let mut outcomes = Vec::new();
+            let mut removed = Vec::new();
+            let mut errors = Vec::new();
             if input_path.is_file() {
-                let document_id = neo4j_client.upsert_document(input_path, &metadata).await?;
-                eprintln!(
-                    "Uploaded document with ID: {}. Embeddings and chunks created.",
-                    document_id
-                );
+                check_file_size(input_path, options.max_file_size)?;
+                let outcome = upsert(input_path.to_path_buf()).await?;
+                report(&outcome);
+                outcomes.push(outcome);
             } else if input_path.is_dir() {
-                let mut uploaded_count = 0;
-                for entry in fs::read_dir(input_path)? {
-                    let entry = entry?;
-                    let path = entry.path();
-                    if path.is_file() {
-                        let document_id = neo4j_client.upsert_document(&path, &metadata).await?;
-                        eprintln!(
-                            "Uploaded document {} with ID: {}. Embeddings and chunks created.",
-                            path.display(),
-                            document_id
-                        );
-                        uploaded_count += 1;
+                let scan = collect_files(input_path, &options)?;
+                for (path, reason) in scan.skipped {
+                    pb.suspend(|| eprintln!("Skipped {}: {}", path.display(), reason));
+                    errors.push(format!("{}: {}", path.display(), reason));
+                }
+                // A file that fails is reported and the rest still upserted.
+                for path in scan.files {
+                    match upsert(path.clone()).await {
+                        Ok(outcome) => {
+                            report(&outcome);
+                            outcomes.push(outcome);
+                        }
+                        Err(e) => {
+                            pb.suspend(|| eprintln!("Failed {}: {:#}", path.display(), e));
+                            errors.push(format!("{}: {:#}", path.display(), e));
+                        }
                     }
                 }
-                eprintln!(
-                    "Uploaded {} documents with embeddings and chunks",
-                    uploaded_count
-                );
+                removed = neo4j_client.prune_documents(input_path).await?;
+                for source in &removed {
+                    pb.suspend(|| eprintln!("Removed {}", source));
+                }
             } else {
                 return Err(anyhow!("Input is neither a file nor a directory"));
             }
+            let count =
+                |status: UpsertStatus| outcomes.iter().filter(|o| o.status == status).count();
+            pb.suspend(|| {
+                eprintln!(
+                    "{} added, {} updated, {} unchanged, {} removed, {} failed",
+                    count(UpsertStatus::Added),
+                    count(UpsertStatus::Updated),
+                    count(UpsertStatus::Unchanged),
+                    removed.len(),
+                    errors.len()
+                )
+            });
+            pb.finish_and_clear();
+
+            let response = UpsertResponse {
+                processed_files: outcomes.iter().map(|o| o.source.clone()).collect(),
+                errors,
+            };
 
             if let Ok(stats) = neo4j_client.get_document_statistics().await {
                 eprintln!("\nDocument Statistics:");
@@ -811,6 +1732,13 @@ pub mod cli {
                 eprintln!("Total chunks: {}", stats.chunk_count);
                 eprintln!("Total embeddings: {}", stats.embedding_count);
             }
+
+            // The machine-readable report goes to stdout, after the messages
+            // above on stderr.
+            let mut report = serde_json::to_value(&response)?;
+            report["documents"] = serde_json::to_value(&outcomes)?;
+            report["removed"] = serde_json::to_value(&removed)?;
+            println!("{}", serde_json::to_string_pretty(&report)?);
         } else {
             return Err(anyhow!("Neo4j configuration not found for this engine"));
         }
@@ -826,20 +1754,98 @@ pub mod cli {
                 "Generate a Cypher query for Neo4j based on this request: {}",
                 query
             ),
+            ..Default::default()
         };
         debug!("Sending request to LLM engine: {:?}", llm_request);
-        let llm_engine: Box<dyn Engine> = match config.engine.as_str() {
-            "openai" => Box::new(OpenAIEngine::new(config.clone()).await?),
-            "anthropic" => Box::new(AnthropicEngine::new(config.clone()).await?),
-            // Add other LLM engines as needed
-            _ => return Err(anyhow!("Unsupported LLM engine for Cypher generation")),
-        };
+        let llm_engine = create_engine(config).await?;
 
         let response = Pin::from(llm_engine.execute(&llm_request)).await?;
 
         debug!("Response from LLM engine: {:?}", response);
         Ok(response.content)
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn engine_error(error: EngineError) -> Error {
+            Error::new(error).context("Request to engine failed")
+        }
+
+        #[test]
+        fn exit_codes_name_the_failure() {
+            let message = || "failed".to_string();
+            let provider = || "test".to_string();
+            let cases = [
+                (
+                    EngineError::Auth {
+                        provider: provider(),
+                        message: message(),
+                    },
+                    10,
+                ),
+                (
+                    EngineError::RateLimited {
+                        provider: provider(),
+                        retry_after: Some(Duration::from_secs(1)),
+                        message: message(),
+                    },
+                    11,
+                ),
+                (
+                    EngineError::QuotaExceeded {
+                        provider: provider(),
+                        message: message(),
+                    },
+                    12,
+                ),
+                (
+                    EngineError::ContextTooLong {
+                        provider: provider(),
+                        message: message(),
+                    },
+                    13,
+                ),
+                (EngineError::content_filtered("test", "failed"), 14),
+                (EngineError::transport("test", "failed"), 15),
+                (EngineError::malformed("test", "failed"), 16),
+                (
+                    EngineError::Provider {
+                        provider: provider(),
+                        status: Some(500),
+                        message: message(),
+                    },
+                    17,
+                ),
+            ];
+
+            for (error, code) in cases {
+                let description = error.to_string();
+                assert_eq!(
+                    exit_code(&Error::new(error.clone())),
+                    code,
+                    "{}",
+                    description
+                );
+                assert_eq!(exit_code(&engine_error(error)), code, "{}", description);
+            }
+        }
+
+        #[test]
+        fn schema_failures_and_other_errors_have_their_own_codes() {
+            let invalid = SchemaValidationError {
+                attempts: 3,
+                errors: vec!["missing field `name`".to_string()],
+                last_content: "{}".to_string(),
+            };
+            assert_eq!(
+                exit_code(&Error::new(invalid).context("Structured output")),
+                18
+            );
+            assert_eq!(exit_code(&anyhow!("No such file or directory")), 1);
+        }
+    }
 }
 
 async fn generate_and_execute_cypher(
@@ -864,6 +1870,7 @@ async fn generate_and_execute_cypher(
             "Given the following database schema:\n\n{}\n\nGenerate a Cypher query for Neo4j based on this request: {}",
             schema, query_string
         ),
+        ..Default::default()
     };
     //info!("Sending request to LLM engine: {:?}", cypher_request);
     let cypher_response = Pin::from(llm_engine.execute(&cypher_request)).await?;
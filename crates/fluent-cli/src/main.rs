@@ -1,6 +1,9 @@
 use fluent_cli::cli;
 
 #[tokio::main]
-async fn main() -> anyhow::Result<()> {
-    cli::run().await
-}
\ No newline at end of file
+async fn main() {
+    if let Err(e) = cli::run().await {
+        eprintln!("Error: {:?}", e);
+        std::process::exit(cli::exit_code(&e));
+    }
+}
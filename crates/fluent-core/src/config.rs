@@ -1,5 +1,8 @@
+use crate::error::ErrorClass;
 use crate::neo4j_client::VoyageAIConfig;
+use crate::pricing::ModelPrice;
 use crate::spinner_configuration::SpinnerConfig;
+use crate::utils::chunking::ChunkingConfig;
 use anyhow::{anyhow, Context, Result};
 use log::debug;
 use serde::{Deserialize, Serialize};
@@ -13,11 +16,115 @@ use std::{env, fs};
 pub struct EngineConfig {
     pub name: String,
     pub engine: String,
+    #[serde(default)]
     pub connection: ConnectionConfig,
+    #[serde(default)]
     pub parameters: HashMap<String, serde_json::Value>,
     pub session_id: Option<String>, // New field for sessionID
     pub neo4j: Option<Neo4jConfig>,
     pub spinner: Option<SpinnerConfig>,
+    pub retry: Option<RetryConfig>,
+    pub rate_limit: Option<RateLimitConfig>,
+    pub cache: Option<CacheConfig>,
+    pub fallback: Option<FallbackConfig>,
+    /// Where image engines save generated files; the CLI sets it from `--download-media`.
+    pub download_dir: Option<String>,
+    /// Cassette file to record request/response pairs to, for the `replay` engine.
+    pub record: Option<String>,
+    /// Overrides the built-in price of the engine's model.
+    pub pricing: Option<ModelPrice>,
+}
+
+impl EngineConfig {
+    /// Turns off the response cache for the engine and its fallback members.
+    pub fn disable_cache(&mut self) {
+        self.cache = None;
+        if let Some(fallback) = &mut self.fallback {
+            for member in &mut fallback.members {
+                member.disable_cache();
+            }
+        }
+    }
+}
+
+/// Retries failed requests with exponential backoff and jitter.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// Total attempts, including the first request.
+    pub max_attempts: u32,
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+    pub retry_on: Vec<ErrorClass>,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        RetryConfig {
+            max_attempts: 3,
+            base_delay_ms: 1000,
+            max_delay_ms: 30_000,
+            retry_on: vec![
+                ErrorClass::RateLimited,
+                ErrorClass::Transport,
+                ErrorClass::Server,
+            ],
+        }
+    }
+}
+
+/// Client-side limits, shared by every engine with the same `name` in the process.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    pub requests_per_minute: Option<u32>,
+    /// Counted from the prompt size before sending, then corrected with the
+    /// usage the provider reports.
+    pub tokens_per_minute: Option<u32>,
+    pub max_in_flight: Option<usize>,
+}
+
+/// Caches responses on disk, keyed by the engine parameters and the request.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct CacheConfig {
+    pub ttl_seconds: u64,
+    /// Size limit for the whole cache directory; least recently used entries
+    /// are evicted past it.
+    pub max_size_mb: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        CacheConfig {
+            ttl_seconds: 24 * 60 * 60,
+            max_size_mb: 256,
+        }
+    }
+}
+
+/// Settings for a `fallback` engine, which tries other engines in order.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct FallbackConfig {
+    /// Names of the engines to try, first to last.
+    pub engines: Vec<String>,
+    /// Errors that move on to the next engine; anything else is returned.
+    #[serde(default = "FallbackConfig::default_fall_through_on")]
+    pub fall_through_on: Vec<ErrorClass>,
+    /// The configs of `engines`, filled in by `load_engine_config`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<EngineConfig>,
+}
+
+impl FallbackConfig {
+    fn default_fall_through_on() -> Vec<ErrorClass> {
+        vec![
+            ErrorClass::RateLimited,
+            ErrorClass::QuotaExceeded,
+            ErrorClass::Transport,
+            ErrorClass::Server,
+        ]
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -26,12 +133,71 @@ pub struct Neo4jConfig {
     pub user: String,
     pub password: String,
     pub database: String,
+    /// The service that embeds questions, responses and document chunks.
+    pub embedding: Option<EmbeddingConfig>,
+    /// Older form of `embedding` for Voyage AI, used when `embedding` is not set.
     pub voyage_ai: Option<VoyageAIConfig>,
+    /// How upserted documents are split into chunks.
+    #[serde(default)]
+    pub chunking: Option<ChunkingConfig>,
     pub query_llm: Option<String>,
     pub parameters: Option<HashMap<String, serde_json::Value>>,
 }
 
+impl Neo4jConfig {
+    /// The embedding settings, from `embedding` or else `voyage_ai`.
+    pub fn embedding_config(&self) -> Option<EmbeddingConfig> {
+        self.embedding.clone().or_else(|| {
+            self.voyage_ai.as_ref().map(|voyage| EmbeddingConfig {
+                provider: EmbeddingProviderKind::Voyage,
+                model: voyage.model.clone(),
+                api_key: Some(voyage.api_key.clone()),
+                base_url: None,
+                dimensions: None,
+                batch_size: None,
+                concurrency: None,
+            })
+        })
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddingProviderKind {
+    Voyage,
+    #[serde(rename = "openai")]
+    OpenAI,
+    Cohere,
+    Mistral,
+    /// A local or self-hosted server with an OpenAI-style `/embeddings`
+    /// endpoint, such as Ollama, llama.cpp or vLLM.
+    #[serde(rename = "openai_compatible")]
+    OpenAICompatible,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct EmbeddingConfig {
+    pub provider: EmbeddingProviderKind,
+    pub model: String,
+    /// Required for every provider except `openai_compatible`.
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Replaces the provider's API base URL, e.g. `http://localhost:11434/v1`.
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// The vector size. Set it for models the built-in table does not know,
+    /// or to ask a model that can shorten its vectors for fewer dimensions.
+    #[serde(default)]
+    pub dimensions: Option<usize>,
+    /// Texts per embedding request; defaults to a size within the provider's limits.
+    #[serde(default)]
+    pub batch_size: Option<usize>,
+    /// Embedding requests in flight at once while upserting (default 4).
+    #[serde(default)]
+    pub concurrency: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
 pub struct ConnectionConfig {
     pub protocol: String,
     pub hostname: String,
@@ -70,6 +236,18 @@ pub fn load_engine_config(
     engine_name: &str,
     overrides: &HashMap<String, Value>,
     credentials: &HashMap<String, String>,
+) -> Result<EngineConfig> {
+    load_engine_config_chain(config_content, engine_name, overrides, credentials, &mut Vec::new())
+}
+
+/// Loads one engine, then the members of a fallback engine. `chain` holds
+/// the fallback engines being loaded, to reject a chain that includes itself.
+fn load_engine_config_chain(
+    config_content: &str,
+    engine_name: &str,
+    overrides: &HashMap<String, Value>,
+    credentials: &HashMap<String, String>,
+    chain: &mut Vec<String>,
 ) -> Result<EngineConfig> {
     //Converts the string into a json value to be manipulated
     let mut config: Value = serde_json::from_str(config_content)?;
@@ -113,7 +291,31 @@ pub fn load_engine_config(
 
     debug!("Loaded and processed config for engine: {}", engine_name);
 
-    serde_json::from_value(engine_config.clone()).context("Could not parse engine config")
+    let mut engine_config: EngineConfig =
+        serde_json::from_value(engine_config.clone()).context("Could not parse engine config")?;
+
+    if let Some(fallback) = engine_config.fallback.as_mut() {
+        chain.push(engine_name.to_string());
+        for member in &fallback.engines {
+            if chain.contains(member) {
+                return Err(anyhow!(
+                    "Fallback engine '{}' includes itself through '{}'",
+                    engine_name,
+                    member
+                ));
+            }
+            fallback.members.push(load_engine_config_chain(
+                config_content,
+                member,
+                overrides,
+                credentials,
+                chain,
+            )?);
+        }
+        chain.pop();
+    }
+
+    Ok(engine_config)
 }
 
 fn apply_variable_resolver(
@@ -0,0 +1,347 @@
+// crates/fluent-core/src/embeddings.rs
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use log::debug;
+use reqwest::Client;
+use serde_json::{json, Value};
+
+use crate::config::{EmbeddingConfig, EmbeddingProviderKind};
+use crate::error::{EngineError, RequestBuilderExt};
+
+/// Vector sizes by model name prefix; the first match wins, so longer
+/// prefixes come first.
+const DIMENSIONS: &[(&str, usize)] = &[
+    ("voyage-3-lite", 512),
+    ("voyage-large-2", 1536),
+    ("voyage-code-2", 1536),
+    ("voyage", 1024),
+    ("text-embedding-3-large", 3072),
+    ("text-embedding-3-small", 1536),
+    ("text-embedding-ada-002", 1536),
+    ("embed-english-light", 384),
+    ("embed-multilingual-light", 384),
+    ("embed-v4", 1536),
+    ("embed-", 1024),
+    ("mistral-embed", 1024),
+    ("codestral-embed", 1536),
+    ("nomic-embed-text", 768),
+    ("mxbai-embed-large", 1024),
+    ("snowflake-arctic-embed", 1024),
+    ("all-minilm", 384),
+    ("bge-m3", 1024),
+    ("bge-large", 1024),
+];
+
+/// The vector size of a known embedding model, matched on the name without
+/// any `vendor/` prefix or `:tag` suffix.
+pub fn model_dimension(model: &str) -> Option<usize> {
+    let model = model.rsplit('/').next().unwrap_or(model).to_lowercase();
+    let model = model.split(':').next().unwrap_or_default();
+    DIMENSIONS
+        .iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+        .map(|&(_, dimension)| dimension)
+}
+
+/// Turns text into vectors for the Neo4j vector index.
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    /// The model name, stored on each `Embedding` node.
+    fn model(&self) -> &str;
+
+    /// The length of the vectors `embed` returns, if known without asking
+    /// the provider.
+    fn dimension(&self) -> Option<usize>;
+
+    /// The most texts to send in one `embed_batch` call.
+    fn max_batch_size(&self) -> usize;
+
+    /// Embeds several texts in one request, returning their vectors in order.
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        self.embed_batch(&[text.to_string()])
+            .await?
+            .pop()
+            .ok_or_else(|| anyhow!("Embedding provider returned no vectors"))
+    }
+
+    /// Embeds a search query, for providers that embed queries and stored
+    /// documents differently.
+    async fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
+        self.embed(text).await
+    }
+}
+
+/// The vector size of a provider's model, embedding a short text to find
+/// out when the model is not in the built-in table.
+pub async fn resolve_dimension(provider: &dyn EmbeddingProvider) -> Result<usize> {
+    match provider.dimension() {
+        Some(dimension) => Ok(dimension),
+        None => Ok(provider.embed("dimension probe").await?.len()),
+    }
+}
+
+/// Builds the provider selected by an `embedding` config.
+pub fn create_embedding_provider(config: &EmbeddingConfig) -> Result<Box<dyn EmbeddingProvider>> {
+    let api_key = || {
+        config
+            .api_key
+            .clone()
+            .ok_or_else(|| anyhow!("{:?} embeddings need an api_key", config.provider))
+    };
+    let native = model_dimension(&config.model);
+    let dimension = config.dimensions.or(native);
+    // Only ask for a size other than the model's own; for models not in the
+    // table, `dimensions` just declares the size.
+    let requested_dimension = config
+        .dimensions
+        .filter(|&dimension| native.is_some_and(|native| native != dimension));
+    let base_url = |default: &str| {
+        config
+            .base_url
+            .as_deref()
+            .unwrap_or(default)
+            .trim_end_matches('/')
+            .to_string()
+    };
+
+    // With chunks of the default 1,000 tokens, batches also stay under each
+    // provider's per-request token limit.
+    let batch_size = |default: usize| config.batch_size.unwrap_or(default).max(1);
+
+    let provider: Box<dyn EmbeddingProvider> = match config.provider {
+        EmbeddingProviderKind::Voyage => Box::new(OpenAIStyleEmbeddings {
+            provider: "Voyage AI",
+            url: format!("{}/embeddings", base_url("https://api.voyageai.com/v1")),
+            api_key: Some(api_key()?),
+            model: config.model.clone(),
+            dimension,
+            dimension_field: "output_dimension",
+            requested_dimension,
+            batch_size: batch_size(64),
+            client: Client::new(),
+        }),
+        EmbeddingProviderKind::OpenAI => Box::new(OpenAIStyleEmbeddings {
+            provider: "OpenAI",
+            url: format!("{}/embeddings", base_url("https://api.openai.com/v1")),
+            api_key: Some(api_key()?),
+            model: config.model.clone(),
+            dimension,
+            dimension_field: "dimensions",
+            requested_dimension,
+            batch_size: batch_size(128),
+            client: Client::new(),
+        }),
+        EmbeddingProviderKind::Mistral => Box::new(OpenAIStyleEmbeddings {
+            provider: "Mistral",
+            url: format!("{}/embeddings", base_url("https://api.mistral.ai/v1")),
+            api_key: Some(api_key()?),
+            model: config.model.clone(),
+            dimension,
+            dimension_field: "output_dimension",
+            requested_dimension,
+            batch_size: batch_size(8),
+            client: Client::new(),
+        }),
+        EmbeddingProviderKind::OpenAICompatible => Box::new(OpenAIStyleEmbeddings {
+            provider: "OpenAI-compatible",
+            url: format!("{}/embeddings", base_url("http://localhost:11434/v1")),
+            api_key: config.api_key.clone(),
+            model: config.model.clone(),
+            dimension,
+            dimension_field: "dimensions",
+            // Local servers size vectors by model and may reject the field.
+            requested_dimension: None,
+            batch_size: batch_size(32),
+            client: Client::new(),
+        }),
+        EmbeddingProviderKind::Cohere => Box::new(CohereEmbeddings {
+            url: format!("{}/embed", base_url("https://api.cohere.com/v2")),
+            api_key: api_key()?,
+            model: config.model.clone(),
+            dimension,
+            requested_dimension,
+            batch_size: batch_size(96),
+            client: Client::new(),
+        }),
+    };
+    debug!(
+        "Embedding with {:?} model {} ({} dimensions)",
+        config.provider,
+        provider.model(),
+        provider
+            .dimension()
+            .map_or_else(|| "unknown".to_string(), |d| d.to_string())
+    );
+    Ok(provider)
+}
+
+fn parse_vector(provider: &str, value: &Value) -> Result<Vec<f32>, EngineError> {
+    value
+        .as_array()
+        .ok_or_else(|| EngineError::malformed(provider, "missing embedding"))?
+        .iter()
+        .map(|x| {
+            x.as_f64()
+                .map(|x| x as f32)
+                .ok_or_else(|| EngineError::malformed(provider, "non-numeric embedding value"))
+        })
+        .collect()
+}
+
+/// Checks that a response has one vector per input, each of the expected size.
+fn check_vectors(
+    provider: &str,
+    expected: Option<usize>,
+    inputs: usize,
+    vectors: &[Vec<f32>],
+) -> Result<()> {
+    if vectors.len() != inputs {
+        return Err(EngineError::malformed(
+            provider,
+            format!("{} embeddings for {} inputs", vectors.len(), inputs),
+        )
+        .into());
+    }
+    match (expected, vectors.iter().find(|v| Some(v.len()) != expected)) {
+        (Some(expected), Some(vector)) => Err(EngineError::malformed(
+            provider,
+            format!(
+                "embedding has {} dimensions, expected {}",
+                vector.len(),
+                expected
+            ),
+        )
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+/// The `/embeddings` API shared by OpenAI, Voyage AI, Mistral and local
+/// OpenAI-style servers: `{model, input}` in, `data[].embedding` out.
+struct OpenAIStyleEmbeddings {
+    provider: &'static str,
+    url: String,
+    api_key: Option<String>,
+    model: String,
+    dimension: Option<usize>,
+    /// The request field that asks for shorter vectors.
+    dimension_field: &'static str,
+    requested_dimension: Option<usize>,
+    batch_size: usize,
+    client: Client,
+}
+
+#[async_trait]
+impl EmbeddingProvider for OpenAIStyleEmbeddings {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn dimension(&self) -> Option<usize> {
+        self.dimension
+    }
+
+    fn max_batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut body = json!({
+            "model": self.model,
+            "input": texts,
+        });
+        if let Some(dimension) = self.requested_dimension {
+            body[self.dimension_field] = json!(dimension);
+        }
+
+        let mut builder = self.client.post(&self.url).json(&body);
+        if let Some(api_key) = &self.api_key {
+            builder = builder.bearer_auth(api_key);
+        }
+        let response = builder.send_json(self.provider).await?;
+
+        let mut data = response["data"]
+            .as_array()
+            .cloned()
+            .ok_or_else(|| EngineError::malformed(self.provider, "missing data"))?;
+        data.sort_by_key(|item| item["index"].as_u64());
+        let vectors = data
+            .iter()
+            .map(|item| parse_vector(self.provider, &item["embedding"]))
+            .collect::<Result<Vec<_>, _>>()?;
+        check_vectors(self.provider, self.dimension, texts.len(), &vectors)?;
+        Ok(vectors)
+    }
+}
+
+/// Cohere's v2 `/embed` API, which needs an `input_type`.
+struct CohereEmbeddings {
+    url: String,
+    api_key: String,
+    model: String,
+    dimension: Option<usize>,
+    requested_dimension: Option<usize>,
+    batch_size: usize,
+    client: Client,
+}
+
+const COHERE: &str = "Cohere";
+
+#[async_trait]
+impl EmbeddingProvider for CohereEmbeddings {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn dimension(&self) -> Option<usize> {
+        self.dimension
+    }
+
+    fn max_batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        self.embed_as(texts, "search_document").await
+    }
+
+    async fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
+        self.embed_as(&[text.to_string()], "search_query")
+            .await?
+            .pop()
+            .ok_or_else(|| anyhow!("Embedding provider returned no vectors"))
+    }
+}
+
+impl CohereEmbeddings {
+    async fn embed_as(&self, texts: &[String], input_type: &str) -> Result<Vec<Vec<f32>>> {
+        let mut body = json!({
+            "model": self.model,
+            "texts": texts,
+            "input_type": input_type,
+            "embedding_types": ["float"],
+        });
+        if let Some(dimension) = self.requested_dimension {
+            body["output_dimension"] = json!(dimension);
+        }
+
+        let response = self
+            .client
+            .post(&self.url)
+            .bearer_auth(&self.api_key)
+            .json(&body)
+            .send_json(COHERE)
+            .await?;
+
+        let vectors = response["embeddings"]["float"]
+            .as_array()
+            .ok_or_else(|| EngineError::malformed(COHERE, "missing embeddings"))?
+            .iter()
+            .map(|vector| parse_vector(COHERE, vector))
+            .collect::<Result<Vec<_>, _>>()?;
+        check_vectors(COHERE, self.dimension, texts.len(), &vectors)?;
+        Ok(vectors)
+    }
+}
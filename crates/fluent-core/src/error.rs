@@ -0,0 +1,634 @@
+// crates/fluent-core/src/error.rs
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use reqwest::header::HeaderMap;
+use reqwest::{RequestBuilder, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Why a request to a provider failed, classified from the HTTP status and
+/// error body so callers can tell a rate limit from a bad key.
+///
+/// Engines return it inside `anyhow::Error`, possibly wrapped in context; use
+/// `EngineError::find` to inspect it.
+#[derive(Debug, Clone)]
+pub enum EngineError {
+    /// The API key is missing, invalid or not allowed to use the model.
+    Auth { provider: String, message: String },
+    /// Too many requests. `retry_after` is set when the provider says how long to wait.
+    RateLimited {
+        provider: String,
+        retry_after: Option<Duration>,
+        message: String,
+    },
+    /// The account is out of credits or over its usage quota.
+    QuotaExceeded { provider: String, message: String },
+    /// The prompt and requested output do not fit the model's context window.
+    ContextTooLong { provider: String, message: String },
+    /// The provider refused the prompt or stopped the output on safety grounds.
+    ContentFiltered { provider: String, message: String },
+    /// No response was received: connection, TLS or timeout failure.
+    Transport { provider: String, message: String },
+    /// A response was received but could not be parsed or lacked expected fields.
+    MalformedResponse { provider: String, message: String },
+    /// Any other error reported by the provider.
+    Provider {
+        provider: String,
+        status: Option<u16>,
+        message: String,
+    },
+}
+
+/// The kind of an `EngineError`, as named in config files (`retry_on`,
+/// `fall_through_on`).
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorClass {
+    Auth,
+    RateLimited,
+    QuotaExceeded,
+    ContextTooLong,
+    ContentFiltered,
+    /// Connection, TLS and timeout failures.
+    Transport,
+    MalformedResponse,
+    /// 5xx, 408 and 409 responses, including provider overload.
+    Server,
+    /// Any other error reported by the provider.
+    Provider,
+}
+
+const CONTEXT_MARKERS: &[&str] = &[
+    "context_length_exceeded",
+    "context length",
+    "maximum context",
+    "context window",
+    "prompt is too long",
+    "input is too long",
+    "too many tokens",
+    "exceeds the maximum number of tokens",
+];
+
+const CONTENT_MARKERS: &[&str] = &[
+    "content_filter",
+    "content_policy",
+    "content management policy",
+    "responsible ai",
+    "moderation",
+    "flagged",
+    "safety",
+];
+
+const QUOTA_MARKERS: &[&str] = &[
+    "insufficient_quota",
+    "quota",
+    "billing",
+    "credit balance",
+    "payment required",
+];
+
+const AUTH_MARKERS: &[&str] = &[
+    "invalid_api_key",
+    "invalid api key",
+    "authentication",
+    "unauthorized",
+    "permission_denied",
+];
+
+impl EngineError {
+    /// The engine error behind `error`, which may be wrapped in context.
+    pub fn find(error: &anyhow::Error) -> Option<&EngineError> {
+        error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<EngineError>())
+    }
+
+    pub fn transport(provider: &str, error: impl fmt::Display) -> Self {
+        EngineError::Transport {
+            provider: provider.to_string(),
+            message: error.to_string(),
+        }
+    }
+
+    pub fn malformed(provider: &str, message: impl Into<String>) -> Self {
+        EngineError::MalformedResponse {
+            provider: provider.to_string(),
+            message: message.into(),
+        }
+    }
+
+    pub fn content_filtered(provider: &str, message: impl Into<String>) -> Self {
+        EngineError::ContentFiltered {
+            provider: provider.to_string(),
+            message: message.into(),
+        }
+    }
+
+    /// Classifies a non-success HTTP response.
+    pub fn from_response_parts(
+        provider: &str,
+        status: StatusCode,
+        headers: &HeaderMap,
+        body: &str,
+    ) -> Self {
+        let value: Value = serde_json::from_str(body).unwrap_or_else(|_| Value::String(body.to_string()));
+        let retry_after = retry_after_header(headers).or_else(|| retry_after_body(&value));
+        classify(provider, Some(status), retry_after, &value)
+    }
+
+    /// Classifies an error object returned in a response body, which some
+    /// providers send with a success status or in the middle of a stream.
+    pub fn from_error_body(provider: &str, body: &Value) -> Self {
+        let status = body["error"]["code"]
+            .as_u64()
+            .or_else(|| body["status"].as_u64())
+            .and_then(|code| StatusCode::from_u16(code as u16).ok());
+        classify(provider, status, retry_after_body(body), body)
+    }
+
+    pub fn provider(&self) -> &str {
+        match self {
+            EngineError::Auth { provider, .. }
+            | EngineError::RateLimited { provider, .. }
+            | EngineError::QuotaExceeded { provider, .. }
+            | EngineError::ContextTooLong { provider, .. }
+            | EngineError::ContentFiltered { provider, .. }
+            | EngineError::Transport { provider, .. }
+            | EngineError::MalformedResponse { provider, .. }
+            | EngineError::Provider { provider, .. } => provider,
+        }
+    }
+
+    /// Whether the same request may succeed if sent again later.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            EngineError::RateLimited { .. } | EngineError::Transport { .. } => true,
+            EngineError::Provider { status: Some(status), .. } => {
+                *status >= 500 || *status == 408 || *status == 409
+            }
+            _ => false,
+        }
+    }
+
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            EngineError::Auth { .. } => ErrorClass::Auth,
+            EngineError::RateLimited { .. } => ErrorClass::RateLimited,
+            EngineError::QuotaExceeded { .. } => ErrorClass::QuotaExceeded,
+            EngineError::ContextTooLong { .. } => ErrorClass::ContextTooLong,
+            EngineError::ContentFiltered { .. } => ErrorClass::ContentFiltered,
+            EngineError::Transport { .. } => ErrorClass::Transport,
+            EngineError::MalformedResponse { .. } => ErrorClass::MalformedResponse,
+            EngineError::Provider { .. } if self.is_retryable() => ErrorClass::Server,
+            EngineError::Provider { .. } => ErrorClass::Provider,
+        }
+    }
+
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            EngineError::RateLimited { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EngineError::Auth { provider, message } => {
+                write!(f, "{} authentication failed: {}", provider, message)
+            }
+            EngineError::RateLimited {
+                provider,
+                retry_after,
+                message,
+            } => match retry_after {
+                Some(delay) => write!(
+                    f,
+                    "{} rate limit exceeded (retry after {:.1}s): {}",
+                    provider,
+                    delay.as_secs_f64(),
+                    message
+                ),
+                None => write!(f, "{} rate limit exceeded: {}", provider, message),
+            },
+            EngineError::QuotaExceeded { provider, message } => {
+                write!(f, "{} quota exceeded: {}", provider, message)
+            }
+            EngineError::ContextTooLong { provider, message } => {
+                write!(f, "{} context length exceeded: {}", provider, message)
+            }
+            EngineError::ContentFiltered { provider, message } => {
+                write!(f, "{} blocked the content: {}", provider, message)
+            }
+            EngineError::Transport { provider, message } => {
+                write!(f, "{} request failed: {}", provider, message)
+            }
+            EngineError::MalformedResponse { provider, message } => {
+                write!(f, "Malformed {} response: {}", provider, message)
+            }
+            EngineError::Provider {
+                provider,
+                status: Some(status),
+                message,
+            } => write!(f, "{} API error ({}): {}", provider, status, message),
+            EngineError::Provider {
+                provider, message, ..
+            } => write!(f, "{} API error: {}", provider, message),
+        }
+    }
+}
+
+impl std::error::Error for EngineError {}
+
+/// Pulls a readable message and the provider's error type/code out of an
+/// error body. Handles the OpenAI, Anthropic, Gemini, Mistral and Cohere shapes.
+fn error_details(body: &Value) -> (String, String) {
+    // Gemini streams errors as the first element of an array.
+    let body = body.as_array().and_then(|items| items.first()).unwrap_or(body);
+    let error = body.get("error").filter(|e| !e.is_null()).unwrap_or(body);
+
+    let message = error["message"]
+        .as_str()
+        .or_else(|| error.as_str())
+        .or_else(|| body["message"].as_str())
+        .or_else(|| body["detail"].as_str())
+        .map(String::from)
+        .unwrap_or_else(|| body.to_string());
+
+    let code = ["type", "code", "status"]
+        .iter()
+        .filter_map(|key| match &error[*key] {
+            Value::String(s) => Some(s.clone()),
+            Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    (message, code)
+}
+
+fn classify(
+    provider: &str,
+    status: Option<StatusCode>,
+    retry_after: Option<Duration>,
+    body: &Value,
+) -> EngineError {
+    let (message, code) = error_details(body);
+    let text = format!("{} {}", code, message).to_lowercase();
+    let mentions = |markers: &[&str]| markers.iter().any(|marker| text.contains(marker));
+    let provider = provider.to_string();
+
+    // Gemini reports per-minute limits as RESOURCE_EXHAUSTED with a message about quota.
+    let exhausted = text.contains("resource_exhausted");
+
+    if mentions(CONTEXT_MARKERS) {
+        EngineError::ContextTooLong { provider, message }
+    } else if mentions(CONTENT_MARKERS) {
+        EngineError::ContentFiltered { provider, message }
+    } else if status == Some(StatusCode::PAYMENT_REQUIRED) || (mentions(QUOTA_MARKERS) && !exhausted) {
+        EngineError::QuotaExceeded { provider, message }
+    } else if status == Some(StatusCode::TOO_MANY_REQUESTS) || exhausted || text.contains("rate limit") || text.contains("rate_limit") {
+        EngineError::RateLimited {
+            provider,
+            retry_after,
+            message,
+        }
+    } else if matches!(status, Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN)) || mentions(AUTH_MARKERS) {
+        EngineError::Auth { provider, message }
+    } else {
+        // Anthropic reports overload as an `overloaded_error` event, sometimes mid-stream.
+        let overloaded = text.contains("overloaded").then_some(529);
+        EngineError::Provider {
+            provider,
+            status: status.map(|s| s.as_u16()).or(overloaded),
+            message,
+        }
+    }
+}
+
+/// Reads `retry-after-ms`, or `retry-after` as seconds or an HTTP date.
+fn retry_after_header(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).map(str::trim);
+
+    if let Some(ms) = header("retry-after-ms").and_then(|v| v.parse::<f64>().ok()) {
+        return Some(Duration::from_secs_f64(ms.max(0.0) / 1000.0));
+    }
+    let value = header("retry-after")?;
+    if let Ok(seconds) = value.parse::<f64>() {
+        return Some(Duration::from_secs_f64(seconds.max(0.0)));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    (date.with_timezone(&Utc) - Utc::now()).to_std().ok()
+}
+
+/// Reads Gemini's `RetryInfo` detail, e.g. `"retryDelay": "30s"`.
+fn retry_after_body(body: &Value) -> Option<Duration> {
+    body["error"]["details"]
+        .as_array()?
+        .iter()
+        .find_map(|detail| detail["retryDelay"].as_str())
+        .and_then(|delay| delay.trim_end_matches('s').parse::<f64>().ok())
+        .map(|seconds| Duration::from_secs_f64(seconds.max(0.0)))
+}
+
+/// Passes through a successful response and turns any other status into an
+/// `EngineError` built from the response body.
+pub async fn check_response(
+    provider: &str,
+    response: reqwest::Response,
+) -> Result<reqwest::Response, EngineError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let headers = response.headers().clone();
+    let body = response
+        .text()
+        .await
+        .map_err(|e| EngineError::transport(provider, e))?;
+    log::debug!("{} error response ({}): {}", provider, status, body);
+    Err(EngineError::from_response_parts(provider, status, &headers, &body))
+}
+
+/// Sends a request and checks its status.
+pub async fn send(provider: &str, request: RequestBuilder) -> Result<reqwest::Response, EngineError> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| EngineError::transport(provider, e))?;
+    check_response(provider, response).await
+}
+
+/// Reads a response body as JSON, failing on an `error` object in the body.
+pub async fn read_json(provider: &str, response: reqwest::Response) -> Result<Value, EngineError> {
+    let text = response
+        .text()
+        .await
+        .map_err(|e| EngineError::transport(provider, e))?;
+    let body: Value = serde_json::from_str(&text)
+        .map_err(|e| EngineError::malformed(provider, format!("{}: {}", e, text)))?;
+    if body.get("error").is_some_and(|error| !error.is_null()) {
+        return Err(EngineError::from_error_body(provider, &body));
+    }
+    Ok(body)
+}
+
+/// Chainable forms of `send` and `read_json` for engine request builders.
+pub trait RequestBuilderExt {
+    fn send_checked<'a>(
+        self,
+        provider: &'a str,
+    ) -> impl Future<Output = Result<reqwest::Response, EngineError>> + Send + 'a;
+
+    fn send_json<'a>(self, provider: &'a str) -> impl Future<Output = Result<Value, EngineError>> + Send + 'a;
+}
+
+impl RequestBuilderExt for RequestBuilder {
+    async fn send_checked(self, provider: &str) -> Result<reqwest::Response, EngineError> {
+        send(provider, self).await
+    }
+
+    async fn send_json(self, provider: &str) -> Result<Value, EngineError> {
+        read_json(provider, send(provider, self).await?).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Context;
+    use reqwest::header::HeaderValue;
+    use serde_json::json;
+
+    use super::*;
+
+    /// Response headers as name and value pairs.
+    type Headers = &'static [(&'static str, &'static str)];
+
+    fn from_response(status: u16, headers: Headers, body: &str) -> EngineError {
+        let mut header_map = HeaderMap::new();
+        for &(name, value) in headers {
+            header_map.insert(name, HeaderValue::from_static(value));
+        }
+        EngineError::from_response_parts(
+            "test",
+            StatusCode::from_u16(status).unwrap(),
+            &header_map,
+            body,
+        )
+    }
+
+    #[test]
+    fn classifies_responses() {
+        let cases: &[(u16, &str, ErrorClass)] = &[
+            (
+                401,
+                r#"{"error":{"message":"Incorrect API key provided","type":"invalid_request_error","code":"invalid_api_key"}}"#,
+                ErrorClass::Auth,
+            ),
+            (403, "Forbidden", ErrorClass::Auth),
+            (
+                400,
+                r#"{"error":{"type":"authentication_error","message":"invalid x-api-key"}}"#,
+                ErrorClass::Auth,
+            ),
+            (
+                429,
+                r#"{"error":{"message":"Rate limit reached for gpt-4o","code":"rate_limit_exceeded"}}"#,
+                ErrorClass::RateLimited,
+            ),
+            (
+                429,
+                r#"{"error":{"code":429,"message":"Quota exceeded for metric: generate_content requests per minute","status":"RESOURCE_EXHAUSTED"}}"#,
+                ErrorClass::RateLimited,
+            ),
+            (
+                429,
+                r#"{"error":{"message":"You exceeded your current quota, please check your plan and billing details.","type":"insufficient_quota"}}"#,
+                ErrorClass::QuotaExceeded,
+            ),
+            (
+                400,
+                r#"{"type":"error","error":{"type":"invalid_request_error","message":"Your credit balance is too low to access the Anthropic API."}}"#,
+                ErrorClass::QuotaExceeded,
+            ),
+            (402, "Payment Required", ErrorClass::QuotaExceeded),
+            (
+                400,
+                r#"{"error":{"message":"This model's maximum context length is 8192 tokens.","code":"context_length_exceeded"}}"#,
+                ErrorClass::ContextTooLong,
+            ),
+            (
+                400,
+                r#"{"type":"error","error":{"type":"invalid_request_error","message":"prompt is too long: 210000 tokens > 200000 maximum"}}"#,
+                ErrorClass::ContextTooLong,
+            ),
+            (
+                400,
+                r#"{"error":{"message":"The response was filtered","code":"content_filter"}}"#,
+                ErrorClass::ContentFiltered,
+            ),
+            (
+                500,
+                r#"{"error":{"message":"The server had an error"}}"#,
+                ErrorClass::Server,
+            ),
+            (503, "<html>Service Unavailable</html>", ErrorClass::Server),
+            (
+                529,
+                r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#,
+                ErrorClass::Server,
+            ),
+            (408, "Request Timeout", ErrorClass::Server),
+            (
+                400,
+                r#"{"error":{"message":"Unknown parameter: 'foo'"}}"#,
+                ErrorClass::Provider,
+            ),
+            (404, r#"{"detail":"Not Found"}"#, ErrorClass::Provider),
+        ];
+
+        for &(status, body, class) in cases {
+            let error = from_response(status, &[], body);
+            assert_eq!(error.class(), class, "{} {}", status, body);
+            assert_eq!(error.provider(), "test");
+            assert_eq!(
+                error.is_retryable(),
+                matches!(class, ErrorClass::RateLimited | ErrorClass::Server),
+                "{} {}",
+                status,
+                body
+            );
+        }
+    }
+
+    #[test]
+    fn server_errors_are_provider_errors_with_the_status() {
+        for status in [500, 502, 503, 504] {
+            let error = from_response(status, &[], "upstream failed");
+            let EngineError::Provider {
+                status: Some(code),
+                message,
+                ..
+            } = &error
+            else {
+                panic!("{:?}", error);
+            };
+            assert_eq!(*code, status);
+            assert_eq!(message, "upstream failed");
+        }
+    }
+
+    #[test]
+    fn reads_the_message_from_the_body() {
+        let error = from_response(
+            401,
+            &[],
+            r#"{"error":{"message":"Incorrect API key provided"}}"#,
+        );
+        assert_eq!(
+            error.to_string(),
+            "test authentication failed: Incorrect API key provided"
+        );
+
+        let error = from_response(
+            400,
+            &[],
+            r#"[{"error":{"code":400,"message":"API key not valid","status":"INVALID_ARGUMENT"}}]"#,
+        );
+        assert_eq!(error.to_string(), "test API error (400): API key not valid");
+    }
+
+    #[test]
+    fn reads_how_long_to_wait() {
+        let cases: &[(Headers, &str, Option<Duration>)] = &[
+            (&[], "Too Many Requests", None),
+            (
+                &[("retry-after", "20")],
+                "Too Many Requests",
+                Some(Duration::from_secs(20)),
+            ),
+            (
+                &[("retry-after", " 1.5 ")],
+                "Too Many Requests",
+                Some(Duration::from_millis(1500)),
+            ),
+            (
+                &[("retry-after-ms", "250"), ("retry-after", "1")],
+                "Too Many Requests",
+                Some(Duration::from_millis(250)),
+            ),
+            (&[("retry-after", "soon")], "Too Many Requests", None),
+            (
+                &[],
+                r#"{"error":{"code":429,"status":"RESOURCE_EXHAUSTED","details":[{"@type":"type.googleapis.com/google.rpc.RetryInfo","retryDelay":"30s"}]}}"#,
+                Some(Duration::from_secs(30)),
+            ),
+        ];
+
+        for &(headers, body, retry_after) in cases {
+            let error = from_response(429, headers, body);
+            assert_eq!(error.class(), ErrorClass::RateLimited, "{:?}", headers);
+            assert_eq!(error.retry_after(), retry_after, "{:?} {}", headers, body);
+        }
+    }
+
+    #[test]
+    fn reads_a_date_to_wait_until() {
+        let date = (Utc::now() + chrono::Duration::seconds(120)).to_rfc2822();
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", HeaderValue::from_str(&date).unwrap());
+        let error =
+            EngineError::from_response_parts("test", StatusCode::TOO_MANY_REQUESTS, &headers, "");
+
+        let delay = error.retry_after().unwrap();
+        assert!(
+            delay > Duration::from_secs(100) && delay <= Duration::from_secs(120),
+            "{:?}",
+            delay
+        );
+    }
+
+    #[test]
+    fn classifies_error_bodies() {
+        let overloaded = json!({
+            "type": "error",
+            "error": { "type": "overloaded_error", "message": "Overloaded" },
+        });
+        let error = EngineError::from_error_body("test", &overloaded);
+        assert!(matches!(
+            error,
+            EngineError::Provider {
+                status: Some(529),
+                ..
+            }
+        ));
+        assert_eq!(error.class(), ErrorClass::Server);
+
+        let limited = json!({"error": {"code": 429, "message": "Resource has been exhausted"}});
+        assert_eq!(
+            EngineError::from_error_body("test", &limited).class(),
+            ErrorClass::RateLimited
+        );
+    }
+
+    #[test]
+    fn finds_the_error_behind_context() {
+        let error = anyhow::Error::new(from_response(401, &[], "Unauthorized"))
+            .context("Request to engine failed")
+            .context("Pipeline step failed");
+        assert_eq!(
+            EngineError::find(&error).map(EngineError::class),
+            Some(ErrorClass::Auth)
+        );
+
+        let error = Err::<(), _>(anyhow::anyhow!("No such file"))
+            .context("Reading the config")
+            .unwrap_err();
+        assert!(EngineError::find(&error).is_none());
+    }
+}
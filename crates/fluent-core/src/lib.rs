@@ -1,13 +1,19 @@
 // crates/fluent-core/src/lib.rs
+pub mod error;
 pub mod types;
 pub mod traits;
 pub mod config;
 pub mod utils;
 pub mod output;
+pub mod embeddings;
+pub mod loaders;
 pub mod neo4j_client;
-mod voyageai_client;
 pub mod output_processor;
+pub mod pricing;
+pub mod rag;
+pub mod search;
 pub mod spinner_configuration;
+pub mod tokenizer;
 
 
 
@@ -0,0 +1,340 @@
+// crates/fluent-core/src/loaders.rs
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use anyhow::Result;
+use quick_xml::escape::unescape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{Reader, XmlVersion};
+
+mod epub;
+mod html;
+mod office;
+mod pdf;
+mod zip;
+
+pub use epub::load_epub;
+pub use html::load_html;
+pub use office::{load_docx, load_pptx, load_xlsx};
+pub use pdf::load_pdf;
+
+/// The metadata keys set when a document is loaded, which replace earlier
+/// values when the document is upserted again.
+pub const METADATA_KEYS: &[&str] = &[
+    "filename", "filesize", "filetype", "title", "author", "subject", "keywords", "created",
+    "modified", "pages", "slides", "sheet", "chapters", "rows",
+];
+
+/// Text extracted from a document, with what the format records about it.
+#[derive(Debug, Clone, Default)]
+pub struct LoadedDocument {
+    pub text: String,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub subject: Option<String>,
+    pub keywords: Option<String>,
+    /// Creation and modification dates, as ISO 8601.
+    pub created: Option<String>,
+    pub modified: Option<String>,
+    pub pages: Option<usize>,
+    /// Character offset in `text` where each page starts, for formats whose
+    /// pages are known.
+    pub page_starts: Vec<usize>,
+    pub slides: Option<usize>,
+    /// Sheet names, in workbook order.
+    pub sheets: Vec<String>,
+    pub chapters: Option<usize>,
+    /// Table rows, not counting the header, for spreadsheets and CSV.
+    pub rows: Option<usize>,
+}
+
+impl LoadedDocument {
+    /// `key:value` terms for `Document.metadata`, e.g. `title:Annual report`.
+    pub fn metadata(&self) -> Vec<String> {
+        let mut metadata = Vec::new();
+        let mut push = |key: &str, value: Option<String>| {
+            if let Some(value) = value.filter(|value| !value.trim().is_empty()) {
+                metadata.push(format!("{}:{}", key, value.trim()));
+            }
+        };
+        push("title", self.title.clone());
+        push("author", self.author.clone());
+        push("subject", self.subject.clone());
+        push("keywords", self.keywords.clone());
+        push("created", self.created.clone());
+        push("modified", self.modified.clone());
+        push("pages", self.pages.map(|n| n.to_string()));
+        push("slides", self.slides.map(|n| n.to_string()));
+        for sheet in &self.sheets {
+            push("sheet", Some(sheet.clone()));
+        }
+        push("chapters", self.chapters.map(|n| n.to_string()));
+        push("rows", self.rows.map(|n| n.to_string()));
+        metadata
+    }
+}
+
+/// The page, from 1, holding the character at `offset` of a document whose
+/// pages start at `page_starts`.
+pub fn page_at(page_starts: &[usize], offset: usize) -> Option<usize> {
+    match page_starts.partition_point(|&start| start <= offset) {
+        0 => None,
+        page => Some(page),
+    }
+}
+
+/// The first and last page, from 1, of the characters `start..end` of a
+/// document whose pages start at `page_starts`.
+pub fn page_range(page_starts: &[usize], start: usize, end: usize) -> Option<(usize, usize)> {
+    let first = page_at(page_starts, start)?;
+    // `end` is exclusive, so text ending where a page starts stays on the
+    // page before.
+    let last = page_at(page_starts, end.saturating_sub(1).max(start))?;
+    Some((first, last))
+}
+
+/// An element start, for `visit_xml`.
+struct Element<'a>(BytesStart<'a>);
+
+impl Element<'_> {
+    /// The tag name without its namespace prefix.
+    fn name(&self) -> &str {
+        self.0.local_name().into_inner()
+    }
+
+    /// An attribute value by its full name, e.g. `w:val`.
+    fn attr(&self, name: &str) -> Option<String> {
+        self.0
+            .try_get_attribute(name)
+            .ok()
+            .flatten()
+            .and_then(|attr| attr.normalized_value(XmlVersion::Implicit1_0).ok())
+            .map(Cow::into_owned)
+    }
+}
+
+enum XmlNode<'a> {
+    Start(&'a Element<'a>),
+    /// The local name of the element that ends; empty elements end at once.
+    End(&'a str),
+    Text(&'a str),
+}
+
+/// Calls `visit` for each element and piece of text in a document, with
+/// entities resolved.
+fn visit_xml(xml: &str, mut visit: impl FnMut(XmlNode)) -> Result<()> {
+    let mut reader = Reader::from_str(xml);
+    loop {
+        match reader.read_event()? {
+            Event::Start(start) => visit(XmlNode::Start(&Element(start))),
+            Event::Empty(start) => {
+                let element = Element(start);
+                visit(XmlNode::Start(&element));
+                visit(XmlNode::End(element.name()));
+            }
+            Event::End(end) => visit(XmlNode::End(end.local_name().into_inner())),
+            Event::Text(text) => visit(XmlNode::Text(&text.xml10_content())),
+            Event::CData(data) => visit(XmlNode::Text(&data.xml10_content())),
+            Event::GeneralRef(reference) => {
+                let text = match reference.resolve_char_ref() {
+                    Ok(Some(c)) => c.to_string(),
+                    _ => unescape(&format!("&{};", reference.xml10_content()))
+                        .map(Cow::into_owned)
+                        .unwrap_or_default(),
+                };
+                visit(XmlNode::Text(&text));
+            }
+            Event::Eof => return Ok(()),
+            _ => {}
+        }
+    }
+}
+
+/// The text of the first element with each of the given local names, e.g.
+/// `title` and `creator` from document properties.
+fn first_texts(xml: &str, names: &[&str]) -> Result<HashMap<String, String>> {
+    let mut texts: HashMap<String, String> = HashMap::new();
+    let mut current: Option<String> = None;
+    visit_xml(xml, |node| match node {
+        XmlNode::Start(element) => {
+            let name = element.name();
+            if current.is_none() && names.contains(&name) && !texts.contains_key(name) {
+                texts.insert(name.to_string(), String::new());
+                current = Some(name.to_string());
+            }
+        }
+        XmlNode::End(name) => {
+            if current.as_deref() == Some(name) {
+                current = None;
+            }
+        }
+        XmlNode::Text(text) => {
+            if let Some(text_of) = current.as_ref().and_then(|name| texts.get_mut(name)) {
+                text_of.push_str(text);
+            }
+        }
+    })?;
+    texts.retain(|_, text| {
+        *text = text.trim().to_string();
+        !text.is_empty()
+    });
+    Ok(texts)
+}
+
+/// Renders table rows one per line. When the first row looks like a header,
+/// each later row names its values, e.g. `Name: Ada | Year: 1843`, so a row
+/// still makes sense in a chunk without the header.
+fn render_table(rows: &[Vec<String>]) -> String {
+    let rows: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| {
+            // Line breaks inside a cell would split its row.
+            let mut cells: Vec<String> = row
+                .iter()
+                .map(|cell| cell.split_whitespace().collect::<Vec<_>>().join(" "))
+                .collect();
+            while cells.last().is_some_and(|cell| cell.is_empty()) {
+                cells.pop();
+            }
+            cells
+        })
+        .filter(|cells| !cells.is_empty())
+        .collect();
+    let Some((header, body)) = rows.split_first() else {
+        return String::new();
+    };
+
+    let has_header = !body.is_empty()
+        && header
+            .iter()
+            .all(|cell| !cell.is_empty() && cell.parse::<f64>().is_err());
+    let mut lines = vec![header.join(" | ")];
+    for row in body {
+        let line = if has_header {
+            row.iter()
+                .enumerate()
+                .filter(|(_, value)| !value.is_empty())
+                .map(|(i, value)| match header.get(i) {
+                    Some(name) => format!("{}: {}", name, value),
+                    None => value.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(" | ")
+        } else {
+            row.join(" | ")
+        };
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+/// Parses comma- or tab-separated values, with quoted fields that may hold
+/// delimiters, doubled quotes and line breaks.
+fn parse_delimited(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => quoted = false,
+                _ => field.push(c),
+            }
+        } else if c == '"' && field.is_empty() {
+            quoted = true;
+        } else if c == delimiter {
+            row.push(std::mem::take(&mut field));
+        } else if c == '\n' || c == '\r' {
+            if c == '\r' && chars.peek() == Some(&'\n') {
+                chars.next();
+            }
+            row.push(std::mem::take(&mut field));
+            rows.push(std::mem::take(&mut row));
+        } else {
+            field.push(c);
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+/// Loads a CSV file, or a TSV file when `delimiter` is a tab.
+pub fn load_delimited(bytes: &[u8], delimiter: char) -> Result<LoadedDocument> {
+    let rows = parse_delimited(&String::from_utf8_lossy(bytes), delimiter);
+    let text = render_table(&rows);
+    Ok(LoadedDocument {
+        rows: Some(rows.len().saturating_sub(1)),
+        text,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_quoted_delimited_fields() {
+        let rows = parse_delimited(
+            "\u{feff}name,notes\r\nAda,\"first, \"\"programmer\"\"\"\n\"Babbage\",\"line\nbreak\"",
+            ',',
+        );
+        assert_eq!(
+            rows,
+            [
+                vec!["name", "notes"],
+                vec!["Ada", "first, \"programmer\""],
+                vec!["Babbage", "line\nbreak"],
+            ]
+        );
+        assert_eq!(parse_delimited("a\tb\n", '\t'), [vec!["a", "b"]]);
+    }
+
+    #[test]
+    fn loads_csv_with_named_values() {
+        let loaded = load_delimited(b"Name,Year\nAda,1843\nCharles,\n", ',').unwrap();
+        assert_eq!(
+            loaded.text,
+            "Name | Year\nName: Ada | Year: 1843\nName: Charles"
+        );
+        assert_eq!(loaded.rows, Some(2));
+    }
+
+    #[test]
+    fn numeric_first_row_is_not_a_header() {
+        let loaded = load_delimited(b"1,2\n3,4\n", ',').unwrap();
+        assert_eq!(loaded.text, "1 | 2\n3 | 4");
+    }
+
+    #[test]
+    fn page_at_finds_the_page_of_an_offset() {
+        let starts = [0, 100, 250];
+        assert_eq!(page_at(&starts, 0), Some(1));
+        assert_eq!(page_at(&starts, 99), Some(1));
+        assert_eq!(page_at(&starts, 100), Some(2));
+        assert_eq!(page_at(&starts, 1000), Some(3));
+        assert_eq!(page_at(&[], 5), None);
+    }
+
+    #[test]
+    fn page_range_ends_on_the_page_of_the_last_character() {
+        let starts = [0, 100, 250];
+        assert_eq!(page_range(&starts, 10, 90), Some((1, 1)));
+        // Ending exactly where page 2 starts keeps the range on page 1.
+        assert_eq!(page_range(&starts, 50, 100), Some((1, 1)));
+        assert_eq!(page_range(&starts, 50, 101), Some((1, 2)));
+        assert_eq!(page_range(&starts, 100, 250), Some((2, 2)));
+        assert_eq!(page_range(&starts, 90, 400), Some((1, 3)));
+        assert_eq!(page_range(&starts, 120, 120), Some((2, 2)));
+        assert_eq!(page_range(&[], 0, 10), None);
+    }
+}
@@ -0,0 +1,180 @@
+// crates/fluent-core/src/loaders/epub.rs
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+
+use super::html::html_to_text;
+use super::zip::ZipArchive;
+use super::{first_texts, visit_xml, LoadedDocument, XmlNode};
+
+/// Decodes `%XX` escapes in a link.
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| text.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// The archive path of a link relative to the directory `base`.
+fn resolve(base: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or_default();
+    let mut parts: Vec<&str> = base.split('/').filter(|part| !part.is_empty()).collect();
+    let href = percent_decode(href);
+    for part in href.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+/// Loads the chapters of an EPUB book in reading order.
+pub fn load_epub(bytes: &[u8]) -> Result<LoadedDocument> {
+    let archive = ZipArchive::new(bytes)?;
+    let container = archive
+        .read_string("META-INF/container.xml")?
+        .ok_or_else(|| anyhow!("Not an EPUB file: META-INF/container.xml is missing"))?;
+    let mut package_path = None;
+    visit_xml(&container, |node| {
+        if let XmlNode::Start(element) = node {
+            if element.name() == "rootfile" && package_path.is_none() {
+                package_path = element.attr("full-path");
+            }
+        }
+    })?;
+    let package_path =
+        package_path.ok_or_else(|| anyhow!("EPUB container does not name a package"))?;
+    let package = archive
+        .read_string(&package_path)?
+        .ok_or_else(|| anyhow!("EPUB package {} is missing", package_path))?;
+    let base = package_path
+        .rsplit_once('/')
+        .map_or("", |(directory, _)| directory);
+
+    let mut manifest: HashMap<String, String> = HashMap::new();
+    let mut spine: Vec<String> = Vec::new();
+    visit_xml(&package, |node| {
+        if let XmlNode::Start(element) = node {
+            match element.name() {
+                "item" => {
+                    if let (Some(id), Some(href)) = (element.attr("id"), element.attr("href")) {
+                        manifest.insert(id, href);
+                    }
+                }
+                "itemref" => spine.extend(element.attr("idref")),
+                _ => {}
+            }
+        }
+    })?;
+    let mut properties = first_texts(&package, &["title", "creator"])?;
+
+    let mut chapters = Vec::new();
+    for id in &spine {
+        let Some(href) = manifest.get(id) else {
+            continue;
+        };
+        let Some(chapter) = archive.read(&resolve(base, href))? else {
+            continue;
+        };
+        let text = html_to_text(&String::from_utf8_lossy(&chapter));
+        if !text.trim().is_empty() {
+            chapters.push(text);
+        }
+    }
+
+    Ok(LoadedDocument {
+        text: chapters.join("\n\n"),
+        title: properties.remove("title"),
+        author: properties.remove("creator"),
+        chapters: Some(chapters.len()),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::zip::tests::zip;
+    use super::*;
+
+    #[test]
+    fn resolves_links_against_the_package_directory() {
+        assert_eq!(
+            resolve("OEBPS", "text/ch1.xhtml#start"),
+            "OEBPS/text/ch1.xhtml"
+        );
+        assert_eq!(
+            resolve("OEBPS/text", "../images/a%20b.png"),
+            "OEBPS/images/a b.png"
+        );
+        assert_eq!(resolve("", "./ch1.xhtml"), "ch1.xhtml");
+    }
+
+    #[test]
+    fn loads_chapters_in_spine_order() {
+        let container = r#"<container><rootfiles>
+            <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+        </rootfiles></container>"#;
+        let package = r#"<package xmlns:dc="dc">
+            <metadata><dc:title>The Engine</dc:title><dc:creator>Charles Babbage</dc:creator></metadata>
+            <manifest>
+                <item id="one" href="text/chapter%201.xhtml"/>
+                <item id="two" href="text/two.xhtml"/>
+                <item id="cover" href="cover.xhtml"/>
+            </manifest>
+            <spine><itemref idref="two"/><itemref idref="cover"/><itemref idref="one"/></spine>
+        </package>"#;
+        let chapter = |heading: &str, text: &str| {
+            format!(
+                "<html><head><title>Ignored</title></head><body><h1>{}</h1><p>{}</p></body></html>",
+                heading, text
+            )
+        };
+        let bytes = zip(&[
+            ("META-INF/container.xml", container),
+            ("OEBPS/content.opf", package),
+            (
+                "OEBPS/text/chapter 1.xhtml",
+                &chapter("One", "First words."),
+            ),
+            ("OEBPS/text/two.xhtml", &chapter("Two", "Second words.")),
+            (
+                "OEBPS/cover.xhtml",
+                "<html><body><img src='cover.png'/></body></html>",
+            ),
+        ]);
+        let loaded = load_epub(&bytes).unwrap();
+        assert_eq!(
+            loaded.text,
+            "# Two\n\nSecond words.\n\n# One\n\nFirst words."
+        );
+        assert_eq!(loaded.title.as_deref(), Some("The Engine"));
+        assert_eq!(loaded.author.as_deref(), Some("Charles Babbage"));
+        assert_eq!(loaded.chapters, Some(2));
+    }
+
+    #[test]
+    fn missing_container_is_an_error() {
+        let error = load_epub(&zip(&[("mimetype", "application/epub+zip")])).unwrap_err();
+        assert!(error.to_string().contains("Not an EPUB file"));
+    }
+}
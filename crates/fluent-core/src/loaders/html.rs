@@ -0,0 +1,320 @@
+// crates/fluent-core/src/loaders/html.rs
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use ego_tree::iter::Edge;
+use scraper::{ElementRef, Html, Node, Selector};
+
+use super::LoadedDocument;
+
+/// Elements whose content is not text a reader sees.
+const SKIPPED: &[&str] = &[
+    "head", "title", "script", "style", "noscript", "template", "svg", "iframe", "canvas", "nav",
+];
+
+/// Elements that start and end a paragraph of text.
+const BLOCKS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "body",
+    "dd",
+    "details",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "header",
+    "hr",
+    "html",
+    "main",
+    "ol",
+    "p",
+    "section",
+    "summary",
+    "table",
+    "ul",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Break {
+    None,
+    Space,
+    Line,
+    Paragraph,
+}
+
+/// Builds text with collapsed whitespace, where breaks requested between
+/// pieces of text are only written once something follows them.
+struct TextBuilder {
+    text: String,
+    pending: Break,
+}
+
+impl TextBuilder {
+    fn request(&mut self, brk: Break) {
+        self.pending = self.pending.max(brk);
+    }
+
+    fn flush(&mut self) {
+        if !self.text.is_empty() {
+            match self.pending {
+                Break::None => {}
+                Break::Space => self.text.push(' '),
+                Break::Line => self.text.push('\n'),
+                Break::Paragraph => self.text.push_str("\n\n"),
+            }
+        }
+        self.pending = Break::None;
+    }
+
+    /// Writes markup such as a list bullet, dropping a pending space.
+    fn marker(&mut self, marker: &str) {
+        if self.pending == Break::Space {
+            self.pending = Break::None;
+        }
+        self.flush();
+        self.text.push_str(marker);
+    }
+
+    fn push(&mut self, text: &str, preformatted: bool) {
+        if preformatted {
+            if !text.is_empty() {
+                self.flush();
+                self.text.push_str(text);
+            }
+            return;
+        }
+        if text.starts_with(char::is_whitespace) {
+            self.request(Break::Space);
+        }
+        let mut words = text.split_whitespace().peekable();
+        let any = words.peek().is_some();
+        while let Some(word) = words.next() {
+            self.flush();
+            self.text.push_str(word);
+            if words.peek().is_some() {
+                self.request(Break::Space);
+            }
+        }
+        if any && text.ends_with(char::is_whitespace) {
+            self.request(Break::Space);
+        }
+    }
+}
+
+/// Selects the elements matching a selector written in this file.
+fn select<'a>(
+    document: &'a Html,
+    selector: &'static OnceLock<Selector>,
+    css: &str,
+) -> impl Iterator<Item = ElementRef<'a>> + 'a {
+    document.select(selector.get_or_init(|| Selector::parse(css).expect("valid selector")))
+}
+
+fn collapse_whitespace(text: &str) -> Option<String> {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+/// The document title and the author from `<meta name="author">`.
+fn head_metadata(document: &Html) -> (Option<String>, Option<String>) {
+    static TITLE: OnceLock<Selector> = OnceLock::new();
+    static META: OnceLock<Selector> = OnceLock::new();
+    let title = select(document, &TITLE, "title")
+        .next()
+        .and_then(|title| collapse_whitespace(&title.text().collect::<String>()));
+
+    let mut author = None;
+    let mut og_title = None;
+    for meta in select(document, &META, "meta") {
+        let meta = meta.value();
+        let Some(content) = meta.attr("content").and_then(collapse_whitespace) else {
+            continue;
+        };
+        let name = meta
+            .attr("name")
+            .or_else(|| meta.attr("property"))
+            .map(|name| name.to_ascii_lowercase());
+        match name.as_deref() {
+            Some("author") => {
+                author.get_or_insert(content);
+            }
+            Some("og:title") => {
+                og_title.get_or_insert(content);
+            }
+            _ => {}
+        }
+    }
+    (title.or(og_title), author)
+}
+
+/// The text a reader sees in an HTML page, with paragraphs, list items and
+/// table rows on their own lines and headings marked as in Markdown.
+pub(super) fn html_to_text(html: &str) -> String {
+    document_text(&Html::parse_document(html))
+}
+
+fn document_text(document: &Html) -> String {
+    let mut out = TextBuilder {
+        text: String::new(),
+        pending: Break::None,
+    };
+    let mut skipped = 0usize;
+    let mut preformatted = 0usize;
+    let mut first_cell = true;
+    for edge in document.tree.root().traverse() {
+        let (node, closing) = match edge {
+            Edge::Open(node) => (node, false),
+            Edge::Close(node) => (node, true),
+        };
+        let element = match node.value() {
+            Node::Text(text) if skipped == 0 && !closing => {
+                out.push(text, preformatted > 0);
+                continue;
+            }
+            Node::Element(element) => element,
+            _ => continue,
+        };
+        let name = element.name();
+        if SKIPPED.contains(&name) {
+            if closing {
+                skipped -= 1;
+            } else {
+                skipped += 1;
+            }
+            continue;
+        }
+        if skipped > 0 {
+            continue;
+        }
+        match name {
+            "br" => out.request(Break::Line),
+            "pre" => {
+                out.request(Break::Paragraph);
+                if closing {
+                    preformatted -= 1;
+                } else {
+                    preformatted += 1;
+                }
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                out.request(Break::Paragraph);
+                if !closing {
+                    let level = usize::from(name.as_bytes()[1] - b'0');
+                    out.marker(&format!("{} ", "#".repeat(level)));
+                }
+            }
+            "li" => {
+                out.request(Break::Line);
+                if !closing {
+                    out.marker("- ");
+                }
+            }
+            "tr" => {
+                out.request(Break::Line);
+                first_cell = true;
+            }
+            "td" | "th" if !closing => {
+                if !first_cell {
+                    out.marker(" | ");
+                }
+                first_cell = false;
+            }
+            "img" if !closing => {
+                if let Some(alt) = element.attr("alt") {
+                    out.push(alt, false);
+                }
+            }
+            _ if BLOCKS.contains(&name) => out.request(Break::Paragraph),
+            _ => {}
+        }
+    }
+    out.text
+}
+
+/// Loads the text of an HTML page with its title and author.
+pub fn load_html(bytes: &[u8]) -> Result<LoadedDocument> {
+    let document = Html::parse_document(&String::from_utf8_lossy(bytes));
+    let (title, author) = head_metadata(&document);
+    Ok(LoadedDocument {
+        text: document_text(&document),
+        title,
+        author,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_visible_text_in_blocks() {
+        let html = r#"<!DOCTYPE html><html><head><title>Page</title>
+            <style>p { color: red; }</style></head>
+            <body><nav><a href="/">Home</a></nav>
+            <h2>Intro</h2><p>Some   <b>bold</b>
+            text.<br>Next line.</p><!-- a comment --><script>alert("x")</script>
+            <div>Tom &amp; Jerry &lt;3 &#233;t&#xE9; &mdash; &#x1F980; &bogus; AT&T caf&eacute;</div></body></html>"#;
+        assert_eq!(
+            html_to_text(html),
+            "## Intro\n\nSome bold text.\nNext line.\n\nTom & Jerry <3 été — 🦀 &bogus; AT&T café"
+        );
+    }
+
+    #[test]
+    fn renders_lists_tables_and_images() {
+        let html = "<ul><li>One</li><li>Two</li></ul>\
+            <table><tr><th>Name</th><th>Year</th></tr><tr><td>Ada</td><td>1843</td></tr></table>\
+            <p><img src='a.png' alt='A diagram'> after</p>";
+        assert_eq!(
+            html_to_text(html),
+            "- One\n- Two\n\nName | Year\nAda | 1843\n\nA diagram after"
+        );
+    }
+
+    #[test]
+    fn keeps_preformatted_whitespace() {
+        let html = "<p>Run:</p><pre>fn main() {\n    println!(\"hi\");\n}</pre><p>Done</p>";
+        assert_eq!(
+            html_to_text(html),
+            "Run:\n\nfn main() {\n    println!(\"hi\");\n}\n\nDone"
+        );
+    }
+
+    #[test]
+    fn recovers_from_unclosed_elements() {
+        let html = "<p>One<p>Two<ul><li>A<li>B</ul><table><tr><td>x<td>y</table><div>Last";
+        assert_eq!(
+            html_to_text(html),
+            "One\n\nTwo\n\n- A\n- B\n\nx | y\n\nLast"
+        );
+    }
+
+    #[test]
+    fn stray_angle_brackets_are_text() {
+        assert_eq!(html_to_text("<p>1 < 2 and 3 > 2</p>"), "1 < 2 and 3 > 2");
+    }
+
+    #[test]
+    fn load_html_reads_title_and_author() {
+        let html = br#"<html><head><title> The
+            Analytical Engine </title><meta name="Author" content="Ada Lovelace"></head>
+            <body><p>Notes.</p></body></html>"#;
+        let loaded = load_html(html).unwrap();
+        assert_eq!(loaded.text, "Notes.");
+        assert_eq!(loaded.title.as_deref(), Some("The Analytical Engine"));
+        assert_eq!(loaded.author.as_deref(), Some("Ada Lovelace"));
+
+        let og = br#"<meta property="og:title" content="Shared title"><p>Body</p>"#;
+        assert_eq!(
+            load_html(og).unwrap().title.as_deref(),
+            Some("Shared title")
+        );
+    }
+}
@@ -0,0 +1,537 @@
+// crates/fluent-core/src/loaders/office.rs
+use std::collections::HashMap;
+use std::mem;
+
+use anyhow::{anyhow, Result};
+
+use super::zip::ZipArchive;
+use super::{first_texts, render_table, visit_xml, LoadedDocument, XmlNode};
+
+/// The title, author and page count recorded in an Office document's
+/// properties.
+fn properties(archive: &ZipArchive) -> Result<LoadedDocument> {
+    let mut core = match archive.read_string("docProps/core.xml")? {
+        Some(xml) => first_texts(&xml, &["title", "creator"])?,
+        None => HashMap::new(),
+    };
+    let app = match archive.read_string("docProps/app.xml")? {
+        Some(xml) => first_texts(&xml, &["Pages"])?,
+        None => HashMap::new(),
+    };
+    Ok(LoadedDocument {
+        title: core.remove("title"),
+        author: core.remove("creator"),
+        pages: app.get("Pages").and_then(|pages| pages.parse().ok()),
+        ..Default::default()
+    })
+}
+
+/// The Markdown heading level of a Word paragraph style, e.g. 2 for
+/// `Heading2`.
+fn heading_level(style: &str) -> Option<usize> {
+    if style.eq_ignore_ascii_case("title") {
+        return Some(1);
+    }
+    let level: usize = style
+        .strip_prefix("Heading")
+        .or_else(|| style.strip_prefix("heading"))?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(level.clamp(1, 6))
+}
+
+#[derive(Default)]
+struct Table {
+    rows: Vec<Vec<String>>,
+    row: Vec<String>,
+    cell: Vec<String>,
+}
+
+#[derive(Default)]
+struct Paragraph {
+    text: String,
+    heading: Option<usize>,
+}
+
+/// Loads the paragraphs and tables of a Word document. Headings become
+/// Markdown headings and table rows become lines of cells.
+pub fn load_docx(bytes: &[u8]) -> Result<LoadedDocument> {
+    let archive = ZipArchive::new(bytes)?;
+    let xml = archive
+        .read_string("word/document.xml")?
+        .ok_or_else(|| anyhow!("Not a DOCX file: word/document.xml is missing"))?;
+
+    let mut blocks: Vec<String> = Vec::new();
+    let mut tables: Vec<Table> = Vec::new();
+    // Text boxes hold paragraphs inside paragraphs.
+    let mut paragraphs: Vec<Paragraph> = Vec::new();
+    let mut in_text = false;
+    let mut in_properties = false;
+    visit_xml(&xml, |node| match node {
+        XmlNode::Start(element) => match element.name() {
+            "p" => paragraphs.push(Paragraph::default()),
+            "pPr" => in_properties = true,
+            "pStyle" => {
+                if let Some(paragraph) = paragraphs.last_mut() {
+                    paragraph.heading = element.attr("w:val").and_then(|s| heading_level(&s));
+                }
+            }
+            "t" => in_text = true,
+            // Tab stops in paragraph properties are not tabs.
+            "tab" if !in_properties => {
+                if let Some(paragraph) = paragraphs.last_mut() {
+                    paragraph.text.push('\t');
+                }
+            }
+            "br" | "cr" => {
+                if let Some(paragraph) = paragraphs.last_mut() {
+                    paragraph.text.push('\n');
+                }
+            }
+            "tbl" => tables.push(Table::default()),
+            _ => {}
+        },
+        XmlNode::End(name) => match name {
+            "t" => in_text = false,
+            "pPr" => in_properties = false,
+            "p" => {
+                let Some(paragraph) = paragraphs.pop() else {
+                    return;
+                };
+                let text = paragraph.text.trim();
+                if text.is_empty() {
+                    return;
+                }
+                let text = match paragraph.heading {
+                    Some(level) => format!("{} {}", "#".repeat(level), text),
+                    None => text.to_string(),
+                };
+                match (paragraphs.last_mut(), tables.last_mut()) {
+                    (Some(outer), _) => {
+                        outer.text.push(' ');
+                        outer.text.push_str(&text);
+                    }
+                    (None, Some(table)) => table.cell.push(text),
+                    (None, None) => blocks.push(text),
+                }
+            }
+            "tc" => {
+                if let Some(table) = tables.last_mut() {
+                    let cell = mem::take(&mut table.cell).join(" ");
+                    table.row.push(cell);
+                }
+            }
+            "tr" => {
+                if let Some(table) = tables.last_mut() {
+                    let row = mem::take(&mut table.row);
+                    table.rows.push(row);
+                }
+            }
+            "tbl" => {
+                if let Some(table) = tables.pop() {
+                    let rendered = render_table(&table.rows);
+                    if !rendered.is_empty() {
+                        match tables.last_mut() {
+                            Some(outer) => outer.cell.push(rendered),
+                            None => blocks.push(rendered),
+                        }
+                    }
+                }
+            }
+            _ => {}
+        },
+        XmlNode::Text(text) => {
+            if in_text {
+                if let Some(paragraph) = paragraphs.last_mut() {
+                    paragraph.text.push_str(text);
+                }
+            }
+        }
+    })?;
+
+    Ok(LoadedDocument {
+        text: blocks.join("\n\n"),
+        ..properties(&archive)?
+    })
+}
+
+/// The paragraphs of a slide or similar DrawingML part.
+fn drawing_paragraphs(xml: &str) -> Result<Vec<String>> {
+    let mut paragraphs = Vec::new();
+    let mut paragraph = String::new();
+    let mut in_text = false;
+    visit_xml(xml, |node| match node {
+        XmlNode::Start(element) => match element.name() {
+            "p" => paragraph.clear(),
+            "t" => in_text = true,
+            "br" => paragraph.push('\n'),
+            _ => {}
+        },
+        XmlNode::End(name) => match name {
+            "t" => in_text = false,
+            "p" => {
+                let text = paragraph.trim();
+                if !text.is_empty() {
+                    paragraphs.push(text.to_string());
+                }
+            }
+            _ => {}
+        },
+        XmlNode::Text(text) => {
+            if in_text {
+                paragraph.push_str(text);
+            }
+        }
+    })?;
+    Ok(paragraphs)
+}
+
+/// Loads the text of each slide of a PowerPoint presentation, under a
+/// `## Slide n` heading.
+pub fn load_pptx(bytes: &[u8]) -> Result<LoadedDocument> {
+    let archive = ZipArchive::new(bytes)?;
+    let mut slides: Vec<(usize, String)> = archive
+        .names()
+        .filter_map(|name| {
+            let number = name
+                .strip_prefix("ppt/slides/slide")?
+                .strip_suffix(".xml")?
+                .parse()
+                .ok()?;
+            Some((number, name.to_string()))
+        })
+        .collect();
+    if slides.is_empty() {
+        return Err(anyhow!("Not a PPTX file: no slides found"));
+    }
+    slides.sort();
+
+    let mut sections = Vec::new();
+    for (number, name) in &slides {
+        let Some(xml) = archive.read_string(name)? else {
+            continue;
+        };
+        let paragraphs = drawing_paragraphs(&xml)?;
+        if !paragraphs.is_empty() {
+            sections.push(format!("## Slide {}\n\n{}", number, paragraphs.join("\n")));
+        }
+    }
+
+    Ok(LoadedDocument {
+        text: sections.join("\n\n"),
+        slides: Some(slides.len()),
+        pages: None,
+        ..properties(&archive)?
+    })
+}
+
+/// Columns in a sheet, `A` to `XFD`.
+const MAX_COLUMNS: usize = 16_384;
+
+/// The zero-based column of a cell reference such as `C7`, or `None` when
+/// it has no column or one past `XFD`.
+fn column_index(reference: &str) -> Option<usize> {
+    let letters = reference
+        .bytes()
+        .take_while(u8::is_ascii_alphabetic)
+        .map(|b| b.to_ascii_uppercase());
+    let mut column = 0;
+    for b in letters {
+        column = column * 26 + (b - b'A' + 1) as usize;
+        if column > MAX_COLUMNS {
+            return None;
+        }
+    }
+    column.checked_sub(1)
+}
+
+fn shared_strings(xml: &str) -> Result<Vec<String>> {
+    let mut strings = Vec::new();
+    let mut current = String::new();
+    let mut in_text = false;
+    // Phonetic runs repeat the reading of East Asian text.
+    let mut in_phonetic = false;
+    visit_xml(xml, |node| match node {
+        XmlNode::Start(element) => match element.name() {
+            "si" => current.clear(),
+            "t" => in_text = true,
+            "rPh" => in_phonetic = true,
+            _ => {}
+        },
+        XmlNode::End(name) => match name {
+            "si" => strings.push(mem::take(&mut current)),
+            "t" => in_text = false,
+            "rPh" => in_phonetic = false,
+            _ => {}
+        },
+        XmlNode::Text(text) => {
+            if in_text && !in_phonetic {
+                current.push_str(text);
+            }
+        }
+    })?;
+    Ok(strings)
+}
+
+fn sheet_rows(xml: &str, shared: &[String]) -> Result<Vec<Vec<String>>> {
+    let mut rows = Vec::new();
+    let mut row: Vec<String> = Vec::new();
+    let mut column = 0;
+    let mut cell_type: Option<String> = None;
+    let mut value = String::new();
+    let mut in_value = false;
+    let mut bad_reference: Option<String> = None;
+    visit_xml(xml, |node| match node {
+        XmlNode::Start(element) => match element.name() {
+            "row" => {
+                row.clear();
+                column = 0;
+            }
+            "c" => {
+                if let Some(reference) = element.attr("r") {
+                    match column_index(&reference) {
+                        Some(index) => column = index,
+                        None => {
+                            bad_reference.get_or_insert(reference);
+                        }
+                    }
+                }
+                cell_type = element.attr("t");
+                value.clear();
+            }
+            "v" | "t" => in_value = true,
+            _ => {}
+        },
+        XmlNode::End(name) => match name {
+            "v" | "t" => in_value = false,
+            "c" => {
+                let text = match cell_type.as_deref() {
+                    Some("s") => value
+                        .trim()
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|i| shared.get(i).cloned())
+                        .unwrap_or_default(),
+                    Some("b") if !value.is_empty() => {
+                        if value.trim() == "1" { "TRUE" } else { "FALSE" }.to_string()
+                    }
+                    _ => value.clone(),
+                };
+                // Cells without a reference follow the previous one.
+                if column >= MAX_COLUMNS {
+                    return;
+                }
+                if row.len() <= column {
+                    row.resize(column + 1, String::new());
+                }
+                row[column] = text;
+                column += 1;
+            }
+            "row" => rows.push(mem::take(&mut row)),
+            _ => {}
+        },
+        XmlNode::Text(text) => {
+            if in_value {
+                value.push_str(text);
+            }
+        }
+    })?;
+    match bad_reference {
+        Some(reference) => Err(anyhow!("Invalid cell reference {}", reference)),
+        None => Ok(rows),
+    }
+}
+
+/// Loads each sheet of an Excel workbook as rows under a `## Sheet: name`
+/// heading.
+pub fn load_xlsx(bytes: &[u8]) -> Result<LoadedDocument> {
+    let archive = ZipArchive::new(bytes)?;
+    let workbook = archive
+        .read_string("xl/workbook.xml")?
+        .ok_or_else(|| anyhow!("Not an XLSX file: xl/workbook.xml is missing"))?;
+    let shared = match archive.read_string("xl/sharedStrings.xml")? {
+        Some(xml) => shared_strings(&xml)?,
+        None => Vec::new(),
+    };
+
+    let mut sheets: Vec<(String, String)> = Vec::new();
+    visit_xml(&workbook, |node| {
+        if let XmlNode::Start(element) = node {
+            if element.name() == "sheet" {
+                if let (Some(name), Some(id)) = (element.attr("name"), element.attr("r:id")) {
+                    sheets.push((name, id));
+                }
+            }
+        }
+    })?;
+    let mut targets: HashMap<String, String> = HashMap::new();
+    if let Some(rels) = archive.read_string("xl/_rels/workbook.xml.rels")? {
+        visit_xml(&rels, |node| {
+            if let XmlNode::Start(element) = node {
+                if element.name() == "Relationship" {
+                    if let (Some(id), Some(target)) = (element.attr("Id"), element.attr("Target")) {
+                        targets.insert(id, target);
+                    }
+                }
+            }
+        })?;
+    }
+
+    let mut sections = Vec::new();
+    let mut names = Vec::new();
+    let mut row_count = 0;
+    for (name, id) in sheets {
+        let Some(target) = targets.get(&id) else {
+            continue;
+        };
+        let path = match target.strip_prefix('/') {
+            Some(absolute) => absolute.to_string(),
+            None => format!("xl/{}", target),
+        };
+        let Some(xml) = archive.read_string(&path)? else {
+            continue;
+        };
+        let rows = sheet_rows(&xml, &shared)?;
+        let table = render_table(&rows);
+        if !table.is_empty() {
+            row_count += rows.len().saturating_sub(1);
+            sections.push(format!("## Sheet: {}\n\n{}", name, table));
+        }
+        names.push(name);
+    }
+
+    Ok(LoadedDocument {
+        text: sections.join("\n\n"),
+        sheets: names,
+        rows: Some(row_count),
+        pages: None,
+        ..properties(&archive)?
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::zip::tests::zip;
+    use super::*;
+
+    const CORE: &str = r#"<cp:coreProperties xmlns:cp="cp" xmlns:dc="dc">
+        <dc:title>Quarterly report</dc:title><dc:creator>Ada Lovelace</dc:creator>
+    </cp:coreProperties>"#;
+
+    #[test]
+    fn column_index_reads_letters_up_to_xfd() {
+        assert_eq!(column_index("A1"), Some(0));
+        assert_eq!(column_index("c7"), Some(2));
+        assert_eq!(column_index("Z9"), Some(25));
+        assert_eq!(column_index("AA10"), Some(26));
+        assert_eq!(column_index("XFD1048576"), Some(16_383));
+        assert_eq!(column_index("XFE1"), None);
+        assert_eq!(column_index(&format!("{}1", "Z".repeat(40))), None);
+        assert_eq!(column_index("17"), None);
+    }
+
+    #[test]
+    fn sheet_rows_place_cells_by_reference() {
+        let xml = r#"<worksheet><sheetData>
+            <row r="1"><c r="A1" t="s"><v>0</v></c><c r="C1" t="inlineStr"><is><t>Inline</t></is></c></row>
+            <row r="2"><c r="B2"><v>42</v></c><c t="b"><v>1</v></c></row>
+        </sheetData></worksheet>"#;
+        let rows = sheet_rows(xml, &["Shared".to_string()]).unwrap();
+        assert_eq!(rows, [vec!["Shared", "", "Inline"], vec!["", "42", "TRUE"]]);
+    }
+
+    #[test]
+    fn sheet_rows_reject_columns_past_xfd() {
+        let xml = r#"<worksheet><sheetData>
+            <row r="1"><c r="ZZZZZZZZZZ1"><v>1</v></c></row>
+        </sheetData></worksheet>"#;
+        let error = sheet_rows(xml, &[]).unwrap_err().to_string();
+        assert!(error.contains("ZZZZZZZZZZ1"), "{}", error);
+    }
+
+    #[test]
+    fn loads_docx_paragraphs_headings_and_tables() {
+        let document = r#"<w:document xmlns:w="w"><w:body>
+            <w:p><w:pPr><w:pStyle w:val="Heading2"/><w:tabs><w:tab w:val="left"/></w:tabs></w:pPr><w:r><w:t>Results</w:t></w:r></w:p>
+            <w:p><w:r><w:t xml:space="preserve">Sales grew </w:t></w:r><w:r><w:t>&amp; costs fell.</w:t></w:r></w:p>
+            <w:tbl>
+                <w:tr><w:tc><w:p><w:r><w:t>Region</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>Total</w:t></w:r></w:p></w:tc></w:tr>
+                <w:tr><w:tc><w:p><w:r><w:t>North</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>12</w:t></w:r></w:p></w:tc></w:tr>
+            </w:tbl>
+        </w:body></w:document>"#;
+        let bytes = zip(&[
+            ("word/document.xml", document),
+            ("docProps/core.xml", CORE),
+            (
+                "docProps/app.xml",
+                "<Properties><Pages>3</Pages></Properties>",
+            ),
+        ]);
+        let loaded = load_docx(&bytes).unwrap();
+        assert_eq!(
+            loaded.text,
+            "## Results\n\nSales grew & costs fell.\n\nRegion | Total\nRegion: North | Total: 12"
+        );
+        assert_eq!(loaded.title.as_deref(), Some("Quarterly report"));
+        assert_eq!(loaded.author.as_deref(), Some("Ada Lovelace"));
+        assert_eq!(loaded.pages, Some(3));
+    }
+
+    #[test]
+    fn loads_pptx_slides_in_number_order() {
+        let slide = |text: &str| {
+            format!(
+                r#"<p:sld xmlns:p="p" xmlns:a="a"><a:p><a:r><a:t>{}</a:t></a:r></a:p></p:sld>"#,
+                text
+            )
+        };
+        let bytes = zip(&[
+            ("ppt/slides/slide10.xml", &slide("Tenth")),
+            ("ppt/slides/slide2.xml", &slide("Second")),
+            ("ppt/slides/_rels/slide2.xml.rels", "<Relationships/>"),
+        ]);
+        let loaded = load_pptx(&bytes).unwrap();
+        assert_eq!(loaded.text, "## Slide 2\n\nSecond\n\n## Slide 10\n\nTenth");
+        assert_eq!(loaded.slides, Some(2));
+        assert!(load_pptx(&zip(&[("other.xml", "<a/>")])).is_err());
+    }
+
+    #[test]
+    fn loads_xlsx_sheets_with_shared_strings() {
+        let workbook = r#"<workbook xmlns:r="r"><sheets>
+            <sheet name="People" sheetId="1" r:id="rId1"/>
+            <sheet name="Empty" sheetId="2" r:id="rId2"/>
+        </sheets></workbook>"#;
+        let rels = r#"<Relationships>
+            <Relationship Id="rId1" Target="worksheets/sheet1.xml"/>
+            <Relationship Id="rId2" Target="/xl/worksheets/sheet2.xml"/>
+        </Relationships>"#;
+        let shared = r#"<sst>
+            <si><t>Name</t></si>
+            <si><t>Year</t></si>
+            <si><r><t>Ada</t></r><rPh><t>ADA</t></rPh></si>
+        </sst>"#;
+        let sheet = r#"<worksheet><sheetData>
+            <row r="1"><c r="A1" t="s"><v>0</v></c><c r="B1" t="s"><v>1</v></c></row>
+            <row r="2"><c r="A2" t="s"><v>2</v></c><c r="B2"><v>1843</v></c></row>
+        </sheetData></worksheet>"#;
+        let bytes = zip(&[
+            ("xl/workbook.xml", workbook),
+            ("xl/_rels/workbook.xml.rels", rels),
+            ("xl/sharedStrings.xml", shared),
+            ("xl/worksheets/sheet1.xml", sheet),
+            (
+                "xl/worksheets/sheet2.xml",
+                "<worksheet><sheetData/></worksheet>",
+            ),
+        ]);
+        let loaded = load_xlsx(&bytes).unwrap();
+        assert_eq!(
+            loaded.text,
+            "## Sheet: People\n\nName | Year\nName: Ada | Year: 1843"
+        );
+        assert_eq!(loaded.sheets, ["People", "Empty"]);
+        assert_eq!(loaded.rows, Some(1));
+    }
+}
@@ -0,0 +1,240 @@
+// crates/fluent-core/src/loaders/pdf.rs
+use anyhow::{anyhow, Result};
+use log::debug;
+use pdf_extract::{decode_text_string, output_doc_page, Dictionary, Document, PlainTextOutput};
+
+use super::LoadedDocument;
+
+/// Separates pages in the loaded text.
+const PAGE_SEPARATOR: &str = "\n\n";
+
+/// Converts a PDF date such as `D:20240131093000+01'00'` to
+/// `2024-01-31T09:30:00`, keeping only the parts present.
+fn pdf_date(date: &str) -> Option<String> {
+    let digits: String = date
+        .trim()
+        .trim_start_matches("D:")
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect();
+    let part = |range: std::ops::Range<usize>, default: &'static str| {
+        digits.get(range).unwrap_or(default).to_string()
+    };
+    if digits.len() < 4 {
+        return None;
+    }
+    let date = format!(
+        "{}-{}-{}",
+        part(0..4, ""),
+        part(4..6, "01"),
+        part(6..8, "01")
+    );
+    if digits.len() < 12 {
+        return Some(date);
+    }
+    Some(format!(
+        "{}T{}:{}:{}",
+        date,
+        part(8..10, "00"),
+        part(10..12, "00"),
+        part(12..14, "00")
+    ))
+}
+
+/// The document information dictionary, which holds the title, author and
+/// dates.
+fn info_dictionary(document: &Document) -> Option<&Dictionary> {
+    let info = document.trailer.get(b"Info").ok()?;
+    document.dereference(info).ok()?.1.as_dict().ok()
+}
+
+fn info_text(info: &Dictionary, key: &[u8]) -> Option<String> {
+    let text = decode_text_string(info.get(key).ok()?).ok()?;
+    let text = text.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Loads the text of a PDF page by page, with the title, author, subject,
+/// keywords and dates from its information dictionary.
+pub fn load_pdf(bytes: &[u8]) -> Result<LoadedDocument> {
+    let mut document = Document::load_mem(bytes)?;
+    if document.is_encrypted() {
+        document
+            .decrypt("")
+            .map_err(|_| anyhow!("PDF is encrypted with a password"))?;
+    }
+
+    let mut text = String::new();
+    let mut page_starts = Vec::new();
+    let mut chars = 0;
+    for page in document.get_pages().into_keys() {
+        if !page_starts.is_empty() {
+            text.push_str(PAGE_SEPARATOR);
+            chars += PAGE_SEPARATOR.len();
+        }
+        page_starts.push(chars);
+
+        let mut page_text = String::new();
+        let extracted = output_doc_page(&document, &mut PlainTextOutput::new(&mut page_text), page);
+        // A page that cannot be read is left empty so the pages after it
+        // keep their numbers.
+        if let Err(e) = extracted {
+            debug!("Failed to extract text from PDF page {}: {}", page, e);
+            continue;
+        }
+        let page_text = page_text.trim();
+        chars += page_text.chars().count();
+        text.push_str(page_text);
+    }
+
+    let info = info_dictionary(&document);
+    let field = |key: &[u8]| info.and_then(|info| info_text(info, key));
+    Ok(LoadedDocument {
+        title: field(b"Title"),
+        author: field(b"Author"),
+        subject: field(b"Subject"),
+        keywords: field(b"Keywords"),
+        created: field(b"CreationDate").and_then(|date| pdf_date(&date)),
+        modified: field(b"ModDate").and_then(|date| pdf_date(&date)),
+        pages: Some(page_starts.len()),
+        page_starts,
+        text,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pdf_extract::content::{Content, Operation};
+    use pdf_extract::{dictionary, Object, Stream};
+
+    use super::*;
+
+    /// Builds a PDF with one page per text, and an empty page for an empty
+    /// text.
+    fn pdf(pages: &[&str], info: Option<Dictionary>) -> Vec<u8> {
+        let mut document = Document::with_version("1.5");
+        let pages_id = document.new_object_id();
+        let font_id = document.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Courier",
+        });
+        let resources_id = document.add_object(dictionary! {
+            "Font" => dictionary! { "F1" => font_id },
+        });
+        let mut kids = Vec::new();
+        for text in pages {
+            let mut operations = Vec::new();
+            if !text.is_empty() {
+                operations = vec![
+                    Operation::new("BT", vec![]),
+                    Operation::new("Tf", vec!["F1".into(), 12.into()]),
+                    Operation::new("Td", vec![100.into(), 600.into()]),
+                    Operation::new("Tj", vec![Object::string_literal(*text)]),
+                    Operation::new("ET", vec![]),
+                ];
+            }
+            let content = Content { operations }.encode().unwrap();
+            let content_id = document.add_object(Stream::new(dictionary! {}, content));
+            kids.push(Object::from(document.add_object(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "Contents" => content_id,
+            })));
+        }
+        document.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Count" => kids.len() as i64,
+                "Kids" => kids,
+                "Resources" => resources_id,
+                "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+            }),
+        );
+        let catalog_id = document.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        document.trailer.set("Root", catalog_id);
+        if let Some(info) = info {
+            let info_id = document.add_object(info);
+            document.trailer.set("Info", info_id);
+        }
+        let mut bytes = Vec::new();
+        document.save_to(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn pdf_date_keeps_the_parts_present() {
+        assert_eq!(
+            pdf_date("D:20240131093000+01'00'").as_deref(),
+            Some("2024-01-31T09:30:00")
+        );
+        assert_eq!(
+            pdf_date("D:202401310930").as_deref(),
+            Some("2024-01-31T09:30:00")
+        );
+        assert_eq!(pdf_date("D:20240131").as_deref(), Some("2024-01-31"));
+        assert_eq!(pdf_date("2024").as_deref(), Some("2024-01-01"));
+        assert_eq!(pdf_date(" D:202406 ").as_deref(), Some("2024-06-01"));
+        assert_eq!(pdf_date("D:202"), None);
+        assert_eq!(pdf_date("yesterday"), None);
+    }
+
+    #[test]
+    fn page_starts_count_the_separators() {
+        let bytes = pdf(&["First page", "", "Third page"], None);
+        let loaded = load_pdf(&bytes).unwrap();
+
+        assert_eq!(loaded.pages, Some(3));
+        assert_eq!(loaded.page_starts.len(), 3);
+        let page = |n: usize| {
+            let start = loaded.page_starts[n];
+            let end = loaded
+                .page_starts
+                .get(n + 1)
+                .map_or(loaded.text.chars().count(), |next| {
+                    next - PAGE_SEPARATOR.len()
+                });
+            loaded
+                .text
+                .chars()
+                .skip(start)
+                .take(end - start)
+                .collect::<String>()
+        };
+        assert_eq!(page(0), "First page");
+        assert_eq!(page(1), "");
+        assert_eq!(page(2), "Third page");
+        assert_eq!(
+            loaded.page_starts[2],
+            "First page".len() + 2 * PAGE_SEPARATOR.len()
+        );
+    }
+
+    #[test]
+    fn reads_the_information_dictionary() {
+        let info = dictionary! {
+            "Title" => Object::string_literal("Annual report"),
+            "Author" => Object::string_literal(" Ada Lovelace "),
+            "Keywords" => Object::string_literal(""),
+            "CreationDate" => Object::string_literal("D:20240131093000Z"),
+            "ModDate" => Object::string_literal("D:20240201"),
+        };
+        let loaded = load_pdf(&pdf(&["Text"], Some(info))).unwrap();
+
+        assert_eq!(loaded.title.as_deref(), Some("Annual report"));
+        assert_eq!(loaded.author.as_deref(), Some("Ada Lovelace"));
+        assert_eq!(loaded.keywords, None);
+        assert_eq!(loaded.created.as_deref(), Some("2024-01-31T09:30:00"));
+        assert_eq!(loaded.modified.as_deref(), Some("2024-02-01"));
+    }
+
+    #[test]
+    fn rejects_other_data() {
+        assert!(load_pdf(b"not a pdf").is_err());
+    }
+}
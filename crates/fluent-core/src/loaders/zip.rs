@@ -0,0 +1,179 @@
+// crates/fluent-core/src/loaders/zip.rs
+use std::io::{Cursor, Read};
+
+use anyhow::{anyhow, Result};
+use zip::result::ZipError;
+
+/// Largest entry read from an archive, against decompression bombs.
+const MAX_ENTRY_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Reads the entries of a zip archive, the container of DOCX, PPTX, XLSX
+/// and EPUB files.
+pub(super) struct ZipArchive<'a> {
+    archive: zip::ZipArchive<Cursor<&'a [u8]>>,
+    max_entry_size: u64,
+}
+
+impl<'a> ZipArchive<'a> {
+    pub(super) fn new(data: &'a [u8]) -> Result<Self> {
+        let archive = zip::ZipArchive::new(Cursor::new(data))
+            .map_err(|e| anyhow!("Not a zip archive: {}", e))?;
+        Ok(Self {
+            archive,
+            max_entry_size: MAX_ENTRY_SIZE,
+        })
+    }
+
+    pub(super) fn names(&self) -> impl Iterator<Item = &str> {
+        self.archive.file_names()
+    }
+
+    /// The contents of an entry, or `None` when there is no such entry.
+    pub(super) fn read(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        // Clones share the central directory, so this only copies the cursor.
+        let mut archive = self.archive.clone();
+        let entry = match archive.by_name(name) {
+            Ok(entry) => entry,
+            Err(ZipError::FileNotFound) => return Ok(None),
+            Err(e) => return Err(anyhow!("Corrupt zip entry {}: {}", name, e)),
+        };
+
+        let mut contents = Vec::new();
+        entry
+            .take(self.max_entry_size + 1)
+            .read_to_end(&mut contents)
+            .map_err(|e| anyhow!("Corrupt zip entry {}: {}", name, e))?;
+        if contents.len() as u64 > self.max_entry_size {
+            return Err(anyhow!(
+                "Zip entry {} is larger than {} MB",
+                name,
+                self.max_entry_size / (1024 * 1024)
+            ));
+        }
+        Ok(Some(contents))
+    }
+
+    /// An entry as text, or `None` when there is no such entry.
+    pub(super) fn read_string(&self, name: &str) -> Result<Option<String>> {
+        Ok(self
+            .read(name)?
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned()))
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use std::io::Write;
+
+    use zip::write::SimpleFileOptions;
+    use zip::{CompressionMethod, ZipWriter};
+
+    use super::*;
+
+    /// Builds a zip archive of entries stored with `method`.
+    fn zip_with(entries: &[(&str, &str)], method: CompressionMethod) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(method);
+        for (name, contents) in entries {
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    /// Builds a zip archive of deflated entries.
+    pub(in crate::loaders) fn zip(entries: &[(&str, &str)]) -> Vec<u8> {
+        zip_with(entries, CompressionMethod::Deflated)
+    }
+
+    /// The offset of the compressed size in a local file header.
+    const COMPRESSED_SIZE: usize = 18;
+    /// The size of an end of central directory record without a comment.
+    const END_RECORD_SIZE: usize = 22;
+
+    #[test]
+    fn reads_entries_by_name() {
+        let data = zip(&[("a.txt", "first"), ("dir/b.xml", "<b>second</b>")]);
+        let archive = ZipArchive::new(&data).unwrap();
+        assert_eq!(archive.names().collect::<Vec<_>>(), ["a.txt", "dir/b.xml"]);
+        assert_eq!(archive.read_string("a.txt").unwrap().unwrap(), "first");
+        assert_eq!(
+            archive.read_string("dir/b.xml").unwrap().unwrap(),
+            "<b>second</b>"
+        );
+        assert!(archive.read("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn reads_stored_entries() {
+        let data = zip_with(
+            &[("mimetype", "application/epub+zip")],
+            CompressionMethod::Stored,
+        );
+        let archive = ZipArchive::new(&data).unwrap();
+        assert_eq!(
+            archive.read_string("mimetype").unwrap().unwrap(),
+            "application/epub+zip"
+        );
+    }
+
+    #[test]
+    fn oversized_entry_is_an_error() {
+        let data = zip(&[("big.txt", &"x".repeat(100))]);
+        let mut archive = ZipArchive::new(&data).unwrap();
+        archive.max_entry_size = 100;
+        assert_eq!(archive.read("big.txt").unwrap().unwrap().len(), 100);
+
+        archive.max_entry_size = 99;
+        let error = archive.read("big.txt").unwrap_err().to_string();
+        assert!(error.contains("big.txt"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_other_data() {
+        assert!(ZipArchive::new(b"not a zip").is_err());
+        let data = zip(&[("a.txt", "first")]);
+        assert!(ZipArchive::new(&data[..data.len() - 30]).is_err());
+    }
+
+    #[test]
+    fn truncated_local_header_is_an_error() {
+        let data = zip(&[("a.txt", "first")]);
+        // Keep the first 20 bytes of the local header, then the central
+        // directory and end record, moved up to follow them.
+        let directory = data
+            .windows(4)
+            .position(|bytes| bytes == b"PK\x01\x02")
+            .unwrap();
+        let mut truncated = data[..20].to_vec();
+        truncated.extend(&data[directory..]);
+        let end = truncated.len() - END_RECORD_SIZE;
+        truncated[end + 16..end + 20].copy_from_slice(&20u32.to_le_bytes());
+
+        let result = ZipArchive::new(&truncated).and_then(|archive| archive.read("a.txt"));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn oversized_compressed_size_is_caught() {
+        for method in [CompressionMethod::Stored, CompressionMethod::Deflated] {
+            let mut data = zip_with(&[("a.txt", "first")], method);
+            let size = (data.len() as u32 * 2).to_le_bytes();
+            data[COMPRESSED_SIZE..COMPRESSED_SIZE + 4].copy_from_slice(&size);
+            // The central directory has its own copy of the size.
+            let directory = data
+                .windows(4)
+                .position(|bytes| bytes == b"PK\x01\x02")
+                .unwrap();
+            data[directory + 20..directory + 24].copy_from_slice(&size);
+
+            let archive = ZipArchive::new(&data).unwrap();
+            match method {
+                // Reading runs into the central directory and fails the checksum.
+                CompressionMethod::Stored => assert!(archive.read("a.txt").is_err()),
+                // A deflate stream marks its own end, so nothing past it is read.
+                _ => assert_eq!(archive.read_string("a.txt").unwrap().unwrap(), "first"),
+            }
+        }
+    }
+}
@@ -7,11 +7,12 @@ use neo4rs::{
 use chrono::Duration as ChronoDuration;
 
 use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
 use log::{debug, error, info, warn};
-use pdf_extract::extract_text;
 use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
 use std::collections::{HashMap, HashSet};
-use std::path::Path;
+use std::path::{Path, MAIN_SEPARATOR};
 use std::sync::RwLock;
 use uuid::Uuid;
 
@@ -19,12 +20,31 @@ use rust_stemmers::{Algorithm, Stemmer};
 use serde::{Deserialize, Serialize};
 
 use crate::config::Neo4jConfig;
-use crate::traits::{DocumentProcessor, DocxProcessor};
+use crate::embeddings::{create_embedding_provider, resolve_dimension, EmbeddingProvider};
+use crate::loaders::{self, page_range, METADATA_KEYS};
+use crate::search::{escape_fulltext, fuse, HitKind, SearchHit, SearchScope};
+use crate::traits::{
+    load_file, CsvProcessor, DocumentProcessor, DocxProcessor, EpubProcessor, HtmlProcessor,
+    PptxProcessor, TextProcessor, XlsxProcessor,
+};
 use crate::types::DocumentStatistics;
-use crate::utils::chunking::chunk_document;
-use crate::voyageai_client::{get_voyage_embedding, EMBEDDING_DIMENSION};
-use tokio::fs::File;
-use tokio::io::AsyncReadExt;
+use crate::utils::chunking::{chunk_text, Chunk, ChunkingConfig};
+
+/// The vector index over `Embedding` nodes.
+const VECTOR_INDEX: &str = "document_embedding_index";
+
+/// Full-text indexes searched by `hybrid_search`, by node label.
+const FULLTEXT_INDEXES: &[(&str, &str)] = &[
+    ("Chunk", "chunkContentIndex"),
+    ("Question", "questionContentIndex"),
+    ("Response", "responseContentIndex"),
+];
+
+/// Embedding requests in flight at once while upserting a document.
+const DEFAULT_EMBEDDING_CONCURRENCY: usize = 4;
+
+/// Called with the number of chunks embedded so far and the total.
+pub type UpsertProgress<'a> = dyn Fn(usize, usize) + Send + Sync + 'a;
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct VoyageAIConfig {
@@ -36,7 +56,9 @@ pub struct Neo4jClient {
     graph: Graph,
     document_count: RwLock<usize>,
     word_document_count: RwLock<HashMap<String, usize>>,
-    voyage_ai_config: Option<VoyageAIConfig>,
+    embedder: Option<Box<dyn EmbeddingProvider>>,
+    embedding_concurrency: usize,
+    chunking: ChunkingConfig,
     query_llm: Option<String>,
 }
 impl Neo4jClient {
@@ -54,6 +76,10 @@ impl Neo4jClient {
     pub fn get_query_llm(&self) -> Option<&String> {
         self.query_llm.as_ref()
     }
+    /// How documents are chunked when an upsert does not say otherwise.
+    pub fn chunking(&self) -> &ChunkingConfig {
+        &self.chunking
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -63,6 +89,8 @@ pub struct InteractionStats {
     pub total_tokens: u32,
     pub response_time: f64, // in seconds
     pub finish_reason: String,
+    /// In US dollars; `None` when the model's price is unknown.
+    pub cost: Option<f64>,
 }
 
 #[derive(Debug, Clone)]
@@ -72,6 +100,162 @@ pub struct Embedding {
     pub model: String,
 }
 
+/// A file's text as extracted for upserting.
+struct ExtractedText {
+    content: String,
+    /// Metadata terms found by the file's loader.
+    metadata: Vec<String>,
+    /// Character offset where each page starts, when the pages are known.
+    page_starts: Vec<usize>,
+}
+
+/// A document chunk found by `Neo4jClient::retrieve`.
+#[derive(Debug, Clone, Serialize)]
+pub struct RetrievedChunk {
+    pub document_id: String,
+    /// The path the document was upserted from, or its id for documents
+    /// upserted before paths were recorded.
+    pub source: String,
+    pub chunk_index: i64,
+    /// The first and last page of the chunk, from 1, for documents with
+    /// pages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_start: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_end: Option<i64>,
+    pub content: String,
+    /// Similarity to the question, from 0 to 1.
+    pub score: f64,
+}
+
+/// What an upsert did with a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpsertStatus {
+    /// No document had been upserted from the source before.
+    Added,
+    /// The content or chunking changed, so the chunks were replaced.
+    Updated,
+    /// Same content and chunking as last time; only metadata was added.
+    Unchanged,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpsertOutcome {
+    pub document_id: String,
+    /// The canonical path the document was read from.
+    pub source: String,
+    pub status: UpsertStatus,
+}
+
+/// A stored document, as listed by `Neo4jClient::list_documents`.
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentSummary {
+    pub id: String,
+    /// The path the document was upserted from, or its id for documents
+    /// upserted before paths were recorded.
+    pub source: String,
+    pub metadata: Vec<String>,
+    pub chunk_count: i64,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+/// One page of documents from `Neo4jClient::list_documents`.
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentList {
+    /// Documents matching the filter, on every page.
+    pub total: i64,
+    pub offset: usize,
+    pub documents: Vec<DocumentSummary>,
+}
+
+/// A chunk of a document, as shown by `Neo4jClient::document_details`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChunkSummary {
+    pub id: String,
+    pub index: i64,
+    /// Character offsets of the chunk in the document, when recorded.
+    pub start: Option<i64>,
+    pub end: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_start: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_end: Option<i64>,
+    pub content: String,
+    /// The model the chunk was embedded with, when recorded.
+    pub embedding_model: Option<String>,
+}
+
+/// A document with its chunks and enrichment.
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentDetails {
+    #[serde(flatten)]
+    pub summary: DocumentSummary,
+    /// Length of the document's text, in characters.
+    pub content_length: i64,
+    pub themes: Vec<String>,
+    pub keywords: Vec<String>,
+    pub clusters: Vec<String>,
+    pub chunks: Vec<ChunkSummary>,
+}
+
+/// What `Neo4jClient::reindex_documents` did.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReindexSummary {
+    /// The embedding model chunks were embedded with.
+    pub model: String,
+    /// Documents with at least one chunk embedded again.
+    pub documents: usize,
+    pub chunks: usize,
+    /// Questions and responses embedded again, by a reindex without a
+    /// metadata filter.
+    pub interactions: usize,
+    /// Whether the vector index was rebuilt for a new vector size.
+    pub index_rebuilt: bool,
+}
+
+/// Called by `reindex_documents` with a document's source, or `questions` or
+/// `responses`, the nodes embedded so far and the total.
+pub type ReindexProgress<'a> = dyn Fn(&str, usize, usize) + Send + Sync + 'a;
+
+/// The columns read by `document_summary`, for a `d` and `chunk_count` in
+/// scope.
+const DOCUMENT_SUMMARY: &str = "d.id AS id,
+    coalesce(d.source, d.id) AS source,
+    coalesce(d.metadata, []) AS metadata,
+    chunk_count,
+    toString(d.created_at) AS created_at,
+    toString(d.updated_at) AS updated_at";
+
+fn document_summary(row: &Row) -> Result<DocumentSummary> {
+    Ok(DocumentSummary {
+        id: row.get("id")?,
+        source: row.get("source")?,
+        metadata: row.get("metadata")?,
+        chunk_count: row.get("chunk_count")?,
+        created_at: row.get("created_at").ok(),
+        updated_at: row.get("updated_at").ok(),
+    })
+}
+
+/// A document already stored for a source.
+struct StoredDocument {
+    id: String,
+    content_hash: Option<String>,
+    chunking: Option<String>,
+}
+
+/// The path a document is stored under: canonical, so the same file is
+/// found again however it is named.
+async fn document_source(file_path: &Path) -> String {
+    tokio::fs::canonicalize(file_path)
+        .await
+        .unwrap_or_else(|_| file_path.to_path_buf())
+        .display()
+        .to_string()
+}
+
 #[derive(Debug)]
 pub struct EnrichmentConfig {
     pub themes_keywords_interval: ChronoDuration,
@@ -97,11 +281,20 @@ impl Neo4jClient {
 
         let graph = Graph::connect(graph_config).await?;
 
+        let embedding = config.embedding_config();
         Ok(Neo4jClient {
             graph,
             document_count: Default::default(),
             word_document_count: Default::default(),
-            voyage_ai_config: config.voyage_ai.clone(),
+            embedder: embedding
+                .as_ref()
+                .map(create_embedding_provider)
+                .transpose()?,
+            embedding_concurrency: embedding
+                .and_then(|embedding| embedding.concurrency)
+                .unwrap_or(DEFAULT_EMBEDDING_CONCURRENCY)
+                .max(1),
+            chunking: config.chunking.unwrap_or_default(),
             query_llm: config.query_llm.clone(),
         })
     }
@@ -117,6 +310,7 @@ impl Neo4jClient {
             "CREATE INDEX IF NOT EXISTS FOR (stats:InteractionStats) ON (stats.id)",
             "CREATE INDEX IF NOT EXISTS FOR (e:Embedding) ON (e.id)",
             "CREATE INDEX IF NOT EXISTS FOR (d:Document) ON (d.id)",
+            "CREATE INDEX IF NOT EXISTS FOR (d:Document) ON (d.source)",
             "CREATE INDEX IF NOT EXISTS FOR (c:Chunk) ON (c.id)",
         ];
 
@@ -125,21 +319,12 @@ impl Neo4jClient {
             let _ = self.graph.execute(query(query_str)).await?;
         }
 
-        // Create a vector index for embeddings
-        let vector_index_query = format!(
-            "CALL db.index.vector.createNodeIndex(
-                'document_embedding_index',
-                'Embedding',
-                'vector',
-                {},
-                'cosine'
-            )",
-            EMBEDDING_DIMENSION
-        );
-
-        match self.graph.execute(query(&vector_index_query)).await {
-            Ok(_) => debug!("Vector index created successfully for Document Embedding nodes"),
-            Err(e) => warn!("Failed to create vector index for Document Embedding nodes: {}. This might be normal if the index already exists.", e),
+        if self.embedder.is_some() {
+            if let Err(e) = self.ensure_vector_index().await {
+                warn!("Failed to create vector index for Document Embedding nodes: {}", e);
+            }
+        } else {
+            debug!("No embedding config found, skipping vector index creation");
         }
 
         // Optionally, we can also create full-text indexes for content fields if needed
@@ -160,6 +345,246 @@ impl Neo4jClient {
         Ok(())
     }
 
+    /// Creates the vector index over `Embedding` nodes, sized for the
+    /// embedding model, unless it already exists.
+    pub async fn ensure_vector_index(&self) -> Result<()> {
+        let embedder = self
+            .embedder
+            .as_ref()
+            .ok_or_else(|| anyhow!("Embedding configuration not found"))?;
+        let vector_index_query = format!(
+            "CREATE VECTOR INDEX {} IF NOT EXISTS
+            FOR (e:Embedding) ON (e.vector)
+            OPTIONS {{indexConfig: {{
+                `vector.dimensions`: {},
+                `vector.similarity_function`: 'cosine'
+            }}}}",
+            VECTOR_INDEX,
+            resolve_dimension(embedder.as_ref()).await?
+        );
+        self.graph.run(query(&vector_index_query)).await?;
+        debug!("Vector index {} is in place", VECTOR_INDEX);
+        Ok(())
+    }
+
+    /// Finds the `top_k` document chunks closest to `question` in the vector
+    /// index. With `metadata_filter`, only documents tagged with every term
+    /// are searched.
+    pub async fn retrieve(
+        &self,
+        question: &str,
+        top_k: usize,
+        metadata_filter: &[String],
+    ) -> Result<Vec<RetrievedChunk>> {
+        let embedder = self
+            .embedder
+            .as_ref()
+            .ok_or_else(|| anyhow!("Embedding configuration not found"))?;
+        let vector = embedder.embed_query(question).await?;
+
+        // The index also holds question and response embeddings, and the
+        // filter is applied after the search, so ask for extra candidates.
+        let candidates = (top_k * 10).max(100);
+        let query = query(
+            "
+            CALL db.index.vector.queryNodes($index, $candidates, $vector)
+            YIELD node, score
+            MATCH (d:Document)-[:HAS_CHUNK]->(c:Chunk)-[:HAS_EMBEDDING]->(node)
+            WHERE all(term IN $filter WHERE term IN coalesce(d.metadata, []))
+            RETURN d.id AS document_id,
+                   coalesce(d.source, d.id) AS source,
+                   c.index AS chunk_index,
+                   c.page_start AS page_start,
+                   c.page_end AS page_end,
+                   c.content AS content,
+                   score
+            ORDER BY score DESC
+            LIMIT $top_k
+            ",
+        )
+        .param("index", VECTOR_INDEX)
+        .param("candidates", candidates as i64)
+        .param("vector", vector)
+        .param("filter", metadata_filter.to_vec())
+        .param("top_k", top_k as i64);
+
+        let mut result = self.graph.execute(query).await?;
+        let mut chunks = Vec::new();
+        while let Some(row) = result.next().await? {
+            chunks.push(RetrievedChunk {
+                document_id: row.get("document_id")?,
+                source: row.get("source")?,
+                chunk_index: row.get("chunk_index")?,
+                page_start: row.get("page_start").ok(),
+                page_end: row.get("page_end").ok(),
+                content: row.get("content")?,
+                score: row.get("score")?,
+            });
+        }
+        debug!("Retrieved {} chunks for question", chunks.len());
+        Ok(chunks)
+    }
+
+    /// Creates the full-text and vector indexes `hybrid_search` uses,
+    /// unless they already exist.
+    pub async fn ensure_search_indexes(&self) -> Result<()> {
+        for (label, index) in FULLTEXT_INDEXES {
+            let index_query = format!(
+                "CREATE FULLTEXT INDEX {} IF NOT EXISTS FOR (n:{}) ON EACH [n.content]",
+                index, label
+            );
+            self.graph.run(query(&index_query)).await?;
+        }
+        self.ensure_vector_index().await
+    }
+
+    /// Searches document chunks and past interactions by keyword (BM25
+    /// full-text search) and by meaning (vector similarity), and merges the
+    /// two rankings with reciprocal rank fusion.
+    pub async fn hybrid_search(
+        &self,
+        search: &str,
+        scope: SearchScope,
+        limit: usize,
+    ) -> Result<Vec<SearchHit>> {
+        // Fusion favours hits found by both searches, so look deeper than
+        // the final limit in each.
+        let depth = (limit * 3).max(20);
+        let text = self.fulltext_search(search, scope, depth).await?;
+        let vector = self.vector_search(search, scope, depth).await?;
+        debug!(
+            "Hybrid search found {} full-text and {} vector hits",
+            text.len(),
+            vector.len()
+        );
+        Ok(fuse(text, vector, limit))
+    }
+
+    async fn fulltext_search(
+        &self,
+        search: &str,
+        scope: SearchScope,
+        limit: usize,
+    ) -> Result<Vec<SearchHit>> {
+        let search = escape_fulltext(search);
+        if search.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut scored = Vec::new();
+        for (label, index) in FULLTEXT_INDEXES {
+            let wanted = if *label == "Chunk" {
+                scope.documents()
+            } else {
+                scope.interactions()
+            };
+            if !wanted {
+                continue;
+            }
+            let query = query(
+                "
+                CALL db.index.fulltext.queryNodes($index, $search) YIELD node, score
+                OPTIONAL MATCH (d:Document)-[:HAS_CHUNK]->(node)
+                OPTIONAL MATCH (s:Session)-[:CONTAINS]->(:Interaction)-[:HAS_QUESTION|HAS_RESPONSE]->(node)
+                WITH node, score,
+                     head(collect(DISTINCT coalesce(d.source, d.id))) AS document,
+                     head(collect(DISTINCT s.id)) AS session
+                RETURN node.id AS id,
+                       labels(node)[0] AS kind,
+                       node.content AS content,
+                       coalesce(document, session, '') AS source,
+                       node.index AS chunk_index,
+                       node.page_start AS page_start,
+                       node.page_end AS page_end,
+                       toString(node.timestamp) AS timestamp,
+                       score
+                ORDER BY score DESC
+                LIMIT $limit
+                ",
+            )
+            .param("index", *index)
+            .param("search", search.as_str())
+            .param("limit", limit as i64);
+            scored.extend(self.search_hits(query).await?);
+        }
+
+        // BM25 scores from separate indexes are close enough to compare.
+        scored.sort_by(|a, b| b.score.total_cmp(&a.score));
+        scored.truncate(limit);
+        Ok(scored)
+    }
+
+    async fn vector_search(
+        &self,
+        search: &str,
+        scope: SearchScope,
+        limit: usize,
+    ) -> Result<Vec<SearchHit>> {
+        let Some(embedder) = &self.embedder else {
+            debug!("No embedding config found, skipping vector search");
+            return Ok(Vec::new());
+        };
+        let vector = embedder.embed_query(search).await?;
+
+        let query = query(
+            "
+            CALL db.index.vector.queryNodes($index, $candidates, $vector) YIELD node, score
+            MATCH (parent)-[:HAS_EMBEDDING]->(node)
+            WHERE (parent:Chunk AND $documents)
+               OR ((parent:Question OR parent:Response) AND $interactions)
+            OPTIONAL MATCH (d:Document)-[:HAS_CHUNK]->(parent)
+            OPTIONAL MATCH (s:Session)-[:CONTAINS]->(:Interaction)-[:HAS_QUESTION|HAS_RESPONSE]->(parent)
+            WITH parent, max(score) AS score,
+                 head(collect(DISTINCT coalesce(d.source, d.id))) AS document,
+                 head(collect(DISTINCT s.id)) AS session
+            RETURN parent.id AS id,
+                   labels(parent)[0] AS kind,
+                   parent.content AS content,
+                   coalesce(document, session, '') AS source,
+                   parent.index AS chunk_index,
+                   parent.page_start AS page_start,
+                   parent.page_end AS page_end,
+                   toString(parent.timestamp) AS timestamp,
+                   score
+            ORDER BY score DESC
+            LIMIT $limit
+            ",
+        )
+        .param("index", VECTOR_INDEX)
+        .param("candidates", (limit * 4) as i64)
+        .param("vector", vector)
+        .param("documents", scope.documents())
+        .param("interactions", scope.interactions())
+        .param("limit", limit as i64);
+        self.search_hits(query).await
+    }
+
+    /// Reads search results, ranked by the `score` column.
+    async fn search_hits(&self, query: neo4rs::Query) -> Result<Vec<SearchHit>> {
+        let mut result = self.graph.execute(query).await?;
+        let mut hits = Vec::new();
+        while let Some(row) = result.next().await? {
+            let label: String = row.get("kind")?;
+            let Some(kind) = HitKind::from_label(&label) else {
+                continue;
+            };
+            hits.push(SearchHit {
+                kind,
+                id: row.get("id")?,
+                content: row.get("content")?,
+                score: row.get("score")?,
+                text_rank: None,
+                vector_rank: None,
+                source: row.get("source")?,
+                chunk_index: row.get("chunk_index").ok(),
+                page_start: row.get("page_start").ok(),
+                page_end: row.get("page_end").ok(),
+                timestamp: row.get("timestamp").ok(),
+            });
+        }
+        Ok(hits)
+    }
+
     pub async fn create_or_update_session(&self, session: &Neo4jSession) -> Result<String> {
         let query_str = r#"
         MERGE (s:Session {id: $id})
@@ -206,6 +631,7 @@ impl Neo4jClient {
         let query_str = r#"
         MERGE (s:Session {id: $session_id})
         ON CREATE SET s.created_at = $timestamp
+        SET s.total_cost = coalesce(s.total_cost, 0.0) + coalesce($cost, 0.0)
 
         MERGE (q:Question {content: $request})
         ON CREATE SET q.id = $question_id, q.timestamp = $timestamp
@@ -231,7 +657,8 @@ impl Neo4jClient {
             completion_tokens: $completion_tokens,
             total_tokens: $total_tokens,
             response_time: $response_time,
-            finish_reason: $finish_reason
+            finish_reason: $finish_reason,
+            cost: $cost
         })
 
         MERGE (s)-[:CONTAINS]->(i)
@@ -296,6 +723,12 @@ impl Neo4jClient {
                     .param(
                         "finish_reason",
                         BoltType::String(BoltString::from(stats.finish_reason.as_str())),
+                    )
+                    .param(
+                        "cost",
+                        stats.cost.map_or(BoltType::Null(BoltNull), |cost| {
+                            BoltType::Float(BoltFloat::new(cost))
+                        }),
                     ),
             )
             .await?;
@@ -312,10 +745,16 @@ impl Neo4jClient {
             debug!("Model name: {}", model_name);
             debug!("Stats id: {}", stats_id);
 
-            if let Some(voyage_config) = &self.voyage_ai_config {
-                debug!("Voyage AI config found, creating embeddings");
+            if let Some(embedder) = &self.embedder {
+                debug!("Embedding config found, creating embeddings");
                 match self
-                    .create_embeddings(request, response, &question_id, &response_id, voyage_config)
+                    .create_embeddings(
+                        request,
+                        response,
+                        &question_id,
+                        &response_id,
+                        embedder.as_ref(),
+                    )
                     .await
                 {
                     Ok(_) => debug!("Created embeddings for interaction {}", interaction_id),
@@ -348,7 +787,7 @@ impl Neo4jClient {
                     Err(e) => warn!("Failed to enrich response {}: {:?}", response_id, e),
                 }
             } else {
-                debug!("No Voyage AI config found, skipping embedding creation and document enrichment");
+                debug!("No embedding config found, skipping embedding creation and document enrichment");
             }
 
             Ok(interaction_id)
@@ -363,21 +802,21 @@ impl Neo4jClient {
         response: &str,
         question_id: &str,
         response_id: &str,
-        voyage_config: &VoyageAIConfig,
+        embedder: &dyn EmbeddingProvider,
     ) -> Result<()> {
-        let question_embedding = get_voyage_embedding(request, voyage_config).await?;
-        let response_embedding = get_voyage_embedding(response, voyage_config).await?;
+        let question_embedding = embedder.embed(request).await?;
+        let response_embedding = embedder.embed(response).await?;
 
         let question_embedding_node = Embedding {
             id: Uuid::new_v4().to_string(),
             vector: question_embedding,
-            model: voyage_config.model.clone(),
+            model: embedder.model().to_string(),
         };
 
         let response_embedding_node = Embedding {
             id: Uuid::new_v4().to_string(),
             vector: response_embedding,
-            model: voyage_config.model.clone(),
+            model: embedder.model().to_string(),
         };
 
         self.create_embedding(&question_embedding_node, question_id, "Question")
@@ -443,153 +882,925 @@ impl Neo4jClient {
     }
 
     pub async fn upsert_document(&self, file_path: &Path, metadata: &[String]) -> Result<String> {
+        let outcome = self
+            .upsert_document_with_progress(file_path, metadata, &self.chunking, &|_, _| {})
+            .await?;
+        Ok(outcome.document_id)
+    }
+
+    /// Like `upsert_document`, chunking as set by `chunking` and calling
+    /// `on_progress` with the chunks embedded so far and the total after each
+    /// batch is written.
+    ///
+    /// Documents are identified by their source path. A document whose
+    /// content and chunking are unchanged is skipped; a changed one has its
+    /// chunks and embeddings replaced.
+    pub async fn upsert_document_with_progress(
+        &self,
+        file_path: &Path,
+        metadata: &[String],
+        chunking: &ChunkingConfig,
+        on_progress: &UpsertProgress<'_>,
+    ) -> Result<UpsertOutcome> {
         debug!("Upserting document from file: {:?}", file_path);
 
-        let content = self.extract_content(file_path).await?;
+        let ExtractedText {
+            content,
+            metadata: document_metadata,
+            page_starts,
+        } = self.extract_content(file_path).await?;
+        let source = document_source(file_path).await;
+        let content_hash = format!("{:x}", Sha256::digest(content.as_bytes()));
+        let chunking_key = serde_json::to_string(chunking)?;
+
+        // A document upserted before sources were recorded can match as
+        // well as a later one; keep the latest and remove the rest.
+        let mut stored = self
+            .documents_from_source(&source, &content)
+            .await?
+            .into_iter();
+        let current = stored.next();
+        for stale in stored {
+            debug!("Removing stale copy {} of {}", stale.id, source);
+            self.delete_document(&stale.id).await?;
+        }
+
+        let (document_id, status) = match current {
+            Some(document)
+                if document.content_hash.as_deref() == Some(content_hash.as_str())
+                    && document.chunking.as_deref() == Some(chunking_key.as_str()) =>
+            {
+                self.merge_metadata(&document.id, metadata, &document_metadata)
+                    .await?;
+                debug!("Document {} is unchanged", source);
+                return Ok(UpsertOutcome {
+                    document_id: document.id,
+                    source,
+                    status: UpsertStatus::Unchanged,
+                });
+            }
+            Some(document) => {
+                self.delete_chunks(&document.id).await?;
+                // The hash is set again once the new chunks are written, so
+                // an interrupted upsert is redone next time.
+                let query = query(
+                    "
+                MATCH (d:Document {id: $id})
+                SET d.content = $content, d.updated_at = datetime()
+                REMOVE d.content_hash, d.chunking
+                ",
+                )
+                .param("id", document.id.clone())
+                .param("content", content.clone());
+                self.graph.run(query).await?;
+                self.merge_metadata(&document.id, metadata, &document_metadata)
+                    .await?;
+                (document.id, UpsertStatus::Updated)
+            }
+            None => {
+                let document_id = Uuid::new_v4().to_string();
+                let mut all_metadata = metadata.to_vec();
+                for term in document_metadata {
+                    if !all_metadata.contains(&term) {
+                        all_metadata.push(term);
+                    }
+                }
+                let query = query(
+                    "
+                CREATE (d:Document {
+                    id: $id,
+                    source: $source,
+                    content: $content,
+                    metadata: $metadata,
+                    created_at: datetime()
+                })
+                ",
+                )
+                .param("id", document_id.clone())
+                .param("source", source.clone())
+                .param("content", content.clone())
+                .param("metadata", all_metadata);
+                self.graph.run(query).await?;
+                (document_id, UpsertStatus::Added)
+            }
+        };
+
+        let config = EnrichmentConfig {
+            themes_keywords_interval: ChronoDuration::hours(1),
+            clustering_interval: ChronoDuration::days(1),
+            sentiment_interval: ChronoDuration::hours(1),
+        };
+
+        let chunks = chunk_text(&content, chunking);
+        debug!(
+            "Split document {} into {} chunks ({:?}, {} tokens, {} overlap)",
+            document_id,
+            chunks.len(),
+            chunking.strategy,
+            chunking.size,
+            chunking.overlap
+        );
+        self.create_chunks_and_embeddings(&document_id, &chunks, &page_starts, on_progress)
+            .await?;
 
-        let document_id = Uuid::new_v4().to_string();
         let query = query(
             "
-        MERGE (d:Document {content: $content})
-        ON CREATE SET
-            d.id = $id,
-            d.metadata = $metadata,
-            d.created_at = datetime()
-        ON MATCH SET
-            d.metadata = d.metadata + $new_metadata,
-            d.updated_at = datetime()
-        RETURN d.id as document_id
+        MATCH (d:Document {id: $id})
+        SET d.content_hash = $content_hash, d.chunking = $chunking
         ",
         )
         .param("id", document_id.clone())
-        .param("content", content.clone()) // Clone here
-        .param("metadata", metadata)
-        .param("new_metadata", metadata);
+        .param("content_hash", content_hash)
+        .param("chunking", chunking_key);
+        self.graph.run(query).await?;
+
+        self.enrich_document_incrementally(&document_id, "Document", &config)
+            .await?;
+        Ok(UpsertOutcome {
+            document_id,
+            source,
+            status,
+        })
+    }
+
+    /// Documents upserted from `source`, most recently changed first.
+    /// Documents upserted before sources were recorded are matched on their
+    /// `content` and given the source.
+    async fn documents_from_source(
+        &self,
+        source: &str,
+        content: &str,
+    ) -> Result<Vec<StoredDocument>> {
+        let query = query(
+            "
+        MATCH (d:Document)
+        WHERE d.source = $source OR (d.source IS NULL AND d.content = $content)
+        SET d.source = $source
+        RETURN d.id AS id, d.content_hash AS content_hash, d.chunking AS chunking
+        ORDER BY coalesce(d.updated_at, d.created_at) DESC
+        ",
+        )
+        .param("source", source)
+        .param("content", content);
 
         let mut result = self.graph.execute(query).await?;
+        let mut documents = Vec::new();
+        while let Some(row) = result.next().await? {
+            documents.push(StoredDocument {
+                id: row.get("id")?,
+                content_hash: row.get("content_hash").ok(),
+                chunking: row.get("chunking").ok(),
+            });
+        }
+        Ok(documents)
+    }
 
-        let document_id = if let Some(row) = result.next().await? {
-            row.get::<String>("document_id")?
-        } else {
-            return Err(anyhow!("Failed to upsert document"));
+    /// Deletes a document with its chunks and their embeddings, and the
+    /// themes, keywords, clusters and sentiments no other node has.
+    pub async fn delete_document(&self, document_id: &str) -> Result<()> {
+        self.delete_chunks(document_id).await?;
+        let query = query(
+            "
+        MATCH (d:Document {id: $id})
+        OPTIONAL MATCH (d)-[:HAS_THEME|HAS_KEYWORD|BELONGS_TO|HAS_SENTIMENT]->(n)
+        DETACH DELETE d
+        WITH DISTINCT n
+        WHERE n IS NOT NULL AND NOT (n)--()
+        DELETE n
+        ",
+        )
+        .param("id", document_id);
+        self.graph.run(query).await?;
+        Ok(())
+    }
+
+    /// Lists documents by source, `limit` at a time from `offset`. With
+    /// `metadata_filter`, only documents tagged with every term are listed.
+    pub async fn list_documents(
+        &self,
+        metadata_filter: &[String],
+        offset: usize,
+        limit: usize,
+    ) -> Result<DocumentList> {
+        let count_query = query(
+            "
+        MATCH (d:Document)
+        WHERE all(term IN $filter WHERE term IN coalesce(d.metadata, []))
+        RETURN count(d) AS total
+        ",
+        )
+        .param("filter", metadata_filter.to_vec());
+        let total = match self.graph.execute(count_query).await?.next().await? {
+            Some(row) => row.get("total")?,
+            None => 0,
         };
 
-        let config = EnrichmentConfig {
-            themes_keywords_interval: ChronoDuration::hours(1),
-            clustering_interval: ChronoDuration::days(1),
-            sentiment_interval: ChronoDuration::hours(1),
+        let query = query(&format!(
+            "
+        MATCH (d:Document)
+        WHERE all(term IN $filter WHERE term IN coalesce(d.metadata, []))
+        WITH d
+        ORDER BY coalesce(d.source, d.id), d.id
+        SKIP $offset
+        LIMIT $limit
+        OPTIONAL MATCH (d)-[:HAS_CHUNK]->(c:Chunk)
+        WITH d, count(c) AS chunk_count
+        RETURN {}
+        ORDER BY source, id
+        ",
+            DOCUMENT_SUMMARY
+        ))
+        .param("filter", metadata_filter.to_vec())
+        .param("offset", offset.min(i64::MAX as usize) as i64)
+        .param("limit", limit.min(i64::MAX as usize) as i64);
+
+        let mut result = self.graph.execute(query).await?;
+        let mut documents = Vec::new();
+        while let Some(row) = result.next().await? {
+            documents.push(document_summary(&row)?);
+        }
+        Ok(DocumentList {
+            total,
+            offset,
+            documents,
+        })
+    }
+
+    /// Finds a document by its id, or by the path it was upserted from.
+    pub async fn find_document(&self, reference: &str) -> Result<Option<DocumentSummary>> {
+        let source = document_source(Path::new(reference)).await;
+        let query = query(&format!(
+            "
+        MATCH (d:Document)
+        WHERE d.id = $reference OR d.source IN [$reference, $source]
+        WITH d
+        ORDER BY d.id = $reference DESC, coalesce(d.updated_at, d.created_at) DESC
+        LIMIT 1
+        OPTIONAL MATCH (d)-[:HAS_CHUNK]->(c:Chunk)
+        WITH d, count(c) AS chunk_count
+        RETURN {}
+        ",
+            DOCUMENT_SUMMARY
+        ))
+        .param("reference", reference)
+        .param("source", source);
+
+        match self.graph.execute(query).await?.next().await? {
+            Some(row) => Ok(Some(document_summary(&row)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// A document with its chunks in order and the themes, keywords and
+    /// clusters found for it.
+    pub async fn document_details(&self, document_id: &str) -> Result<Option<DocumentDetails>> {
+        let query_str = format!(
+            "
+        MATCH (d:Document {{id: $id}})
+        OPTIONAL MATCH (d)-[:HAS_CHUNK]->(c:Chunk)
+        WITH d, count(c) AS chunk_count
+        OPTIONAL MATCH (d)-[:HAS_THEME]->(t:Theme)
+        WITH d, chunk_count, collect(DISTINCT t.name) AS themes
+        OPTIONAL MATCH (d)-[:HAS_KEYWORD]->(k:Keyword)
+        WITH d, chunk_count, themes, collect(DISTINCT k.name) AS keywords
+        OPTIONAL MATCH (d)-[:BELONGS_TO]->(cl:Cluster)
+        WITH d, chunk_count, themes, keywords, collect(DISTINCT cl.name) AS clusters
+        RETURN {},
+               size(coalesce(d.content, '')) AS content_length,
+               themes,
+               keywords,
+               clusters
+        ",
+            DOCUMENT_SUMMARY
+        );
+        let details_query = query(&query_str).param("id", document_id);
+        let Some(row) = self.graph.execute(details_query).await?.next().await? else {
+            return Ok(None);
         };
+        let summary = document_summary(&row)?;
+        let content_length = row.get("content_length")?;
+        let themes = row.get("themes")?;
+        let keywords = row.get("keywords")?;
+        let clusters = row.get("clusters")?;
 
-        let chunks = chunk_document(&content); // Now we can use content here
-        self.create_chunks_and_embeddings(&document_id, &chunks)
-            .await?;
-        self.enrich_document_incrementally(&document_id, "Document", &config)
+        let chunks_query = query(
+            "
+        MATCH (:Document {id: $id})-[:HAS_CHUNK]->(c:Chunk)
+        OPTIONAL MATCH (c)-[:HAS_EMBEDDING]->(e:Embedding)
+        WITH c, head(collect(e.model)) AS embedding_model
+        RETURN c.id AS id,
+               c.index AS index,
+               c.start AS start,
+               c.end AS end,
+               c.page_start AS page_start,
+               c.page_end AS page_end,
+               c.content AS content,
+               embedding_model
+        ORDER BY index
+        ",
+        )
+        .param("id", document_id);
+        let mut result = self.graph.execute(chunks_query).await?;
+        let mut chunks = Vec::new();
+        while let Some(row) = result.next().await? {
+            chunks.push(ChunkSummary {
+                id: row.get("id")?,
+                index: row.get("index")?,
+                start: row.get("start").ok(),
+                end: row.get("end").ok(),
+                page_start: row.get("page_start").ok(),
+                page_end: row.get("page_end").ok(),
+                content: row.get("content")?,
+                embedding_model: row.get("embedding_model").ok(),
+            });
+        }
+
+        Ok(Some(DocumentDetails {
+            summary,
+            content_length,
+            themes,
+            keywords,
+            clusters,
+            chunks,
+        }))
+    }
+
+    /// Deletes every document tagged with all of `metadata_filter`, as
+    /// `delete_document` does, and returns them.
+    pub async fn delete_documents(
+        &self,
+        metadata_filter: &[String],
+    ) -> Result<Vec<DocumentSummary>> {
+        if metadata_filter.is_empty() {
+            return Err(anyhow!(
+                "Deleting documents by metadata needs at least one term"
+            ));
+        }
+        let documents = self
+            .list_documents(metadata_filter, 0, usize::MAX)
+            .await?
+            .documents;
+        for document in &documents {
+            self.delete_document(&document.id).await?;
+        }
+        Ok(documents)
+    }
+
+    /// Deletes every document upserted before sources were recorded, as
+    /// `delete_document` does, and returns them. Upserting a file again
+    /// gives its document a source, so the documents left are those whose
+    /// file has changed or gone since.
+    pub async fn delete_unsourced_documents(&self) -> Result<Vec<DocumentSummary>> {
+        let documents = self.unsourced_documents().await?;
+        for document in &documents {
+            self.delete_document(&document.id).await?;
+        }
+        Ok(documents)
+    }
+
+    /// Documents upserted before sources were recorded.
+    pub async fn unsourced_documents(&self) -> Result<Vec<DocumentSummary>> {
+        let query = query(&format!(
+            "
+        MATCH (d:Document)
+        WHERE d.source IS NULL
+        OPTIONAL MATCH (d)-[:HAS_CHUNK]->(c:Chunk)
+        WITH d, count(c) AS chunk_count
+        RETURN {}
+        ORDER BY id
+        ",
+            DOCUMENT_SUMMARY
+        ));
+        let mut result = self.graph.execute(query).await?;
+        let mut documents = Vec::new();
+        while let Some(row) = result.next().await? {
+            documents.push(document_summary(&row)?);
+        }
+        Ok(documents)
+    }
+
+    /// Embeds the chunks of stored documents again with the configured
+    /// embedding model, for example after changing models. Chunks already
+    /// embedded with the model are skipped unless `force` is set, so an
+    /// interrupted reindex can be resumed. Without `metadata_filter`,
+    /// questions and responses are embedded again too. When the model's
+    /// vectors have a different size, the vector index is rebuilt and every
+    /// node in it is embedded again, so this needs no filter.
+    pub async fn reindex_documents(
+        &self,
+        metadata_filter: &[String],
+        force: bool,
+        on_progress: &ReindexProgress<'_>,
+    ) -> Result<ReindexSummary> {
+        let embedder = self
+            .embedder
+            .as_ref()
+            .ok_or_else(|| anyhow!("Embedding configuration not found"))?;
+        let model = embedder.model().to_string();
+
+        let dimension = resolve_dimension(embedder.as_ref()).await?;
+        let index_rebuilt = match self.vector_index_dimension().await? {
+            Some(current) if current != dimension as i64 => {
+                if !metadata_filter.is_empty() {
+                    return Err(anyhow!(
+                        "{} makes {}-dimension vectors but the vector index holds {}; \
+                         reindex without a metadata filter to embed every document, \
+                         question and response again",
+                        model,
+                        dimension,
+                        current
+                    ));
+                }
+                info!(
+                    "Rebuilding vector index {} for {} dimensions (was {})",
+                    VECTOR_INDEX, dimension, current
+                );
+                self.graph
+                    .run(query(&format!("DROP INDEX {} IF EXISTS", VECTOR_INDEX)))
+                    .await?;
+                true
+            }
+            _ => false,
+        };
+        self.ensure_vector_index().await?;
+
+        let documents = self
+            .list_documents(metadata_filter, 0, usize::MAX)
+            .await?
+            .documents;
+        let mut summary = ReindexSummary {
+            model: model.clone(),
+            documents: 0,
+            chunks: 0,
+            interactions: 0,
+            index_rebuilt,
+        };
+        for document in &documents {
+            let chunks = self
+                .chunks_to_reindex(&document.id, &model, force || index_rebuilt)
+                .await?;
+            if chunks.is_empty() {
+                continue;
+            }
+            self.embed_again(
+                embedder.as_ref(),
+                "Chunk",
+                &chunks,
+                &document.source,
+                on_progress,
+            )
             .await?;
-        Ok(document_id)
+            summary.documents += 1;
+            summary.chunks += chunks.len();
+        }
+
+        // Questions and responses carry no metadata, so a filter leaves them.
+        if metadata_filter.is_empty() {
+            for (label, source) in [("Question", "questions"), ("Response", "responses")] {
+                let nodes = self
+                    .interactions_to_reindex(label, &model, force || index_rebuilt)
+                    .await?;
+                self.embed_again(embedder.as_ref(), label, &nodes, source, on_progress)
+                    .await?;
+                summary.interactions += nodes.len();
+            }
+        }
+        Ok(summary)
     }
 
-    async fn extract_content(&self, file_path: &Path) -> Result<String> {
+    /// Embeds `nodes` with the label `label` again, in batches, reporting
+    /// progress under `source`.
+    async fn embed_again(
+        &self,
+        embedder: &dyn EmbeddingProvider,
+        label: &str,
+        nodes: &[(String, String)],
+        source: &str,
+        on_progress: &ReindexProgress<'_>,
+    ) -> Result<()> {
+        let mut batches = stream::iter(nodes.chunks(embedder.max_batch_size()))
+            .map(|batch| async move {
+                let texts: Vec<String> = batch.iter().map(|(_, content)| content.clone()).collect();
+                let vectors = embedder.embed_batch(&texts).await?;
+                Ok::<_, Error>((batch, vectors))
+            })
+            .buffered(self.embedding_concurrency);
+        let mut done = 0;
+        while let Some(batch) = batches.next().await {
+            let (batch, vectors) = batch?;
+            self.replace_embeddings(label, batch, vectors, embedder.model())
+                .await?;
+            done += batch.len();
+            on_progress(source, done, nodes.len());
+        }
+        Ok(())
+    }
+
+    /// The vector size of the vector index, if it exists.
+    async fn vector_index_dimension(&self) -> Result<Option<i64>> {
+        let query = query(
+            "
+        SHOW INDEXES YIELD name, options
+        WHERE name = $name
+        RETURN options.indexConfig['vector.dimensions'] AS dimensions
+        ",
+        )
+        .param("name", VECTOR_INDEX);
+        match self.graph.execute(query).await?.next().await? {
+            Some(row) => Ok(row.get("dimensions").ok()),
+            None => Ok(None),
+        }
+    }
+
+    /// The ids and content of a document's chunks to embed again, in order.
+    async fn chunks_to_reindex(
+        &self,
+        document_id: &str,
+        model: &str,
+        all: bool,
+    ) -> Result<Vec<(String, String)>> {
+        let query = query(
+            "
+        MATCH (:Document {id: $id})-[:HAS_CHUNK]->(c:Chunk)
+        WHERE $all OR NOT EXISTS {
+            MATCH (c)-[:HAS_EMBEDDING]->(e:Embedding)
+            WHERE e.model = $model
+        }
+        RETURN c.id AS id, c.content AS content
+        ORDER BY c.index
+        ",
+        )
+        .param("id", document_id)
+        .param("model", model)
+        .param("all", all);
+
+        let mut result = self.graph.execute(query).await?;
+        let mut chunks = Vec::new();
+        while let Some(row) = result.next().await? {
+            chunks.push((row.get("id")?, row.get("content")?));
+        }
+        Ok(chunks)
+    }
+
+    /// The ids and content of the questions or responses to embed again.
+    async fn interactions_to_reindex(
+        &self,
+        label: &str,
+        model: &str,
+        all: bool,
+    ) -> Result<Vec<(String, String)>> {
+        let query = query(&format!(
+            "
+        MATCH (n:{})
+        WHERE n.id IS NOT NULL AND n.content IS NOT NULL AND ($all OR NOT EXISTS {{
+            MATCH (n)-[:HAS_EMBEDDING]->(e:Embedding)
+            WHERE e.model = $model
+        }})
+        RETURN n.id AS id, n.content AS content
+        ORDER BY n.timestamp
+        ",
+            label
+        ))
+        .param("model", model)
+        .param("all", all);
+
+        let mut result = self.graph.execute(query).await?;
+        let mut nodes = Vec::new();
+        while let Some(row) = result.next().await? {
+            nodes.push((row.get("id")?, row.get("content")?));
+        }
+        Ok(nodes)
+    }
+
+    /// Gives chunks, questions or responses, by `label`, new embeddings,
+    /// deleting the old ones unless another node still has them.
+    async fn replace_embeddings(
+        &self,
+        label: &str,
+        nodes: &[(String, String)],
+        vectors: Vec<Vec<f32>>,
+        model: &str,
+    ) -> Result<()> {
+        let mut rows = BoltList::new();
+        for ((id, _), vector) in nodes.iter().zip(vectors) {
+            let mut row = BoltMap::new();
+            row.put(
+                BoltString::from("id"),
+                BoltType::String(BoltString::from(id.as_str())),
+            );
+            row.put(
+                BoltString::from("embedding_id"),
+                BoltType::String(BoltString::from(Uuid::new_v4().to_string())),
+            );
+            row.put(BoltString::from("vector"), BoltType::from(vector));
+            rows.push(BoltType::Map(row));
+        }
+
+        let query = query(&format!(
+            "
+        UNWIND $rows AS row
+        MATCH (c:{} {{id: row.id}})
+        CREATE (c)-[:HAS_EMBEDDING]->(e:Embedding {{
+            id: row.embedding_id,
+            vector: row.vector,
+            model: $model,
+            created_at: datetime()
+        }})
+        WITH c, e
+        MATCH (c)-[r:HAS_EMBEDDING]->(old:Embedding)
+        WHERE old <> e
+        DELETE r
+        WITH DISTINCT old
+        WHERE NOT (old)<--()
+        DETACH DELETE old
+        ",
+            label
+        ))
+        .param("rows", BoltType::List(rows))
+        .param("model", model);
+        self.graph.run(query).await?;
+        Ok(())
+    }
+
+    /// Deletes the chunks of a document and their embeddings. Chunks that
+    /// another document also has are only unlinked.
+    async fn delete_chunks(&self, document_id: &str) -> Result<()> {
+        let query = query(
+            "
+        MATCH (d:Document {id: $id})-[r:HAS_CHUNK]->(c:Chunk)
+        DELETE r
+        WITH DISTINCT c
+        WHERE NOT (c)<-[:HAS_CHUNK]-(:Document)
+        OPTIONAL MATCH (c)-[:HAS_EMBEDDING]->(e:Embedding)
+        DETACH DELETE c
+        WITH DISTINCT e
+        WHERE e IS NOT NULL AND NOT (e)<--()
+        DETACH DELETE e
+        ",
+        )
+        .param("id", document_id);
+        self.graph.run(query).await?;
+        Ok(())
+    }
+
+    /// Deletes documents upserted from files under `directory` that no
+    /// longer exist. Returns the sources removed.
+    pub async fn prune_documents(&self, directory: &Path) -> Result<Vec<String>> {
+        let directory = tokio::fs::canonicalize(directory).await?;
+        let prefix = format!("{}{}", directory.display(), MAIN_SEPARATOR);
+        let query = query(
+            "
+        MATCH (d:Document)
+        WHERE d.source STARTS WITH $prefix
+        RETURN d.id AS id, d.source AS source
+        ",
+        )
+        .param("prefix", prefix);
+
+        let mut result = self.graph.execute(query).await?;
+        let mut removed = Vec::new();
+        while let Some(row) = result.next().await? {
+            let id: String = row.get("id")?;
+            let source: String = row.get("source")?;
+            // Keep documents whose file cannot be checked.
+            if tokio::fs::try_exists(&source).await.unwrap_or(true) {
+                continue;
+            }
+            self.delete_document(&id).await?;
+            removed.push(source);
+        }
+        Ok(removed)
+    }
+
+    /// Adds `metadata` to a document's metadata and replaces the terms a
+    /// loader sets, such as `title:`, with `document_metadata`.
+    async fn merge_metadata(
+        &self,
+        document_id: &str,
+        metadata: &[String],
+        document_metadata: &[String],
+    ) -> Result<()> {
+        let replaced: Vec<String> = METADATA_KEYS
+            .iter()
+            .map(|key| format!("{}:", key))
+            .collect();
+        let query = query(
+            "
+        MATCH (d:Document {id: $id})
+        WITH d, [term IN coalesce(d.metadata, [])
+            WHERE NOT any(key IN $replaced WHERE term STARTS WITH key)] AS kept
+        WITH d, kept + [term IN $document_metadata WHERE NOT term IN kept] AS kept
+        SET d.metadata = kept + [term IN $metadata WHERE NOT term IN kept]
+        ",
+        )
+        .param("id", document_id)
+        .param("replaced", replaced)
+        .param("document_metadata", document_metadata)
+        .param("metadata", metadata);
+        self.graph.run(query).await?;
+        Ok(())
+    }
+
+    /// The text of a file, the metadata its loader found and, for PDFs,
+    /// where its pages start.
+    async fn extract_content(&self, file_path: &Path) -> Result<ExtractedText> {
         let extension = file_path
             .extension()
             .and_then(|ext| ext.to_str())
             .ok_or_else(|| anyhow!("Unable to determine file type"))?;
 
-        match extension.to_lowercase().as_str() {
+        let (content, metadata) = match extension.to_lowercase().as_str() {
             "pdf" => {
-                let path_buf = file_path.to_path_buf();
-                Ok(tokio::task::spawn_blocking(move || extract_text(&path_buf)).await??)
-            }
-            "txt" | "json" | "csv" | "tsv" | "md" | "html" | "xml" | "yml" | "yaml" | "json5"
-            | "py" | "rb" | "rs" | "js" | "ts" | "php" | "java" | "c" | "cpp" | "go" | "sh"
-            | "bat" | "ps1" | "psm1" | "psd1" | "ps1xml" | "psc1" | "pssc" | "pss1" | "psh" => {
-                let mut file = File::open(file_path).await?;
-                let mut content = String::new();
-                file.read_to_string(&mut content).await?;
-                Ok(content)
+                let (document, metadata) = load_file(file_path, loaders::load_pdf).await?;
+                return Ok(ExtractedText {
+                    content: document.text,
+                    metadata,
+                    page_starts: document.page_starts,
+                });
             }
-            "docx" => {
-                let processor = DocxProcessor;
-                let (content, _metadata) = processor.process(file_path).await?;
-                Ok(content)
+            "docx" => DocxProcessor.process(file_path).await?,
+            "pptx" => PptxProcessor.process(file_path).await?,
+            "xlsx" | "xlsm" => XlsxProcessor.process(file_path).await?,
+            "csv" => CsvProcessor { delimiter: ',' }.process(file_path).await?,
+            "tsv" => CsvProcessor { delimiter: '\t' }.process(file_path).await?,
+            "html" | "htm" | "xhtml" => HtmlProcessor.process(file_path).await?,
+            "epub" => EpubProcessor.process(file_path).await?,
+            "txt" | "json" | "md" | "xml" | "yml" | "yaml" | "json5" | "py" | "rb" | "rs"
+            | "js" | "ts" | "php" | "java" | "c" | "cpp" | "go" | "sh" | "bat" | "ps1" | "psm1"
+            | "psd1" | "ps1xml" | "psc1" | "pssc" | "pss1" | "psh" => {
+                TextProcessor.process(file_path).await?
             }
             // Add more file types here as needed
-            _ => Err(anyhow!("Unsupported file type: {}", extension)),
-        }
+            _ => return Err(anyhow!("Unsupported file type: {}", extension)),
+        };
+        Ok(ExtractedText {
+            content,
+            metadata,
+            page_starts: Vec::new(),
+        })
     }
+
     async fn create_chunks_and_embeddings(
         &self,
         document_id: &str,
-        chunks: &[String],
+        chunks: &[Chunk],
+        page_starts: &[usize],
+        on_progress: &UpsertProgress<'_>,
     ) -> Result<()> {
         debug!(
             "Creating chunks and embeddings for document {}",
             document_id
         );
-        if let Some(voyage_config) = &self.voyage_ai_config {
-            for (i, chunk) in chunks.iter().enumerate() {
-                let embedding = get_voyage_embedding(chunk, voyage_config).await?;
+        let embedder = self
+            .embedder
+            .as_ref()
+            .ok_or_else(|| anyhow!("Embedding configuration not found"))?;
+
+        // Batches are embedded concurrently but written in order, so each
+        // write can return chunk ids in chunk order.
+        let batch_size = embedder.max_batch_size();
+        let mut batches = stream::iter(chunks.chunks(batch_size).enumerate())
+            .map(|(batch, batch_chunks)| async move {
+                let texts: Vec<String> = batch_chunks
+                    .iter()
+                    .map(|chunk| chunk.content.clone())
+                    .collect();
+                let vectors = embedder.embed_batch(&texts).await?;
+                Ok::<_, Error>((batch * batch_size, batch_chunks, vectors))
+            })
+            .buffered(self.embedding_concurrency);
+
+        let mut chunk_ids = Vec::with_capacity(chunks.len());
+        while let Some(batch) = batches.next().await {
+            let (offset, batch_chunks, vectors) = batch?;
+            chunk_ids.extend(
+                self.write_chunk_batch(document_id, offset, batch_chunks, page_starts, vectors)
+                    .await?,
+            );
+            info!(
+                "Embedded {}/{} chunks of document {}",
+                chunk_ids.len(),
+                chunks.len(),
+                document_id
+            );
+            on_progress(chunk_ids.len(), chunks.len());
+        }
 
-                if embedding.len() != EMBEDDING_DIMENSION {
-                    return Err(anyhow!("Embedding dimension mismatch"));
-                }
+        self.link_chunks(&chunk_ids).await
+    }
 
-                let query = query(
-                    "
+    /// Writes a batch of chunks with their embeddings in one query and
+    /// returns the chunk ids in order.
+    async fn write_chunk_batch(
+        &self,
+        document_id: &str,
+        offset: usize,
+        chunks: &[Chunk],
+        page_starts: &[usize],
+        vectors: Vec<Vec<f32>>,
+    ) -> Result<Vec<String>> {
+        let model = self
+            .embedder
+            .as_ref()
+            .map(|embedder| embedder.model().to_string())
+            .unwrap_or_default();
+        let mut rows = BoltList::new();
+        for (i, (chunk, vector)) in chunks.iter().zip(vectors).enumerate() {
+            let mut row = BoltMap::new();
+            row.put(
+                BoltString::from("chunk_id"),
+                BoltType::String(BoltString::from(Uuid::new_v4().to_string())),
+            );
+            row.put(
+                BoltString::from("content"),
+                BoltType::String(BoltString::from(chunk.content.as_str())),
+            );
+            row.put(
+                BoltString::from("start"),
+                BoltType::Integer(BoltInteger::new(chunk.start as i64)),
+            );
+            row.put(
+                BoltString::from("end"),
+                BoltType::Integer(BoltInteger::new(chunk.end as i64)),
+            );
+            row.put(
+                BoltString::from("index"),
+                BoltType::Integer(BoltInteger::new((offset + i) as i64)),
+            );
+            // The pages the chunk spans, from 1, when the document has pages.
+            let pages = page_range(page_starts, chunk.start, chunk.end);
+            let pages = [pages.map(|(first, _)| first), pages.map(|(_, last)| last)];
+            for (key, page) in ["page_start", "page_end"].into_iter().zip(pages) {
+                let page = match page {
+                    Some(page) => BoltType::Integer(BoltInteger::new(page as i64)),
+                    None => BoltType::Null(BoltNull),
+                };
+                row.put(BoltString::from(key), page);
+            }
+            row.put(
+                BoltString::from("embedding_id"),
+                BoltType::String(BoltString::from(Uuid::new_v4().to_string())),
+            );
+            row.put(BoltString::from("vector"), BoltType::from(vector));
+            rows.push(BoltType::Map(row));
+        }
+
+        let query = query(
+            "
             MATCH (d:Document {id: $document_id})
-            MERGE (c:Chunk {content: $content})
-            ON CREATE SET
-                c.id = $chunk_id,
-                c.index = $index
-            MERGE (e:Embedding {vector: $vector})
-            ON CREATE SET
-                e.id = $embedding_id
-            MERGE (d)-[:HAS_CHUNK]->(c)
-            MERGE (c)-[:HAS_EMBEDDING]->(e)
-            WITH c, e, $prev_chunk_id AS prev_id
-            OPTIONAL MATCH (prev:Chunk {id: prev_id})
-            FOREACH (_ IN CASE WHEN prev IS NOT NULL THEN [1] ELSE [] END |
-                MERGE (prev)-[:NEXT]->(c)
-            )
-            RETURN c.id as chunk_id, e.id as embedding_id
+            UNWIND $rows AS row
+            CREATE (d)-[:HAS_CHUNK]->(c:Chunk {
+                id: row.chunk_id,
+                content: row.content,
+                index: row.index,
+                start: row.start,
+                end: row.end,
+                page_start: row.page_start,
+                page_end: row.page_end
+            })
+            CREATE (c)-[:HAS_EMBEDDING]->(e:Embedding {
+                id: row.embedding_id,
+                vector: row.vector,
+                model: $model,
+                created_at: datetime()
+            })
+            RETURN row.index AS index, c.id AS chunk_id
+            ORDER BY index
             ",
-                )
-                .param(
-                    "document_id",
-                    BoltType::String(BoltString::from(document_id)),
-                )
-                .param(
-                    "chunk_id",
-                    BoltType::String(BoltString::from(Uuid::new_v4().to_string())),
-                )
-                .param(
-                    "content",
-                    BoltType::String(BoltString::from(chunk.as_str())),
-                )
-                .param("index", BoltType::Integer(BoltInteger::new(i as i64)))
-                .param(
-                    "embedding_id",
-                    BoltType::String(BoltString::from(Uuid::new_v4().to_string())),
-                )
-                .param("vector", embedding)
-                .param(
-                    "prev_chunk_id",
-                    if i > 0 {
-                        BoltType::String(BoltString::from(chunks[i - 1].as_str()))
-                    } else {
-                        BoltType::Null(BoltNull)
-                    },
-                );
+        )
+        .param(
+            "document_id",
+            BoltType::String(BoltString::from(document_id)),
+        )
+        .param("rows", BoltType::List(rows))
+        .param("model", model);
 
-                let mut result = self.graph.execute(query).await?;
+        let mut result = self.graph.execute(query).await?;
+        let mut chunk_ids = Vec::with_capacity(chunks.len());
+        while let Some(row) = result.next().await? {
+            chunk_ids.push(row.get::<String>("chunk_id")?);
+        }
+        if chunk_ids.len() != chunks.len() {
+            return Err(anyhow!("Failed to create chunk and embedding"));
+        }
+        Ok(chunk_ids)
+    }
 
-                if result.next().await?.is_none() {
-                    return Err(anyhow!("Failed to create or merge chunk and embedding"));
-                }
-            }
-            Ok(())
-        } else {
-            Err(anyhow!("VoyageAI configuration not found"))
+    /// Links consecutive chunks with `NEXT` relationships.
+    async fn link_chunks(&self, chunk_ids: &[String]) -> Result<()> {
+        if chunk_ids.len() < 2 {
+            return Ok(());
         }
+        let query = query(
+            "
+            UNWIND range(0, size($ids) - 2) AS i
+            MATCH (prev:Chunk {id: $ids[i]}), (next:Chunk {id: $ids[i + 1]})
+            MERGE (prev)-[:NEXT]->(next)
+            ",
+        )
+        .param("ids", chunk_ids.to_vec());
+        self.graph.run(query).await?;
+        Ok(())
     }
 
     pub async fn get_document_statistics(&self) -> Result<DocumentStatistics> {
@@ -630,25 +1841,24 @@ impl Neo4jClient {
         let status = self.get_enrichment_status(node_id, node_type).await?;
         let now = Utc::now();
 
-        if let Some(voyage_config) = &self.voyage_ai_config {
+        if self.embedder.is_some() {
             if status
                 .last_themes_keywords_update
-                .map_or(true, |last| now - last > config.themes_keywords_interval)
+                .is_none_or(|last| now - last > config.themes_keywords_interval)
             {
-                self.update_themes_and_keywords(node_id, node_type, voyage_config)
-                    .await?;
+                self.update_themes_and_keywords(node_id, node_type).await?;
             }
 
             if status
                 .last_clustering_update
-                .map_or(true, |last| now - last > config.clustering_interval)
+                .is_none_or(|last| now - last > config.clustering_interval)
             {
                 self.update_clustering(node_id, node_type).await?;
             }
 
             if status
                 .last_sentiment_update
-                .map_or(true, |last| now - last > config.sentiment_interval)
+                .is_none_or(|last| now - last > config.sentiment_interval)
             {
                 self.update_sentiment(node_id, node_type).await?;
             }
@@ -657,7 +1867,7 @@ impl Neo4jClient {
                 .await?;
             Ok(())
         } else {
-            Err(anyhow!("VoyageAI configuration not found"))
+            Err(anyhow!("Embedding configuration not found"))
         }
     }
 
@@ -698,12 +1908,11 @@ impl Neo4jClient {
         &self,
         node_id: &str,
         node_type: &str,
-        voyage_config: &VoyageAIConfig,
     ) -> Result<()> {
         debug!("Updating themes and keywords for {} {}", node_type, node_id);
         let content = self.get_node_content(node_id, node_type).await?;
         let (themes, keywords) = self
-            .extract_themes_and_keywords(&content, voyage_config)
+            .extract_themes_and_keywords(&content)
             .await?;
         self.create_theme_and_keyword_nodes(node_id, node_type, &themes, &keywords)
             .await?;
@@ -1379,7 +2588,6 @@ impl Neo4jClient {
     async fn extract_themes_and_keywords(
         &self,
         content: &str,
-        _config: &VoyageAIConfig,
     ) -> Result<(Vec<String>, Vec<String>)> {
         debug!("Extracting themes and keywords");
         debug!("content: {}", content);
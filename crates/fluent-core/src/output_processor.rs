@@ -65,7 +65,7 @@ impl OutputProcessor {
         } else {
             Url::parse(url)?
                 .path_segments()
-                .and_then(|segments| segments.last())
+                .and_then(|mut segments| segments.next_back())
                 .map(|s| s.to_string())
                 .unwrap_or_else(|| Uuid::new_v4().to_string())
         };
@@ -101,7 +101,7 @@ impl OutputProcessor {
         Url::parse(url)
             .ok()?
             .path_segments()?
-            .last()?
+            .next_back()?
             .split('?')
             .next()
             .map(|s| s.to_string())
@@ -0,0 +1,174 @@
+// crates/fluent-core/src/pricing.rs
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::EngineConfig;
+use crate::tokenizer::model_name;
+use crate::types::{Response, Usage};
+
+/// Set by the pipeline executor to a file that every CLI request run by the
+/// pipeline appends its cost to.
+pub const COST_LOG_ENV: &str = "FLUENT_COST_LOG";
+
+/// Prices in US dollars. Set as `pricing` in an engine config to override
+/// the built-in table or price a model it does not know.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+#[serde(default)]
+pub struct ModelPrice {
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+    pub per_image: f64,
+}
+
+impl ModelPrice {
+    const fn tokens(input_per_million: f64, output_per_million: f64) -> Self {
+        Self {
+            input_per_million,
+            output_per_million,
+            per_image: 0.0,
+        }
+    }
+
+    const fn image(per_image: f64) -> Self {
+        Self {
+            input_per_million: 0.0,
+            output_per_million: 0.0,
+            per_image,
+        }
+    }
+
+    pub fn token_cost(&self, usage: &Usage) -> f64 {
+        (usage.prompt_tokens as f64 * self.input_per_million
+            + usage.completion_tokens as f64 * self.output_per_million)
+            / 1_000_000.0
+    }
+}
+
+/// List prices by model name prefix; the first match wins, so longer
+/// prefixes come first. Prices change, so treat these as estimates.
+const PRICES: &[(&str, ModelPrice)] = &[
+    ("gpt-4o-mini", ModelPrice::tokens(0.15, 0.60)),
+    ("gpt-4o", ModelPrice::tokens(2.50, 10.00)),
+    ("chatgpt-4o", ModelPrice::tokens(5.00, 15.00)),
+    ("gpt-4.1-nano", ModelPrice::tokens(0.10, 0.40)),
+    ("gpt-4.1-mini", ModelPrice::tokens(0.40, 1.60)),
+    ("gpt-4.1", ModelPrice::tokens(2.00, 8.00)),
+    ("gpt-4-turbo", ModelPrice::tokens(10.00, 30.00)),
+    ("gpt-4", ModelPrice::tokens(30.00, 60.00)),
+    ("gpt-3.5-turbo", ModelPrice::tokens(0.50, 1.50)),
+    ("o1-mini", ModelPrice::tokens(1.10, 4.40)),
+    ("o1", ModelPrice::tokens(15.00, 60.00)),
+    ("o3-mini", ModelPrice::tokens(1.10, 4.40)),
+    ("o3", ModelPrice::tokens(2.00, 8.00)),
+    ("o4-mini", ModelPrice::tokens(1.10, 4.40)),
+    ("claude-3-5-haiku", ModelPrice::tokens(0.80, 4.00)),
+    ("claude-3-haiku", ModelPrice::tokens(0.25, 1.25)),
+    ("claude-3-opus", ModelPrice::tokens(15.00, 75.00)),
+    ("claude-opus", ModelPrice::tokens(15.00, 75.00)),
+    ("claude-haiku", ModelPrice::tokens(1.00, 5.00)),
+    ("claude", ModelPrice::tokens(3.00, 15.00)),
+    ("gemini-1.5-flash", ModelPrice::tokens(0.075, 0.30)),
+    ("gemini-1.5-pro", ModelPrice::tokens(1.25, 5.00)),
+    ("gemini-2.0-flash", ModelPrice::tokens(0.10, 0.40)),
+    ("gemini-2.5-flash", ModelPrice::tokens(0.30, 2.50)),
+    ("gemini-2.5-pro", ModelPrice::tokens(1.25, 10.00)),
+    ("mistral-large", ModelPrice::tokens(2.00, 6.00)),
+    ("mistral-small", ModelPrice::tokens(0.20, 0.60)),
+    ("open-mistral-nemo", ModelPrice::tokens(0.15, 0.15)),
+    ("mistral-nemo", ModelPrice::tokens(0.15, 0.15)),
+    ("codestral", ModelPrice::tokens(0.30, 0.90)),
+    ("command-r-plus", ModelPrice::tokens(2.50, 10.00)),
+    ("command-r", ModelPrice::tokens(0.15, 0.60)),
+    ("llama3-70b", ModelPrice::tokens(0.59, 0.79)),
+    ("llama-3.1-70b", ModelPrice::tokens(0.59, 0.79)),
+    ("llama-3.3-70b", ModelPrice::tokens(0.59, 0.79)),
+    ("llama3-8b", ModelPrice::tokens(0.05, 0.08)),
+    ("llama-3.1-8b", ModelPrice::tokens(0.05, 0.08)),
+    ("gemma2-9b", ModelPrice::tokens(0.20, 0.20)),
+    ("sonar-pro", ModelPrice::tokens(3.00, 15.00)),
+    ("sonar", ModelPrice::tokens(1.00, 1.00)),
+    ("dall-e-2", ModelPrice::image(0.02)),
+    ("dall-e", ModelPrice::image(0.04)),
+    ("stabilityai-ultra", ModelPrice::image(0.08)),
+    ("leonardo-ai", ModelPrice::image(0.02)),
+    ("imaginepro-midjourney", ModelPrice::image(0.05)),
+];
+
+/// The built-in price of a model, matched on the name without any
+/// `vendor/` prefix.
+pub fn model_price(model: &str) -> Option<ModelPrice> {
+    let model = model.rsplit('/').next().unwrap_or(model).to_lowercase();
+    PRICES
+        .iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+        .map(|&(_, price)| price)
+}
+
+/// The price for a model served by an engine: the engine's `pricing`
+/// override, else the built-in price of the model, else of the configured
+/// `modelName`.
+pub fn price_for(config: &EngineConfig, model: &str) -> Option<ModelPrice> {
+    config
+        .pricing
+        .or_else(|| model_price(model))
+        .or_else(|| model_name(config).and_then(model_price))
+}
+
+/// What `images` generated images cost, for image engines to report.
+pub fn image_cost(config: &EngineConfig, model: &str, images: usize) -> Option<f64> {
+    price_for(config, model).map(|price| price.per_image * images as f64)
+}
+
+/// The cost of a response: what the engine reported, or its token usage at
+/// the model's price. `None` when the price is unknown.
+pub fn response_cost(config: &EngineConfig, response: &Response) -> Option<f64> {
+    response.cost.or_else(|| {
+        price_for(config, &response.model).map(|price| price.token_cost(&response.usage))
+    })
+}
+
+/// Dollars with enough decimals to show small per-request costs.
+pub fn format_cost(cost: f64) -> String {
+    if cost > 0.0 && cost < 0.01 {
+        format!("${:.6}", cost)
+    } else {
+        format!("${:.4}", cost)
+    }
+}
+
+/// Appends a request's cost to the file named by `COST_LOG_ENV`, if set.
+pub fn log_cost(cost: f64) -> Result<()> {
+    match std::env::var(COST_LOG_ENV) {
+        Ok(path) => append_cost(Path::new(&path), cost),
+        Err(_) => Ok(()),
+    }
+}
+
+pub fn append_cost(path: &Path, cost: f64) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open cost log {}", path.display()))?;
+    writeln!(file, "{}", cost)?;
+    Ok(())
+}
+
+/// Sums a cost log and removes it, so the next call only sees new entries.
+/// Zero if the log does not exist.
+pub fn take_logged_cost(path: &Path) -> Result<f64> {
+    if !path.exists() {
+        return Ok(0.0);
+    }
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read cost log {}", path.display()))?;
+    fs::remove_file(path)?;
+    Ok(contents
+        .lines()
+        .filter_map(|line| line.trim().parse::<f64>().ok())
+        .sum())
+}
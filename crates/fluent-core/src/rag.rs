@@ -0,0 +1,77 @@
+// crates/fluent-core/src/rag.rs
+use crate::neo4j_client::RetrievedChunk;
+use crate::types::{Message, Request, Role};
+
+/// Chunks retrieved per question when not set otherwise.
+pub const DEFAULT_TOP_K: usize = 5;
+
+const INSTRUCTIONS: &str = "Answer the question using the numbered sources that follow it. \
+Cite the sources each statement relies on by number in square brackets, e.g. [1] or [2][3]. \
+If the sources do not contain the answer, say so instead of guessing.";
+
+/// The pages a chunk spans, e.g. `page 42` or `pages 41-42`, for documents
+/// with pages.
+pub fn page_label(page_start: Option<i64>, page_end: Option<i64>) -> Option<String> {
+    match (page_start, page_end) {
+        (Some(start), Some(end)) if end > start => Some(format!("pages {}-{}", start, end)),
+        (Some(page), _) | (None, Some(page)) => Some(format!("page {}", page)),
+        (None, None) => None,
+    }
+}
+
+/// Where a chunk is in its document, e.g. `chunk 3, page 42`.
+fn location(chunk: &RetrievedChunk) -> String {
+    match page_label(chunk.page_start, chunk.page_end) {
+        Some(pages) => format!("chunk {}, {}", chunk.chunk_index, pages),
+        None => format!("chunk {}", chunk.chunk_index),
+    }
+}
+
+/// The numbered context block sent with the question, one entry per chunk
+/// with its source document, chunk index and pages.
+pub fn format_sources(chunks: &[RetrievedChunk]) -> String {
+    chunks
+        .iter()
+        .enumerate()
+        .map(|(i, chunk)| {
+            format!(
+                "[{}] {} ({})\n{}",
+                i + 1,
+                chunk.source,
+                location(chunk),
+                chunk.content.trim()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Adds retrieved chunks to a request: instructions to answer from them with
+/// citations as a system turn, and the numbered sources after the question.
+pub fn add_sources(request: &mut Request, chunks: &[RetrievedChunk]) {
+    request.messages.insert(0, Message::new(Role::System, INSTRUCTIONS));
+    request.payload = format!(
+        "{}\n\n----\n\nSources:\n{}",
+        request.payload.trim(),
+        format_sources(chunks)
+    );
+}
+
+/// The reference list printed after an answer, e.g.
+/// `[1] report.pdf, chunk 3, page 42`.
+pub fn source_list(chunks: &[RetrievedChunk]) -> String {
+    chunks
+        .iter()
+        .enumerate()
+        .map(|(i, chunk)| {
+            format!(
+                "[{}] {}, {} (score {:.3})",
+                i + 1,
+                chunk.source,
+                location(chunk),
+                chunk.score
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
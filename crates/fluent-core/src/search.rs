@@ -0,0 +1,257 @@
+// crates/fluent-core/src/search.rs
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Dampens the weight of top ranks in reciprocal rank fusion; 60 is the
+/// value from the original RRF paper.
+pub const RRF_K: f64 = 60.0;
+
+/// Hits returned by a search when not set otherwise.
+pub const DEFAULT_SEARCH_LIMIT: usize = 10;
+
+/// What a hybrid search looks through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchScope {
+    #[default]
+    All,
+    /// Chunks of upserted documents.
+    Documents,
+    /// Questions and responses from past interactions.
+    Interactions,
+}
+
+impl SearchScope {
+    pub fn documents(self) -> bool {
+        matches!(self, SearchScope::All | SearchScope::Documents)
+    }
+
+    pub fn interactions(self) -> bool {
+        matches!(self, SearchScope::All | SearchScope::Interactions)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HitKind {
+    Chunk,
+    Question,
+    Response,
+}
+
+impl HitKind {
+    /// The kind of a node from its Neo4j label.
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "Chunk" => Some(HitKind::Chunk),
+            "Question" => Some(HitKind::Question),
+            "Response" => Some(HitKind::Response),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HitKind::Chunk => "chunk",
+            HitKind::Question => "question",
+            HitKind::Response => "response",
+        }
+    }
+}
+
+/// A node found by `Neo4jClient::hybrid_search`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub kind: HitKind,
+    pub id: String,
+    pub content: String,
+    /// The fused score; higher is better.
+    pub score: f64,
+    /// Position in the full-text results, from 1, if it matched there.
+    pub text_rank: Option<usize>,
+    /// Position in the vector results, from 1, if it matched there.
+    pub vector_rank: Option<usize>,
+    /// The document path for chunks, the session id for questions and
+    /// responses.
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<i64>,
+    /// The first and last page of a chunk, from 1, for documents with pages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_start: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_end: Option<i64>,
+    /// When a question or response was recorded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+}
+
+/// Merges full-text and vector results, each ordered best first, with
+/// reciprocal rank fusion: a hit scores `1 / (RRF_K + rank)` in each list it
+/// appears in. Returns the best `limit` hits.
+pub fn fuse(text: Vec<SearchHit>, vector: Vec<SearchHit>, limit: usize) -> Vec<SearchHit> {
+    let mut hits: Vec<SearchHit> = Vec::new();
+    let mut positions: HashMap<(HitKind, String), usize> = HashMap::new();
+
+    for (from_text, list) in [(true, text), (false, vector)] {
+        for (i, hit) in list.into_iter().enumerate() {
+            let rank = i + 1;
+            let position = *positions
+                .entry((hit.kind, hit.id.clone()))
+                .or_insert_with(|| {
+                    hits.push(SearchHit {
+                        score: 0.0,
+                        text_rank: None,
+                        vector_rank: None,
+                        ..hit
+                    });
+                    hits.len() - 1
+                });
+            let fused = &mut hits[position];
+            let list_rank = if from_text {
+                &mut fused.text_rank
+            } else {
+                &mut fused.vector_rank
+            };
+            // A node reached twice in one list counts at its best rank.
+            if list_rank.is_none() {
+                *list_rank = Some(rank);
+                fused.score += 1.0 / (RRF_K + rank as f64);
+            }
+        }
+    }
+
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    hits.truncate(limit);
+    hits
+}
+
+/// Escapes Lucene query syntax so a search matches the words as typed.
+pub fn escape_fulltext(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|word| match word {
+            // Lucene's boolean operators; lowercase words are plain terms.
+            "AND" | "OR" | "NOT" => word.to_lowercase(),
+            _ => {
+                let mut escaped = String::with_capacity(word.len());
+                for c in word.chars() {
+                    if "+-&|!(){}[]^\"~*?:\\/".contains(c) {
+                        escaped.push('\\');
+                    }
+                    escaped.push(c);
+                }
+                escaped
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(kind: HitKind, id: &str) -> SearchHit {
+        SearchHit {
+            kind,
+            id: id.to_string(),
+            content: format!("content of {}", id),
+            score: 0.0,
+            text_rank: None,
+            vector_rank: None,
+            source: "source".to_string(),
+            chunk_index: None,
+            page_start: None,
+            page_end: None,
+            timestamp: None,
+        }
+    }
+
+    fn chunks(ids: &[&str]) -> Vec<SearchHit> {
+        ids.iter().map(|id| hit(HitKind::Chunk, id)).collect()
+    }
+
+    fn ids(hits: &[SearchHit]) -> Vec<&str> {
+        hits.iter().map(|hit| hit.id.as_str()).collect()
+    }
+
+    fn rrf(rank: usize) -> f64 {
+        1.0 / (RRF_K + rank as f64)
+    }
+
+    #[test]
+    fn single_list_keeps_its_order() {
+        let hits = fuse(chunks(&["a", "b", "c"]), Vec::new(), 10);
+        assert_eq!(ids(&hits), ["a", "b", "c"]);
+        assert_eq!(hits[0].score, rrf(1));
+        assert_eq!(hits[2].score, rrf(3));
+        assert_eq!(hits[1].text_rank, Some(2));
+        assert_eq!(hits[1].vector_rank, None);
+    }
+
+    #[test]
+    fn hits_in_both_lists_rank_first() {
+        let hits = fuse(chunks(&["a", "b"]), chunks(&["b", "c"]), 10);
+        assert_eq!(ids(&hits), ["b", "a", "c"]);
+        assert_eq!(hits[0].score, rrf(2) + rrf(1));
+        assert_eq!(hits[0].text_rank, Some(2));
+        assert_eq!(hits[0].vector_rank, Some(1));
+        assert_eq!(hits[2].text_rank, None);
+        assert_eq!(hits[2].vector_rank, Some(2));
+    }
+
+    #[test]
+    fn duplicate_in_one_list_counts_at_its_best_rank() {
+        let hits = fuse(chunks(&["a", "b", "a"]), Vec::new(), 10);
+        assert_eq!(ids(&hits), ["a", "b"]);
+        assert_eq!(hits[0].score, rrf(1));
+        assert_eq!(hits[0].text_rank, Some(1));
+    }
+
+    #[test]
+    fn same_id_of_another_kind_is_a_separate_hit() {
+        let hits = fuse(
+            vec![hit(HitKind::Question, "a")],
+            vec![hit(HitKind::Response, "a")],
+            10,
+        );
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].kind, HitKind::Question);
+        assert_eq!(hits[1].kind, HitKind::Response);
+    }
+
+    #[test]
+    fn fused_results_are_limited() {
+        let hits = fuse(chunks(&["a", "b", "c"]), chunks(&["c", "d"]), 2);
+        assert_eq!(ids(&hits), ["c", "a"]);
+    }
+
+    #[test]
+    fn escapes_lucene_special_characters() {
+        assert_eq!(
+            escape_fulltext(r#"a+b -c "quoted" (x) [y] {z} a:b c/d e\f g~ h* i? j^2 !k a&&b c||d"#),
+            r#"a\+b \-c \"quoted\" \(x\) \[y\] \{z\} a\:b c\/d e\\f g\~ h\* i\? j\^2 \!k a\&\&b c\|\|d"#
+        );
+    }
+
+    #[test]
+    fn lowercases_boolean_operators() {
+        assert_eq!(
+            escape_fulltext("rust AND tokio OR async NOT blocking"),
+            "rust and tokio or async not blocking"
+        );
+        // Only whole words are operators.
+        assert_eq!(escape_fulltext("ANDROID Or not"), "ANDROID Or not");
+    }
+
+    #[test]
+    fn collapses_whitespace() {
+        assert_eq!(
+            escape_fulltext("  neo4j \t vector\nindex "),
+            "neo4j vector index"
+        );
+        assert_eq!(escape_fulltext("   "), "");
+    }
+}
@@ -0,0 +1,553 @@
+// crates/fluent-core/src/tokenizer.rs
+use log::warn;
+use tiktoken_rs::{cl100k_base_singleton, o200k_base_singleton, CoreBPE, Rank};
+
+use crate::config::EngineConfig;
+use crate::types::{Message, Request, Role};
+
+/// Appended to text cut short to fit the context window.
+pub const TRUNCATION_MARKER: &str = "... [truncated]";
+
+/// Tokens kept free for the reply when the engine sets no `max_tokens`.
+pub const DEFAULT_COMPLETION_TOKENS: usize = 1024;
+
+/// Chat formats wrap every message in a few tokens of role markup.
+const TOKENS_PER_MESSAGE: usize = 3;
+/// Tokens that prime the assistant's reply.
+const REPLY_PRIMING_TOKENS: usize = 3;
+
+/// Other vendors' tokenizers are not published, so their counts are
+/// approximated with `cl100k_base` plus this margin to stay on the safe side.
+const APPROXIMATION_MARGIN: f64 = 1.1;
+
+/// Context window sizes by model name prefix; the first match wins, so
+/// longer prefixes come first.
+const CONTEXT_WINDOWS: &[(&str, usize)] = &[
+    ("gpt-4.1", 1_047_576),
+    ("gpt-4o", 128_000),
+    ("chatgpt-4o", 128_000),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-4-1106", 128_000),
+    ("gpt-4-0125", 128_000),
+    ("gpt-4-32k", 32_768),
+    ("gpt-4", 8_192),
+    ("gpt-3.5-turbo", 16_385),
+    ("o1", 200_000),
+    ("o3", 200_000),
+    ("o4", 200_000),
+    ("claude", 200_000),
+    ("gemini-1.5-pro", 2_097_152),
+    ("gemini", 1_048_576),
+    ("mistral-large", 128_000),
+    ("mistral-nemo", 128_000),
+    ("open-mistral-nemo", 128_000),
+    ("codestral", 256_000),
+    ("mistral", 32_000),
+    ("open-mixtral-8x22b", 64_000),
+    ("mixtral", 32_768),
+    ("command-r", 128_000),
+    ("command", 4_096),
+    ("llama-3.1", 128_000),
+    ("llama-3.2", 128_000),
+    ("llama-3.3", 128_000),
+    ("llama3.1", 128_000),
+    ("llama3.2", 128_000),
+    ("llama3.3", 128_000),
+    ("llama-3", 8_192),
+    ("llama3", 8_192),
+    ("gemma", 8_192),
+    ("sonar", 127_072),
+];
+
+/// The model name from an engine config: `modelName`, or the `deployment`
+/// of an Azure engine.
+pub fn model_name(config: &EngineConfig) -> Option<&str> {
+    ["modelName", "deployment", "model"]
+        .iter()
+        .find_map(|key| config.parameters.get(*key).and_then(|v| v.as_str()))
+}
+
+/// The context window of a known model, matched on the name without any
+/// `vendor/` prefix.
+pub fn context_window(model: &str) -> Option<usize> {
+    let model = model.rsplit('/').next().unwrap_or(model).to_lowercase();
+    CONTEXT_WINDOWS
+        .iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+        .map(|&(_, window)| window)
+}
+
+/// Counts and cuts text in model tokens.
+///
+/// OpenAI models are counted exactly with their BPE encoding; everything
+/// else is an estimate.
+#[derive(Clone, Copy)]
+pub struct Tokenizer {
+    bpe: &'static CoreBPE,
+    exact: bool,
+}
+
+impl Tokenizer {
+    pub fn for_model(engine: &str, model: &str) -> Self {
+        use tiktoken_rs::tokenizer::{get_tokenizer, Tokenizer as Encoding};
+
+        match get_tokenizer(model) {
+            Some(Encoding::O200kBase) => Self::exact(o200k_base_singleton()),
+            Some(Encoding::Cl100kBase) => Self::exact(cl100k_base_singleton()),
+            // Azure deployments and OpenAI fine-tunes may have any name.
+            _ if matches!(engine, "openai" | "azure_openai") => Self::approximate(o200k_base_singleton()),
+            _ => Self::approximate(cl100k_base_singleton()),
+        }
+    }
+
+    pub fn for_config(config: &EngineConfig) -> Self {
+        Self::for_model(&config.engine.to_lowercase(), model_name(config).unwrap_or_default())
+    }
+
+    /// `cl100k_base`, for measuring text that is not tied to a model.
+    pub fn cl100k() -> Self {
+        Self::exact(cl100k_base_singleton())
+    }
+
+    fn exact(bpe: &'static CoreBPE) -> Self {
+        Self { bpe, exact: true }
+    }
+
+    fn approximate(bpe: &'static CoreBPE) -> Self {
+        Self { bpe, exact: false }
+    }
+
+    /// Whether counts come from the model's own tokenizer.
+    pub fn is_exact(&self) -> bool {
+        self.exact
+    }
+
+    fn encode(&self, text: &str) -> Vec<Rank> {
+        self.bpe.encode_ordinary(text)
+    }
+
+    fn scale(&self, tokens: usize) -> usize {
+        if self.exact {
+            tokens
+        } else {
+            (tokens as f64 * APPROXIMATION_MARGIN).ceil() as usize
+        }
+    }
+
+    pub fn count(&self, text: &str) -> usize {
+        self.scale(self.encode(text).len())
+    }
+
+    pub fn count_messages(&self, messages: &[Message]) -> usize {
+        let tokens: usize = messages
+            .iter()
+            .map(|message| {
+                let calls = if message.tool_calls.is_empty() {
+                    0
+                } else {
+                    self.encode(&serde_json::to_string(&message.tool_calls).unwrap_or_default())
+                        .len()
+                };
+                TOKENS_PER_MESSAGE + self.encode(&message.content).len() + calls
+            })
+            .sum();
+        self.scale(tokens + REPLY_PRIMING_TOKENS)
+    }
+
+    /// Tokens the request will use: the conversation plus tool definitions.
+    pub fn count_request(&self, request: &Request) -> usize {
+        let tools = if request.tools.is_empty() {
+            0
+        } else {
+            self.count(&serde_json::to_string(&request.tools).unwrap_or_default())
+        };
+        self.count_messages(&request.conversation()) + tools
+    }
+
+    /// The longest prefix of `text` within `max_tokens`, ending with
+    /// `TRUNCATION_MARKER` when anything was cut. Always cuts on a character
+    /// boundary.
+    pub fn truncate(&self, text: &str, max_tokens: usize) -> String {
+        let tokens = self.encode(text);
+        let keep = if self.exact {
+            max_tokens
+        } else {
+            (max_tokens as f64 / APPROXIMATION_MARGIN) as usize
+        };
+        if tokens.len() <= keep {
+            return text.to_string();
+        }
+
+        let marker = self.encode(TRUNCATION_MARKER).len();
+        let mut end = keep.saturating_sub(marker);
+        // A token boundary may fall inside a multi-byte character.
+        while end > 0 {
+            if let Ok(prefix) = self.bpe.decode(tokens[..end].to_vec()) {
+                return prefix + TRUNCATION_MARKER;
+            }
+            end -= 1;
+        }
+        TRUNCATION_MARKER.to_string()
+    }
+}
+
+/// How many tokens a request may use: the model's context window less the
+/// tokens kept for the reply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenBudget {
+    pub context_window: usize,
+    pub completion_tokens: usize,
+}
+
+impl TokenBudget {
+    /// The budget for an engine, from its `context_window` parameter or the
+    /// known window of its model, and its `max_tokens`. `None` when the
+    /// window is unknown.
+    pub fn for_config(config: &EngineConfig) -> Option<Self> {
+        let number = |key: &str| {
+            config.parameters.get(key).and_then(|value| {
+                value
+                    .as_u64()
+                    .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+            })
+        };
+        let context_window = number("context_window")
+            .map(|window| window as usize)
+            .or_else(|| model_name(config).and_then(context_window))?;
+        let completion_tokens = number("max_tokens")
+            .map(|tokens| tokens as usize)
+            .unwrap_or(DEFAULT_COMPLETION_TOKENS);
+
+        Some(Self {
+            context_window,
+            completion_tokens,
+        })
+    }
+
+    pub fn prompt_tokens(&self) -> usize {
+        self.context_window.saturating_sub(self.completion_tokens)
+    }
+}
+
+/// What `fit_request` found and changed.
+#[derive(Debug, Clone)]
+pub struct PromptEstimate {
+    /// Tokens the request uses after trimming.
+    pub prompt_tokens: usize,
+    /// Whether `prompt_tokens` is an exact count rather than an estimate.
+    pub exact: bool,
+    pub budget: Option<TokenBudget>,
+    /// Earlier conversation turns removed to make room.
+    pub dropped_messages: usize,
+    /// Whether the final user turn was cut short.
+    pub truncated: bool,
+}
+
+impl PromptEstimate {
+    /// Describes the trimming done, if any, e.g. "dropped 2 earlier messages".
+    pub fn trimmed(&self) -> Option<String> {
+        let mut changes = Vec::new();
+        if self.dropped_messages > 0 {
+            changes.push(format!("dropped {} earlier messages", self.dropped_messages));
+        }
+        if self.truncated {
+            changes.push("truncated the prompt".to_string());
+        }
+        (!changes.is_empty()).then(|| changes.join(", "))
+    }
+}
+
+/// The index of the oldest turn that can be dropped: not a system message,
+/// and not the final user turn when the payload is empty.
+fn oldest_droppable(request: &Request) -> Option<usize> {
+    let last = if request.payload.is_empty() {
+        request.messages.len().checked_sub(1)
+    } else {
+        None
+    };
+    request
+        .messages
+        .iter()
+        .enumerate()
+        .find(|&(index, message)| message.role != Role::System && Some(index) != last)
+        .map(|(index, _)| index)
+}
+
+/// Trims a request to fit the engine's token budget.
+///
+/// The oldest conversation turns are dropped first, keeping system messages,
+/// together with the tool results that answer them. If that is not enough,
+/// the final user turn is truncated.
+pub fn fit_request(request: &mut Request, config: &EngineConfig) -> PromptEstimate {
+    let tokenizer = Tokenizer::for_config(config);
+    let budget = TokenBudget::for_config(config);
+    let mut estimate = PromptEstimate {
+        prompt_tokens: tokenizer.count_request(request),
+        exact: tokenizer.is_exact(),
+        budget,
+        dropped_messages: 0,
+        truncated: false,
+    };
+    let Some(limit) = budget.map(|budget| budget.prompt_tokens()) else {
+        return estimate;
+    };
+
+    while estimate.prompt_tokens > limit {
+        let Some(index) = oldest_droppable(request) else {
+            break;
+        };
+        request.messages.remove(index);
+        estimate.dropped_messages += 1;
+        while request
+            .messages
+            .get(index)
+            .is_some_and(|message| message.role == Role::Tool)
+        {
+            request.messages.remove(index);
+            estimate.dropped_messages += 1;
+        }
+        estimate.prompt_tokens = tokenizer.count_request(request);
+    }
+
+    if estimate.prompt_tokens > limit {
+        let text = if request.payload.is_empty() {
+            request.messages.last_mut().map(|message| &mut message.content)
+        } else {
+            Some(&mut request.payload)
+        };
+        if let Some(text) = text {
+            let current = tokenizer.count(text);
+            let room = current.saturating_sub(estimate.prompt_tokens - limit);
+            *text = tokenizer.truncate(text, room);
+            estimate.truncated = true;
+            estimate.prompt_tokens = tokenizer.count_request(request);
+        }
+    }
+
+    if let Some(summary) = estimate.trimmed() {
+        warn!(
+            "Trimmed the request to {} tokens to fit the context window: {}",
+            estimate.prompt_tokens, summary
+        );
+    }
+    estimate
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::types::ToolCall;
+
+    fn config(context_window: usize) -> EngineConfig {
+        serde_json::from_value(json!({
+            "name": "test",
+            "engine": "openai",
+            "parameters": {
+                "modelName": "gpt-4o",
+                "context_window": context_window,
+                "max_tokens": 100,
+            },
+        }))
+        .unwrap()
+    }
+
+    /// A config whose prompt budget is exactly what `request` uses.
+    fn config_fitting(request: &Request) -> EngineConfig {
+        let tokens = Tokenizer::for_config(&config(0)).count_request(request);
+        config(tokens + 100)
+    }
+
+    fn request(messages: Vec<Message>, payload: &str) -> Request {
+        Request {
+            flowname: "test".to_string(),
+            payload: payload.to_string(),
+            messages,
+            tools: Vec::new(),
+            response_schema: None,
+        }
+    }
+
+    fn turn(role: Role, word: &str) -> Message {
+        Message::new(role, format!("{} ", word).repeat(40))
+    }
+
+    fn contents(request: &Request) -> Vec<&str> {
+        request
+            .messages
+            .iter()
+            .map(|message| message.content.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn leaves_a_request_that_fits() {
+        let mut request = request(vec![turn(Role::User, "alpha")], "question");
+        let config = config_fitting(&request);
+        let estimate = fit_request(&mut request, &config);
+
+        assert_eq!(request.messages.len(), 1);
+        assert_eq!(request.payload, "question");
+        assert_eq!(estimate.dropped_messages, 0);
+        assert!(!estimate.truncated);
+        assert!(estimate.exact);
+        assert_eq!(estimate.trimmed(), None);
+    }
+
+    #[test]
+    fn leaves_a_request_for_an_unknown_model() {
+        let config: EngineConfig = serde_json::from_value(json!({
+            "name": "test",
+            "engine": "local",
+            "parameters": { "modelName": "my-model" },
+        }))
+        .unwrap();
+        let mut request = request(vec![turn(Role::User, "alpha")], "question");
+        let estimate = fit_request(&mut request, &config);
+
+        assert_eq!(estimate.budget, None);
+        assert!(!estimate.exact);
+        assert_eq!(request.messages.len(), 1);
+    }
+
+    #[test]
+    fn drops_the_oldest_turns_and_keeps_system_messages() {
+        let system = turn(Role::System, "rules");
+        let newer = vec![turn(Role::User, "gamma"), turn(Role::Assistant, "delta")];
+        let mut expected = request(vec![system.clone()], "question");
+        expected.messages.extend(newer.clone());
+        let config = config_fitting(&expected);
+
+        let mut messages = vec![
+            system,
+            turn(Role::User, "alpha"),
+            turn(Role::Assistant, "beta"),
+        ];
+        messages.extend(newer);
+        let mut request = request(messages, "question");
+        let estimate = fit_request(&mut request, &config);
+
+        assert_eq!(contents(&request), contents(&expected));
+        assert_eq!(request.payload, "question");
+        assert_eq!(estimate.dropped_messages, 2);
+        assert!(!estimate.truncated);
+        assert!(estimate.prompt_tokens <= estimate.budget.unwrap().prompt_tokens());
+        assert_eq!(
+            estimate.trimmed().as_deref(),
+            Some("dropped 2 earlier messages")
+        );
+    }
+
+    #[test]
+    fn drops_tool_results_with_the_call() {
+        let call = ToolCall {
+            id: "call_1".to_string(),
+            name: "lookup".to_string(),
+            arguments: json!({ "query": "alpha" }),
+        };
+        let newer = turn(Role::User, "gamma");
+        let config = config_fitting(&request(vec![newer.clone()], "question"));
+
+        let mut request = request(
+            vec![
+                Message::assistant_tool_calls("", vec![call.clone()]),
+                Message::tool_result(&call, "beta ".repeat(40)),
+                newer,
+            ],
+            "question",
+        );
+        let estimate = fit_request(&mut request, &config);
+
+        assert_eq!(request.messages.len(), 1);
+        assert_eq!(request.messages[0].role, Role::User);
+        assert_eq!(estimate.dropped_messages, 2);
+    }
+
+    #[test]
+    fn truncates_the_final_user_turn() {
+        let text = "Grüße aus Köln — 東京の天気は晴れです。 ".repeat(20);
+        let system = turn(Role::System, "rules");
+        let budget = request(vec![system.clone(), turn(Role::User, "alpha")], "");
+        let config = config_fitting(&budget);
+
+        let mut request = request(vec![system, Message::new(Role::User, text.clone())], "");
+        let estimate = fit_request(&mut request, &config);
+
+        assert_eq!(request.messages.len(), 2);
+        assert_eq!(request.messages[0].role, Role::System);
+        let cut = request.messages[1]
+            .content
+            .strip_suffix(TRUNCATION_MARKER)
+            .unwrap();
+        assert!(!cut.is_empty());
+        assert!(text.starts_with(cut));
+        assert_eq!(estimate.dropped_messages, 0);
+        assert!(estimate.truncated);
+        assert!(estimate.prompt_tokens <= estimate.budget.unwrap().prompt_tokens());
+        assert_eq!(estimate.trimmed().as_deref(), Some("truncated the prompt"));
+    }
+
+    #[test]
+    fn truncates_the_payload_after_dropping_every_turn() {
+        let system = turn(Role::System, "rules");
+        let config = config_fitting(&request(vec![system.clone()], &"alpha ".repeat(40)));
+
+        let mut request = request(
+            vec![system, turn(Role::User, "beta")],
+            &"gamma ".repeat(100),
+        );
+        let estimate = fit_request(&mut request, &config);
+
+        assert_eq!(request.messages.len(), 1);
+        assert_eq!(request.messages[0].role, Role::System);
+        assert!(request.payload.starts_with("gamma "));
+        assert!(request.payload.ends_with(TRUNCATION_MARKER));
+        assert_eq!(estimate.dropped_messages, 1);
+        assert!(estimate.truncated);
+        assert!(estimate.prompt_tokens <= estimate.budget.unwrap().prompt_tokens());
+    }
+
+    #[test]
+    fn reports_a_request_that_cannot_fit() {
+        let config = config_fitting(&request(Vec::new(), "hi"));
+
+        let mut request = request(vec![turn(Role::System, "rules")], "hi");
+        let estimate = fit_request(&mut request, &config);
+
+        assert_eq!(request.messages.len(), 1);
+        assert_eq!(request.payload, TRUNCATION_MARKER);
+        assert!(estimate.truncated);
+        assert!(estimate.prompt_tokens > estimate.budget.unwrap().prompt_tokens());
+    }
+
+    #[test]
+    fn truncate_cuts_on_a_character_boundary() {
+        let tokenizer = Tokenizer::cl100k();
+        let text = "𝔘𝔫𝔦𝔠𝔬𝔡𝔢 🦀🦀 naïve café ".repeat(10);
+        let tokens = tokenizer.count(&text);
+
+        assert_eq!(tokenizer.truncate(&text, tokens), text);
+        for max_tokens in 0..tokens {
+            let truncated = tokenizer.truncate(&text, max_tokens);
+            let cut = truncated.strip_suffix(TRUNCATION_MARKER).unwrap();
+            assert!(text.starts_with(cut), "{:?}", truncated);
+        }
+        let truncated = tokenizer.truncate(&text, 20);
+        assert!(truncated.len() > TRUNCATION_MARKER.len());
+        assert!(tokenizer.count(&truncated) <= 20);
+    }
+
+    #[test]
+    fn approximate_counts_keep_a_margin() {
+        let exact = Tokenizer::for_model("openai", "gpt-4");
+        let approximate = Tokenizer::for_model("anthropic", "claude-3-opus");
+        let text = "The quick brown fox jumps over the lazy dog. ".repeat(10);
+
+        assert!(exact.is_exact());
+        assert!(!approximate.is_exact());
+        assert!(approximate.count(&text) > exact.count(&text));
+        let truncated = approximate.truncate(&text, 50);
+        assert!(approximate.count(&truncated) <= 50);
+    }
+}
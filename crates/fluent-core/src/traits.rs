@@ -1,13 +1,18 @@
 use crate::config::EngineConfig;
+use crate::loaders::{self, LoadedDocument};
 use crate::neo4j_client::Neo4jClient;
-use crate::types::{ExtractedContent, Request, Response, UpsertRequest, UpsertResponse};
+use crate::types::{
+    ExtractedContent, Message, Request, Response, ResponseChunk, Role, ToolCall, ToolDefinition,
+    UpsertRequest, UpsertResponse,
+};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
+use futures::stream::{self, Stream};
 use log::debug;
-use pdf_extract::extract_text;
 use serde_json::{json, Value};
 use std::future::Future;
 use std::path::Path;
+use std::pin::Pin;
 use std::sync::Arc;
 use tokio::fs::File;
 use tokio::io::AsyncReadExt;
@@ -26,6 +31,9 @@ pub trait FileUpload: Send + Sync {
     ) -> Box<dyn Future<Output = Result<Response>> + Send + 'a>;
 }
 
+/// Stream of incremental response chunks produced by `Engine::execute_stream`.
+pub type ResponseStream<'a> = Pin<Box<dyn Stream<Item = Result<ResponseChunk>> + Send + 'a>>;
+
 #[async_trait]
 pub trait Engine: Send + Sync {
     fn execute<'a>(
@@ -53,6 +61,26 @@ pub trait Engine: Send + Sync {
         request: &'a Request,
         file_path: &'a Path,
     ) -> Box<dyn Future<Output = Result<Response>> + Send + 'a>;
+
+    /// Executes the request and yields the generated text as it arrives.
+    ///
+    /// Engines without a streaming API fall back to `execute` and emit the
+    /// whole response as a single chunk.
+    fn execute_stream<'a>(
+        &'a self,
+        request: &'a Request,
+    ) -> Box<dyn Future<Output = Result<ResponseStream<'a>>> + Send + 'a> {
+        Box::new(async move {
+            let response = Pin::from(self.execute(request)).await?;
+            let chunk = ResponseChunk {
+                delta: response.content,
+                usage: Some(response.usage),
+                model: Some(response.model),
+                finish_reason: response.finish_reason,
+            };
+            Ok(Box::pin(stream::once(async move { Ok(chunk) })) as ResponseStream<'a>)
+        })
+    }
 }
 
 pub trait EngineConfigProcessor {
@@ -79,7 +107,7 @@ impl EngineConfigProcessor for AnthropicConfigProcessor {
 
         // Add other parameters, excluding 'sessionID'
         for (key, value) in &config.parameters {
-            if key != "sessionID" && !["modelName", "bearer_token"].contains(&key.as_str()) {
+            if key != "sessionID" && !["modelName", "bearer_token", "context_window"].contains(&key.as_str()) {
                 match key.as_str() {
                     "max_tokens" => {
                         if let Some(num) = value.as_str().and_then(|s| s.parse::<i64>().ok()) {
@@ -107,7 +135,173 @@ impl EngineConfigProcessor for AnthropicConfigProcessor {
     }
 }
 
+impl AnthropicConfigProcessor {
+    /// Maps a conversation onto an Anthropic payload. System turns go to the
+    /// top-level `system` prompt (after any configured one), the rest to `messages`.
+    pub fn apply_messages(payload: &mut Value, messages: &[Message]) {
+        let (system, turns): (Vec<&Message>, Vec<&Message>) =
+            messages.iter().partition(|m| m.role == Role::System);
+
+        if !system.is_empty() {
+            let mut prompts: Vec<String> = payload["system"]
+                .as_str()
+                .map(|s| vec![s.to_string()])
+                .unwrap_or_default();
+            prompts.extend(system.iter().map(|m| m.content.clone()));
+            payload["system"] = json!(prompts.join("\n\n"));
+        }
+
+        let mut mapped: Vec<Value> = Vec::new();
+        for m in turns {
+            match m.role {
+                Role::Tool => {
+                    let block = json!({
+                        "type": "tool_result",
+                        "tool_use_id": m.tool_call_id,
+                        "content": m.content,
+                    });
+                    // Results for one assistant turn go back in a single user turn.
+                    match mapped.last_mut() {
+                        Some(last) if last["content"][0]["type"] == "tool_result" => {
+                            if let Some(blocks) = last["content"].as_array_mut() {
+                                blocks.push(block);
+                            }
+                        }
+                        _ => mapped.push(json!({ "role": "user", "content": [block] })),
+                    }
+                }
+                Role::Assistant if !m.tool_calls.is_empty() => {
+                    let mut blocks = Vec::new();
+                    if !m.content.is_empty() {
+                        blocks.push(json!({ "type": "text", "text": m.content }));
+                    }
+                    blocks.extend(m.tool_calls.iter().map(|call| {
+                        json!({
+                            "type": "tool_use",
+                            "id": call.id,
+                            "name": call.name,
+                            "input": call.arguments,
+                        })
+                    }));
+                    mapped.push(json!({ "role": "assistant", "content": blocks }));
+                }
+                _ => mapped.push(json!({ "role": m.role.as_str(), "content": m.content })),
+            }
+        }
+        payload["messages"] = Value::Array(mapped);
+    }
+
+    pub fn map_tools(tools: &[ToolDefinition]) -> Value {
+        tools
+            .iter()
+            .map(|tool| {
+                json!({
+                    "name": tool.name,
+                    "description": tool.description,
+                    "input_schema": tool.parameters,
+                })
+            })
+            .collect()
+    }
+
+    /// Splits a Messages API response body into its text and `tool_use` blocks.
+    pub fn parse_content(response_body: &Value) -> (String, Vec<ToolCall>) {
+        let mut text = Vec::new();
+        let mut tool_calls = Vec::new();
+        for block in response_body["content"].as_array().into_iter().flatten() {
+            match block["type"].as_str() {
+                Some("text") => text.extend(block["text"].as_str()),
+                Some("tool_use") => tool_calls.push(ToolCall {
+                    id: block["id"].as_str().unwrap_or_default().to_string(),
+                    name: block["name"].as_str().unwrap_or_default().to_string(),
+                    arguments: block["input"].clone(),
+                }),
+                _ => {}
+            }
+        }
+        (text.concat(), tool_calls)
+    }
+}
+
 pub struct OpenAIConfigProcessor;
+impl OpenAIConfigProcessor {
+    /// Maps a conversation into a chat completions `messages` array. Mistral,
+    /// Groq and Perplexity accept the same shape.
+    pub fn map_messages(messages: &[Message]) -> Value {
+        messages
+            .iter()
+            .map(|m| match m.role {
+                Role::Tool => json!({
+                    "role": "tool",
+                    "tool_call_id": m.tool_call_id,
+                    "content": m.content,
+                }),
+                Role::Assistant if !m.tool_calls.is_empty() => json!({
+                    "role": "assistant",
+                    "content": if m.content.is_empty() { Value::Null } else { json!(m.content) },
+                    "tool_calls": m.tool_calls.iter().map(|call| json!({
+                        "id": call.id,
+                        "type": "function",
+                        "function": {
+                            "name": call.name,
+                            "arguments": match &call.arguments {
+                                Value::String(raw) => raw.clone(),
+                                arguments => arguments.to_string(),
+                            },
+                        },
+                    })).collect::<Vec<_>>(),
+                }),
+                _ => json!({ "role": m.role.as_str(), "content": m.content }),
+            })
+            .collect()
+    }
+
+    /// The `response_format` for a requested JSON Schema. JSON mode only
+    /// produces objects, so it is left off for schemas whose top level is an
+    /// array or a scalar; the reply is still validated against the schema.
+    pub fn response_format(schema: Option<&Value>) -> Option<Value> {
+        (schema?["type"] == "object").then(|| json!({ "type": "json_object" }))
+    }
+
+    pub fn map_tools(tools: &[ToolDefinition]) -> Value {
+        tools
+            .iter()
+            .map(|tool| {
+                json!({
+                    "type": "function",
+                    "function": {
+                        "name": tool.name,
+                        "description": tool.description,
+                        "parameters": tool.parameters,
+                    },
+                })
+            })
+            .collect()
+    }
+
+    /// Reads the `tool_calls` of a chat completions `message`. Arguments arrive
+    /// as a JSON string; they are kept as a string if they fail to parse.
+    pub fn parse_tool_calls(message: &Value) -> Vec<ToolCall> {
+        message["tool_calls"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|call| {
+                let arguments = match &call["function"]["arguments"] {
+                    Value::String(raw) => serde_json::from_str(raw)
+                        .unwrap_or_else(|_| Value::String(raw.clone())),
+                    arguments => arguments.clone(),
+                };
+                ToolCall {
+                    id: call["id"].as_str().unwrap_or_default().to_string(),
+                    name: call["function"]["name"].as_str().unwrap_or_default().to_string(),
+                    arguments,
+                }
+            })
+            .collect()
+    }
+}
+
 impl EngineConfigProcessor for OpenAIConfigProcessor {
     fn process_config(&self, config: &EngineConfig) -> Result<serde_json::Value> {
         debug!("OpenAIConfigProcessor::process_config");
@@ -161,6 +355,14 @@ impl EngineConfigProcessor for OpenAIConfigProcessor {
 pub struct TextProcessor;
 pub struct PdfProcessor;
 pub struct DocxProcessor;
+pub struct PptxProcessor;
+pub struct XlsxProcessor;
+/// Comma-separated values, or tab-separated with a `'\t'` delimiter.
+pub struct CsvProcessor {
+    pub delimiter: char,
+}
+pub struct HtmlProcessor;
+pub struct EpubProcessor;
 #[async_trait]
 pub trait DocumentProcessor {
     async fn process(&self, file_path: &Path) -> Result<(String, Vec<String>)>;
@@ -179,75 +381,92 @@ impl DocumentProcessor for TextProcessor {
 #[async_trait]
 impl DocumentProcessor for PdfProcessor {
     async fn process(&self, file_path: &Path) -> Result<(String, Vec<String>)> {
-        // Clone the PathBuf to move it into the closure
-        debug!("PdfProcessor::process");
-        let path_buf = file_path.to_path_buf();
-
-        // Extract text from PDF
-        let text = tokio::task::spawn_blocking(move || {
-            debug!("PdfProcessor::process: Extracting text from PDF");
-            extract_text(&path_buf)
-        })
-        .await??;
-
-        // Extract metadata (you can expand this based on your needs)
-        let mut metadata = Vec::new();
-        debug!("PdfProcessor::process: Extracting metadata from PDF");
-
-        // Add file name to metadata
-        if let Some(file_name) = file_path.file_name() {
-            if let Some(file_name_str) = file_name.to_str() {
-                metadata.push(format!("filename:{}", file_name_str));
-            }
-        }
-        debug!("PdfProcessor::process: Metadata: {:#?}", metadata);
-        // Add file size to metadata
-        let file_size = tokio::fs::metadata(file_path).await?.len();
+        load_document(file_path, loaders::load_pdf).await
+    }
+}
 
-        debug!("PdfProcessor::process: File size: {}", file_size);
-        metadata.push(format!("file_size:{}", file_size));
+/// Runs a loader from `crate::loaders` on a file, returning the loaded
+/// document and the metadata it found along with the file's name, size and
+/// type.
+pub(crate) async fn load_file<F>(
+    file_path: &Path,
+    loader: F,
+) -> Result<(LoadedDocument, Vec<String>)>
+where
+    F: FnOnce(&[u8]) -> Result<LoadedDocument> + Send + 'static,
+{
+    let mut file = File::open(file_path).await?;
+    let mut buffer = Vec::new();
+    file.read_to_end(&mut buffer).await?;
+    let file_size = buffer.len();
+
+    let document = tokio::task::spawn_blocking(move || loader(&buffer)).await??;
+
+    let mut metadata = Vec::new();
+    if let Some(file_name) = file_path.file_name() {
+        metadata.push(format!("filename:{}", file_name.to_string_lossy()));
+    }
+    metadata.push(format!("filesize:{}", file_size));
+    if let Some(extension) = file_path.extension() {
+        metadata.push(format!(
+            "filetype:{}",
+            extension.to_string_lossy().to_lowercase()
+        ));
+    }
+    metadata.extend(document.metadata());
+    Ok((document, metadata))
+}
 
-        // You can add more metadata extraction here, such as:
-        // - Number of pages
-        // - Author
-        // - Creation date
-        // - etc.
+async fn load_document<F>(file_path: &Path, loader: F) -> Result<(String, Vec<String>)>
+where
+    F: FnOnce(&[u8]) -> Result<LoadedDocument> + Send + 'static,
+{
+    let (document, metadata) = load_file(file_path, loader).await?;
+    Ok((document.text, metadata))
+}
 
-        Ok((text, metadata))
+#[async_trait]
+impl DocumentProcessor for DocxProcessor {
+    async fn process(&self, file_path: &Path) -> Result<(String, Vec<String>)> {
+        load_document(file_path, loaders::load_docx).await
     }
 }
 
 #[async_trait]
-impl DocumentProcessor for DocxProcessor {
+impl DocumentProcessor for PptxProcessor {
     async fn process(&self, file_path: &Path) -> Result<(String, Vec<String>)> {
-        let mut file = File::open(file_path).await?;
-        let mut buffer = Vec::new();
-        file.read_to_end(&mut buffer).await?;
+        load_document(file_path, loaders::load_pptx).await
+    }
+}
 
-        let file_path = file_path.to_owned();
-        let file_size = buffer.len(); // Calculate buffer length here
+#[async_trait]
+impl DocumentProcessor for XlsxProcessor {
+    async fn process(&self, file_path: &Path) -> Result<(String, Vec<String>)> {
+        load_document(file_path, loaders::load_xlsx).await
+    }
+}
 
-        let content = tokio::task::spawn_blocking(move || -> Result<String> {
-            // Instead of parsing DOCX, we'll just read the file as UTF-8 text
-            // This won't work for actual DOCX files, but serves as a placeholder
-            let content = String::from_utf8_lossy(&buffer).to_string();
+#[async_trait]
+impl DocumentProcessor for CsvProcessor {
+    async fn process(&self, file_path: &Path) -> Result<(String, Vec<String>)> {
+        let delimiter = self.delimiter;
+        load_document(file_path, move |bytes| {
+            loaders::load_delimited(bytes, delimiter)
+        })
+        .await
+    }
+}
 
-            // Simulate paragraph separation
-            let content = content.replace("\n", "\n\n");
+#[async_trait]
+impl DocumentProcessor for HtmlProcessor {
+    async fn process(&self, file_path: &Path) -> Result<(String, Vec<String>)> {
+        load_document(file_path, loaders::load_html).await
+    }
+}
 
-            Ok(content)
-        })
-        .await??;
-
-        let metadata = vec![
-            format!(
-                "filename:{}",
-                file_path.file_name().unwrap().to_string_lossy()
-            ),
-            format!("filesize:{}", file_size), // Use file_size here instead of buffer.len()
-            "filetype:docx".to_string(), // Adding this to maintain similarity with original function
-        ];
-
-        Ok((content, metadata))
+#[async_trait]
+impl DocumentProcessor for EpubProcessor {
+    async fn process(&self, file_path: &Path) -> Result<(String, Vec<String>)> {
+        load_document(file_path, loaders::load_epub).await
     }
 }
@@ -1,23 +1,156 @@
 // crates/fluent-core/src/types.rs
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
 pub struct Request {
     pub flowname: String,
     pub payload: String,
+    /// Prior conversation turns. When present, `payload` is sent as the next
+    /// user turn after them (and may be left empty if the last message
+    /// already is the user turn to answer).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub messages: Vec<Message>,
+    /// Tools the model may call instead of, or before, answering.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<ToolDefinition>,
+    /// When set, the reply should be JSON matching this schema. Engines with a
+    /// native JSON mode switch it on; validation is up to the caller.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_schema: Option<Value>,
+}
+
+impl Request {
+    /// Ordered conversation to send to a chat engine: `messages` followed by
+    /// `payload` as a user turn. A request without messages is a single user turn.
+    pub fn conversation(&self) -> Vec<Message> {
+        let mut conversation = self.messages.clone();
+        if !self.payload.is_empty() || conversation.is_empty() {
+            conversation.push(Message::new(Role::User, self.payload.clone()));
+        }
+        conversation
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    System,
+    User,
+    Assistant,
+    /// The result of a tool call, sent back to the model.
+    Tool,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::System => "system",
+            Role::User => "user",
+            Role::Assistant => "assistant",
+            Role::Tool => "tool",
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Message {
+    pub role: Role,
+    pub content: String,
+    /// Tool calls the assistant made in this turn.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    /// For `Role::Tool` turns, the id of the call this result answers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+    /// For `Role::Tool` turns, the name of the tool that produced the result.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl Message {
+    pub fn new(role: Role, content: impl Into<String>) -> Self {
+        Message {
+            role,
+            content: content.into(),
+            tool_calls: Vec::new(),
+            tool_call_id: None,
+            name: None,
+        }
+    }
+
+    /// An assistant turn that requested `tool_calls`, to replay in the history.
+    pub fn assistant_tool_calls(content: impl Into<String>, tool_calls: Vec<ToolCall>) -> Self {
+        Message {
+            tool_calls,
+            ..Message::new(Role::Assistant, content)
+        }
+    }
+
+    /// A tool turn answering `call` with `content`.
+    pub fn tool_result(call: &ToolCall, content: impl Into<String>) -> Self {
+        Message {
+            tool_call_id: Some(call.id.clone()),
+            name: Some(call.name.clone()),
+            ..Message::new(Role::Tool, content)
+        }
+    }
+}
+
+/// A tool the model may call, with its arguments described by a JSON Schema.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ToolDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "empty_object_schema")]
+    pub parameters: Value,
+}
+
+fn empty_object_schema() -> Value {
+    serde_json::json!({ "type": "object", "properties": {} })
+}
+
+/// A call to one of the request's tools, as returned by the model.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    pub arguments: Value,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
 pub struct Response {
     pub content: String,
     pub usage: Usage,
     pub model: String,
     pub finish_reason: Option<String>,
-
+    /// Tool calls the model made instead of, or alongside, its text answer.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    /// Name of the configured engine that answered, set by fallback chains.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub engine: Option<String>,
+    /// Cost in US dollars, when the engine prices the response itself
+    /// (image engines) or a fallback chain priced it for the engine that answered.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
 }
 
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+/// A single incremental piece of a streamed response.
+///
+/// `delta` carries the newly generated text. Usage, model and finish reason are
+/// only set on the chunks where the provider reports them, usually the last one.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct ResponseChunk {
+    pub delta: String,
+    pub usage: Option<Usage>,
+    pub model: Option<String>,
+    pub finish_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
 pub struct Usage {
     pub prompt_tokens: u32,
     pub completion_tokens: u32,
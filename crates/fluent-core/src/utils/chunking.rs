@@ -0,0 +1,501 @@
+// crates/fluent-core/src/utils/chunking.rs
+use std::ops::Range;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use anyhow::{anyhow, Error};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::tokenizer::Tokenizer;
+
+/// Tokens per chunk when not configured.
+pub const DEFAULT_CHUNK_SIZE: usize = 1000;
+/// Tokens repeated between consecutive chunks when not configured.
+pub const DEFAULT_CHUNK_OVERLAP: usize = 200;
+
+/// How a document is cut into chunks. Every strategy keeps the text as
+/// written and falls back to smaller pieces for parts larger than a chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChunkStrategy {
+    /// Fixed windows of `size` tokens, cut between words.
+    #[default]
+    Tokens,
+    /// Whole paragraphs, or whole sentences of long paragraphs, packed up to
+    /// `size` tokens.
+    Sentences,
+    /// Sections starting at Markdown headings, packed up to `size` tokens.
+    Markdown,
+    /// Top-level blocks of source code, such as functions, split at blank
+    /// lines followed by an unindented line.
+    Code,
+}
+
+impl FromStr for ChunkStrategy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tokens" => Ok(ChunkStrategy::Tokens),
+            "sentences" => Ok(ChunkStrategy::Sentences),
+            "markdown" => Ok(ChunkStrategy::Markdown),
+            "code" => Ok(ChunkStrategy::Code),
+            _ => Err(anyhow!(
+                "Unknown chunk strategy '{}'; use tokens, sentences, markdown or code",
+                s
+            )),
+        }
+    }
+}
+
+/// Set as `chunking` in a `neo4j` config, or per upsert on the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChunkingConfig {
+    pub strategy: ChunkStrategy,
+    /// Most tokens in a chunk.
+    pub size: usize,
+    /// Tokens at the end of a chunk repeated at the start of the next.
+    pub overlap: usize,
+}
+
+impl Default for ChunkingConfig {
+    fn default() -> Self {
+        Self {
+            strategy: ChunkStrategy::default(),
+            size: DEFAULT_CHUNK_SIZE,
+            overlap: DEFAULT_CHUNK_OVERLAP,
+        }
+    }
+}
+
+/// A piece of a document with its position in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk {
+    pub content: String,
+    /// Character offset of the first character of `content`.
+    pub start: usize,
+    /// Character offset just past the last character of `content`.
+    pub end: usize,
+}
+
+/// Finds where a span of text splits into smaller pieces, as byte offsets
+/// strictly inside the span.
+type Splitter = fn(&str, Range<usize>) -> Vec<usize>;
+
+fn regex(cell: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+    cell.get_or_init(|| Regex::new(pattern).expect("valid chunking regex"))
+}
+
+fn words(text: &str, span: Range<usize>) -> Vec<usize> {
+    static WORD: OnceLock<Regex> = OnceLock::new();
+    regex(&WORD, r"\S+")
+        .find_iter(&text[span.clone()])
+        .map(|m| span.start + m.start())
+        .collect()
+}
+
+fn sentences(text: &str, span: Range<usize>) -> Vec<usize> {
+    text[span.clone()]
+        .split_sentence_bound_indices()
+        .map(|(i, _)| span.start + i)
+        .collect()
+}
+
+fn lines(text: &str, span: Range<usize>) -> Vec<usize> {
+    text[span.clone()]
+        .match_indices('\n')
+        .map(|(i, _)| span.start + i + 1)
+        .collect()
+}
+
+fn paragraphs(text: &str, span: Range<usize>) -> Vec<usize> {
+    static BLANK_LINE: OnceLock<Regex> = OnceLock::new();
+    regex(&BLANK_LINE, r"\n[ \t]*\n\s*")
+        .find_iter(&text[span.clone()])
+        .map(|m| span.start + m.end())
+        .collect()
+}
+
+/// Line starts with their byte offsets.
+fn line_starts(text: &str, span: Range<usize>) -> impl Iterator<Item = (usize, &str)> {
+    let base = span.start;
+    text[span].split_inclusive('\n').scan(base, |offset, line| {
+        let start = *offset;
+        *offset += line.len();
+        Some((start, line))
+    })
+}
+
+/// Starts of Markdown headings outside fenced code blocks.
+fn markdown_sections(text: &str, span: Range<usize>) -> Vec<usize> {
+    static HEADING: OnceLock<Regex> = OnceLock::new();
+    let heading = regex(&HEADING, r"^#{1,6}\s");
+    let mut in_fence = false;
+    let mut starts = Vec::new();
+    for (start, line) in line_starts(text, span) {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        } else if !in_fence && heading.is_match(line) {
+            starts.push(start);
+        }
+    }
+    starts
+}
+
+/// Starts of top-level code blocks: an unindented line after a blank line,
+/// unless it only closes a bracket.
+fn code_blocks(text: &str, span: Range<usize>) -> Vec<usize> {
+    let mut after_blank = false;
+    let mut starts = Vec::new();
+    for (start, line) in line_starts(text, span) {
+        if line.trim().is_empty() {
+            after_blank = true;
+            continue;
+        }
+        let continues = line.starts_with(char::is_whitespace) || line.starts_with(['}', ')', ']']);
+        if after_blank && !continues {
+            starts.push(start);
+        }
+        after_blank = false;
+    }
+    starts
+}
+
+/// The splitters of a strategy, from coarsest to finest.
+fn splitters(strategy: ChunkStrategy) -> &'static [Splitter] {
+    match strategy {
+        ChunkStrategy::Tokens => &[words],
+        ChunkStrategy::Sentences => &[paragraphs, sentences, words],
+        ChunkStrategy::Markdown => &[markdown_sections, paragraphs, sentences, words],
+        ChunkStrategy::Code => &[code_blocks, lines, words],
+    }
+}
+
+struct Unit {
+    span: Range<usize>,
+    tokens: usize,
+}
+
+/// Cuts a span into contiguous units of at most `size` tokens, using each
+/// splitter in turn on the pieces still too large. A piece the finest
+/// splitter cannot cut, such as a very long word, is kept whole.
+fn split_to_fit(
+    text: &str,
+    span: Range<usize>,
+    levels: &[Splitter],
+    size: usize,
+    tokenizer: &Tokenizer,
+    units: &mut Vec<Unit>,
+) {
+    let mut bounds = vec![span.start];
+    bounds.extend(
+        levels[0](text, span.clone())
+            .into_iter()
+            .filter(|&i| i > span.start && i < span.end),
+    );
+    bounds.push(span.end);
+    bounds.dedup();
+
+    for piece in bounds.windows(2).map(|w| w[0]..w[1]) {
+        let tokens = tokenizer.count(&text[piece.clone()]);
+        if tokens > size && levels.len() > 1 {
+            split_to_fit(text, piece, &levels[1..], size, tokenizer, units);
+        } else {
+            units.push(Unit {
+                span: piece,
+                tokens,
+            });
+        }
+    }
+}
+
+/// Packs consecutive units into chunks of at most `size` tokens, starting
+/// each chunk with the last units of the previous one, up to `overlap`
+/// tokens.
+fn pack(units: &[Unit], size: usize, overlap: usize) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    let mut first = 0;
+    while first < units.len() {
+        let mut next = first;
+        let mut tokens = 0;
+        while next < units.len() && (next == first || tokens + units[next].tokens <= size) {
+            tokens += units[next].tokens;
+            next += 1;
+        }
+        spans.push(units[first].span.start..units[next - 1].span.end);
+        if next == units.len() {
+            break;
+        }
+
+        let mut start = next;
+        let mut repeated = 0;
+        while start > first + 1 && repeated + units[start - 1].tokens <= overlap {
+            repeated += units[start - 1].tokens;
+            start -= 1;
+        }
+        first = start;
+    }
+    spans
+}
+
+/// Converts increasing byte offsets to character offsets.
+struct CharOffsets<'a> {
+    text: &'a str,
+    byte: usize,
+    chars: usize,
+}
+
+impl CharOffsets<'_> {
+    fn at(&mut self, byte: usize) -> usize {
+        self.chars += self.text[self.byte..byte].chars().count();
+        self.byte = byte;
+        self.chars
+    }
+}
+
+/// Cuts `text` into chunks as configured.
+pub fn chunk_text(text: &str, config: &ChunkingConfig) -> Vec<Chunk> {
+    let tokenizer = Tokenizer::cl100k();
+    let size = config.size.max(1);
+    // Chunks must move forward, so at most half of one can be repeated.
+    let overlap = config.overlap.min(size / 2);
+
+    let mut units = Vec::new();
+    split_to_fit(
+        text,
+        0..text.len(),
+        splitters(config.strategy),
+        size,
+        &tokenizer,
+        &mut units,
+    );
+
+    let mut starts = CharOffsets {
+        text,
+        byte: 0,
+        chars: 0,
+    };
+    let mut ends = CharOffsets {
+        text,
+        byte: 0,
+        chars: 0,
+    };
+    pack(&units, size, overlap)
+        .into_iter()
+        .filter_map(|span| {
+            let piece = &text[span.clone()];
+            let trimmed = piece.trim();
+            if trimmed.is_empty() {
+                return None;
+            }
+            let start = span.start + (piece.len() - piece.trim_start().len());
+            let end = start + trimmed.len();
+            Some(Chunk {
+                content: trimmed.to_string(),
+                start: starts.at(start),
+                end: ends.at(end),
+            })
+        })
+        .collect()
+}
+
+/// Cuts `content` into chunks with the default settings.
+pub fn chunk_document(content: &str) -> Vec<String> {
+    chunk_text(content, &ChunkingConfig::default())
+        .into_iter()
+        .map(|chunk| chunk.content)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(strategy: ChunkStrategy, size: usize, overlap: usize) -> ChunkingConfig {
+        ChunkingConfig {
+            strategy,
+            size,
+            overlap,
+        }
+    }
+
+    fn contents(chunks: &[Chunk]) -> Vec<&str> {
+        chunks.iter().map(|chunk| chunk.content.as_str()).collect()
+    }
+
+    /// Checks that each chunk's character offsets slice back to its content.
+    fn assert_offsets(text: &str, chunks: &[Chunk]) {
+        for chunk in chunks {
+            let slice: String = text
+                .chars()
+                .skip(chunk.start)
+                .take(chunk.end - chunk.start)
+                .collect();
+            assert_eq!(slice, chunk.content);
+        }
+    }
+
+    fn units(tokens: &[usize]) -> Vec<Unit> {
+        tokens
+            .iter()
+            .enumerate()
+            .map(|(i, &tokens)| Unit {
+                span: i..i + 1,
+                tokens,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tokens_chunks_stay_within_size() {
+        let text = (0..300)
+            .map(|i| format!("word{}", i))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let chunks = chunk_text(&text, &config(ChunkStrategy::Tokens, 20, 5));
+        let tokenizer = Tokenizer::cl100k();
+
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            assert!(tokenizer.count(&chunk.content) <= 20, "{:?}", chunk);
+        }
+        assert_eq!(chunks[0].start, 0);
+        assert_eq!(chunks.last().unwrap().end, text.chars().count());
+        assert_offsets(&text, &chunks);
+    }
+
+    #[test]
+    fn overlap_repeats_the_end_of_the_previous_chunk() {
+        let text = (0..100)
+            .map(|i| format!("word{}", i))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let chunks = chunk_text(&text, &config(ChunkStrategy::Tokens, 20, 5));
+
+        for pair in chunks.windows(2) {
+            assert!(pair[1].start > pair[0].start);
+            assert!(pair[1].start < pair[0].end);
+        }
+    }
+
+    #[test]
+    fn overlap_never_stalls() {
+        let text = "one two three four five six seven eight nine ten ".repeat(10);
+        // Overlap is capped at half a chunk, so chunks still move forward.
+        let chunks = chunk_text(&text, &config(ChunkStrategy::Tokens, 4, 100));
+
+        assert!(chunks.len() > 1);
+        for pair in chunks.windows(2) {
+            assert!(pair[1].start > pair[0].start);
+        }
+        assert_eq!(chunks.last().unwrap().end, text.trim_end().chars().count());
+
+        // Units as large as the overlap still leave each chunk's first unit
+        // behind.
+        assert_eq!(pack(&units(&[10, 10, 10]), 10, 10), [0..1, 1..2, 2..3]);
+    }
+
+    #[test]
+    fn pack_fills_chunks_and_repeats_overlap() {
+        assert_eq!(pack(&units(&[5, 5, 5, 5]), 10, 5), [0..2, 1..3, 2..4]);
+        assert_eq!(pack(&units(&[5, 5, 5, 5]), 10, 0), [0..2, 2..4]);
+        assert!(pack(&[], 10, 5).is_empty());
+    }
+
+    #[test]
+    fn oversized_unit_is_kept_whole() {
+        assert_eq!(pack(&units(&[3, 50, 3]), 10, 2), [0..1, 1..2, 2..3]);
+
+        let word = "x".repeat(500);
+        let text = format!("short {} words", word);
+        let chunks = chunk_text(&text, &config(ChunkStrategy::Tokens, 10, 0));
+        assert!(contents(&chunks).iter().any(|chunk| chunk.contains(&word)));
+        assert_offsets(&text, &chunks);
+    }
+
+    #[test]
+    fn multibyte_offsets_are_characters() {
+        let text = "Grüße aus Köln 🦀 — naïve café déjà vu. ".repeat(20);
+        let chunks = chunk_text(&text, &config(ChunkStrategy::Tokens, 12, 4));
+
+        assert!(chunks.len() > 1);
+        assert!(chunks.last().unwrap().end <= text.chars().count());
+        assert_offsets(&text, &chunks);
+    }
+
+    #[test]
+    fn sentences_keep_paragraphs_together() {
+        let paragraphs = [
+            "The first paragraph has two sentences. This is the second one.",
+            "Another paragraph follows here. It also has two sentences.",
+            "The last paragraph is short.",
+        ];
+        let text = paragraphs.join("\n\n");
+        let tokenizer = Tokenizer::cl100k();
+        let size = paragraphs.iter().map(|p| tokenizer.count(p)).max().unwrap();
+        let chunks = chunk_text(&text, &config(ChunkStrategy::Sentences, size, 0));
+
+        assert_eq!(contents(&chunks), paragraphs);
+        assert_offsets(&text, &chunks);
+    }
+
+    #[test]
+    fn sentences_split_long_paragraphs_between_sentences() {
+        let text = "One short sentence here. Two short sentences here. Three short sentences here.";
+        let chunks = chunk_text(text, &config(ChunkStrategy::Sentences, 6, 0));
+
+        assert_eq!(
+            contents(&chunks),
+            [
+                "One short sentence here.",
+                "Two short sentences here.",
+                "Three short sentences here."
+            ]
+        );
+    }
+
+    #[test]
+    fn markdown_splits_at_headings_outside_fences() {
+        let sections = [
+            "# Install\nRun the installer.\n\n```sh\n# not a heading\n./install.sh\n```",
+            "## Usage\nPass a file to read.",
+        ];
+        let text = sections.join("\n\n");
+        let tokenizer = Tokenizer::cl100k();
+        // A section's unit runs on to the next heading, blank line included.
+        let size = sections
+            .iter()
+            .map(|s| tokenizer.count(&format!("{}\n\n", s)))
+            .max()
+            .unwrap();
+        let chunks = chunk_text(&text, &config(ChunkStrategy::Markdown, size, 0));
+
+        assert_eq!(contents(&chunks), sections);
+        assert_offsets(&text, &chunks);
+    }
+
+    #[test]
+    fn code_splits_at_top_level_blocks() {
+        let blocks = [
+            "fn first() {\n    let x = 1;\n\n    x + 1\n}",
+            "fn second() -> u32 {\n    2\n}",
+        ];
+        let text = blocks.join("\n\n");
+        let tokenizer = Tokenizer::cl100k();
+        let size = blocks.iter().map(|b| tokenizer.count(b)).max().unwrap();
+        let chunks = chunk_text(&text, &config(ChunkStrategy::Code, size, 0));
+
+        assert_eq!(contents(&chunks), blocks);
+        assert_offsets(&text, &chunks);
+    }
+
+    #[test]
+    fn blank_text_has_no_chunks() {
+        assert!(chunk_text("", &ChunkingConfig::default()).is_empty());
+        assert!(chunk_text(" \n\n\t ", &ChunkingConfig::default()).is_empty());
+    }
+}
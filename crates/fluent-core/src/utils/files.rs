@@ -0,0 +1,477 @@
+// crates/fluent-core/src/utils/files.rs
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use regex::Regex;
+
+/// Largest file upserted when not set otherwise, in megabytes.
+pub const DEFAULT_MAX_FILE_SIZE_MB: u64 = 25;
+
+/// A glob with `.gitignore` rules: `*` and `?` stay within a directory, `**`
+/// crosses directories, a pattern without `/` matches a name at any depth,
+/// and a trailing `/` matches only directories.
+#[derive(Debug, Clone)]
+pub struct Glob {
+    regex: Regex,
+    dir_only: bool,
+    /// The pattern's path components that start with a `.`, which name
+    /// hidden files or directories.
+    hidden: Vec<Regex>,
+}
+
+impl Glob {
+    pub fn new(pattern: &str) -> Result<Self> {
+        let dir_only = pattern.ends_with('/');
+        let pattern = pattern.trim_end_matches('/');
+        if pattern.is_empty() {
+            return Err(anyhow!("Empty glob pattern"));
+        }
+        let anchored = pattern.contains('/');
+        let pattern = pattern.trim_start_matches('/');
+        let prefix = if anchored { "" } else { "(?:.*/)?" };
+        let regex = Regex::new(&format!("^{}{}$", prefix, glob_regex(pattern)))
+            .with_context(|| format!("Invalid glob pattern '{}'", pattern))?;
+        let hidden = pattern
+            .split('/')
+            .filter(|component| component.starts_with('.'))
+            .map(|component| Regex::new(&format!("^{}$", glob_regex(component))))
+            .collect::<Result<_, _>>()
+            .with_context(|| format!("Invalid glob pattern '{}'", pattern))?;
+        Ok(Self {
+            regex,
+            dir_only,
+            hidden,
+        })
+    }
+
+    /// Whether `path`, relative to where the pattern applies and with `/`
+    /// separators, matches.
+    pub fn is_match(&self, path: &str, is_dir: bool) -> bool {
+        (is_dir || !self.dir_only) && self.regex.is_match(path)
+    }
+
+    /// Whether the pattern spells out a hidden file or directory `name`, as
+    /// `.github/**` does for `.github`.
+    pub fn names_hidden(&self, name: &str) -> bool {
+        self.hidden.iter().any(|component| component.is_match(name))
+    }
+}
+
+fn glob_regex(pattern: &str) -> String {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut regex = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                if chars.get(i + 2) == Some(&'/') {
+                    regex.push_str("(?:.*/)?");
+                    i += 3;
+                } else {
+                    regex.push_str(".*");
+                    i += 2;
+                }
+                continue;
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => {
+                let negated = matches!(chars.get(i + 1), Some('!' | '^'));
+                let first = i + 1 + usize::from(negated);
+                // A `]` right after the opening bracket is part of the set.
+                let close = (first + 1..chars.len()).find(|&j| chars[j] == ']');
+                match close {
+                    Some(close) => {
+                        regex.push('[');
+                        if negated {
+                            regex.push('^');
+                        }
+                        for &c in &chars[first..close] {
+                            if matches!(c, '\\' | '[' | ']' | '^' | '&' | '~') {
+                                regex.push('\\');
+                            }
+                            regex.push(c);
+                        }
+                        regex.push(']');
+                        i = close;
+                    }
+                    None => regex.push_str(r"\["),
+                }
+            }
+            '\\' if i + 1 < chars.len() => {
+                i += 1;
+                regex.push_str(&regex::escape(&chars[i].to_string()));
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+    regex
+}
+
+struct IgnoreRule {
+    glob: Glob,
+    negated: bool,
+}
+
+/// The rules of one `.gitignore`, which apply below the directory it is in.
+struct IgnoreFile {
+    /// The directory relative to the walk's root, with a trailing `/`, or
+    /// empty for the root.
+    base: String,
+    rules: Vec<IgnoreRule>,
+}
+
+fn parse_gitignore(text: &str) -> Vec<IgnoreRule> {
+    text.lines()
+        .filter_map(|line| {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let (negated, pattern) = match line.strip_prefix('!') {
+                Some(pattern) => (true, pattern),
+                None => (false, line.strip_prefix('\\').unwrap_or(line)),
+            };
+            Glob::new(pattern)
+                .ok()
+                .map(|glob| IgnoreRule { glob, negated })
+        })
+        .collect()
+}
+
+/// Whether the last matching rule of the applicable `.gitignore` files
+/// ignores a path.
+fn is_ignored(ignores: &[IgnoreFile], path: &str, is_dir: bool) -> bool {
+    let mut ignored = false;
+    for file in ignores {
+        let Some(local) = path.strip_prefix(file.base.as_str()) else {
+            continue;
+        };
+        for rule in &file.rules {
+            if rule.glob.is_match(local, is_dir) {
+                ignored = !rule.negated;
+            }
+        }
+    }
+    ignored
+}
+
+/// Which files `collect_files` picks. Patterns match paths relative to the
+/// directory walked.
+#[derive(Debug, Clone)]
+pub struct WalkOptions {
+    /// When not empty, only files matching one of these are picked.
+    pub include: Vec<Glob>,
+    /// Files and directories to leave out.
+    pub exclude: Vec<Glob>,
+    /// Larger files are skipped, in bytes.
+    pub max_file_size: Option<u64>,
+    /// Whether to honour `.gitignore` files.
+    pub gitignore: bool,
+}
+
+/// The files found by `collect_files`.
+#[derive(Debug, Default)]
+pub struct FileScan {
+    pub files: Vec<PathBuf>,
+    /// Files and directories that could not be picked, with the reason.
+    pub skipped: Vec<(PathBuf, String)>,
+}
+
+fn megabytes(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// Checks a file against the size limit.
+pub fn check_file_size(path: &Path, max_file_size: Option<u64>) -> Result<()> {
+    let size = fs::metadata(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?
+        .len();
+    match max_file_size {
+        Some(max) if size > max => Err(anyhow!(
+            "File is {}, over the {} limit",
+            megabytes(size),
+            megabytes(max)
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Walks `root` recursively in name order and returns the files that pass
+/// `options`. Hidden files and directories are left out unless an `include`
+/// pattern names them.
+pub fn collect_files(root: &Path, options: &WalkOptions) -> Result<FileScan> {
+    let mut scan = FileScan::default();
+    walk(root, "", options, &mut Vec::new(), &mut scan)?;
+    Ok(scan)
+}
+
+fn walk(
+    dir: &Path,
+    relative: &str,
+    options: &WalkOptions,
+    ignores: &mut Vec<IgnoreFile>,
+    scan: &mut FileScan,
+) -> Result<()> {
+    let mut entries = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory {}", dir.display()))?
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    let gitignore = dir.join(".gitignore");
+    let has_gitignore = options.gitignore && gitignore.is_file();
+    if has_gitignore {
+        let text = fs::read_to_string(&gitignore).unwrap_or_default();
+        ignores.push(IgnoreFile {
+            base: relative.to_string(),
+            rules: parse_gitignore(&text),
+        });
+    }
+
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with('.') && !options.include.iter().any(|glob| glob.names_hidden(&name)) {
+            continue;
+        }
+        let path = entry.path();
+        // Symlinked directories are not followed, to avoid cycles.
+        let is_dir = entry.file_type().is_ok_and(|file_type| file_type.is_dir());
+        let relative_path = format!("{}{}", relative, name);
+        if is_ignored(ignores, &relative_path, is_dir)
+            || options
+                .exclude
+                .iter()
+                .any(|glob| glob.is_match(&relative_path, is_dir))
+        {
+            continue;
+        }
+
+        if is_dir {
+            let relative_dir = format!("{}/", relative_path);
+            if let Err(e) = walk(&path, &relative_dir, options, ignores, scan) {
+                scan.skipped.push((path, format!("{:#}", e)));
+            }
+            continue;
+        }
+        if !path.is_file() {
+            continue;
+        }
+        if !options.include.is_empty()
+            && !options
+                .include
+                .iter()
+                .any(|glob| glob.is_match(&relative_path, false))
+        {
+            continue;
+        }
+        match check_file_size(&path, options.max_file_size) {
+            Ok(()) => scan.files.push(path),
+            Err(e) => scan.skipped.push((path, e.to_string())),
+        }
+    }
+
+    if has_gitignore {
+        ignores.pop();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glob(pattern: &str) -> Glob {
+        Glob::new(pattern).unwrap()
+    }
+
+    fn ignored(gitignore: &str, path: &str, is_dir: bool) -> bool {
+        let ignores = [IgnoreFile {
+            base: String::new(),
+            rules: parse_gitignore(gitignore),
+        }];
+        is_ignored(&ignores, path, is_dir)
+    }
+
+    #[test]
+    fn pattern_without_slash_matches_at_any_depth() {
+        let md = glob("*.md");
+        assert!(md.is_match("README.md", false));
+        assert!(md.is_match("docs/guide/intro.md", false));
+        assert!(!md.is_match("README.mdx", false));
+    }
+
+    #[test]
+    fn pattern_with_slash_is_anchored() {
+        let docs = glob("docs/*.md");
+        assert!(docs.is_match("docs/intro.md", false));
+        assert!(!docs.is_match("docs/guide/intro.md", false));
+        assert!(!docs.is_match("site/docs/intro.md", false));
+
+        let root = glob("/build");
+        assert!(root.is_match("build", true));
+        assert!(!root.is_match("src/build", true));
+    }
+
+    #[test]
+    fn double_star_crosses_directories() {
+        let leading = glob("**/test_*.rs");
+        assert!(leading.is_match("test_a.rs", false));
+        assert!(leading.is_match("crates/core/tests/test_a.rs", false));
+
+        let middle = glob("docs/**/*.md");
+        assert!(middle.is_match("docs/intro.md", false));
+        assert!(middle.is_match("docs/a/b/intro.md", false));
+        assert!(!middle.is_match("src/docs/intro.md", false));
+
+        let trailing = glob("target/**");
+        assert!(trailing.is_match("target/debug/fluent", false));
+        assert!(!trailing.is_match("target", true));
+    }
+
+    #[test]
+    fn single_star_and_question_mark_stay_in_a_directory() {
+        assert!(!glob("src/*.rs").is_match("src/utils/files.rs", false));
+        assert!(glob("v?.txt").is_match("v1.txt", false));
+        assert!(!glob("v?.txt").is_match("v/.txt", false));
+    }
+
+    #[test]
+    fn trailing_slash_matches_only_directories() {
+        let target = glob("target/");
+        assert!(target.is_match("target", true));
+        assert!(target.is_match("crates/core/target", true));
+        assert!(!target.is_match("target", false));
+    }
+
+    #[test]
+    fn character_classes() {
+        assert!(glob("file[0-9].txt").is_match("file7.txt", false));
+        assert!(!glob("file[!0-9].txt").is_match("file7.txt", false));
+        assert!(glob("file[!0-9].txt").is_match("fileA.txt", false));
+        assert!(glob("[]]").is_match("]", false));
+        assert!(glob("a[b").is_match("a[b", false));
+        assert!(glob(r"\*.txt").is_match("*.txt", false));
+        assert!(!glob(r"\*.txt").is_match("a.txt", false));
+    }
+
+    #[test]
+    fn empty_pattern_is_an_error() {
+        assert!(Glob::new("").is_err());
+        assert!(Glob::new("/").is_err());
+    }
+
+    #[test]
+    fn gitignore_skips_comments_and_blank_lines() {
+        let rules = parse_gitignore("# build output\n\ntarget/\n  \n\\#notes\n");
+        assert_eq!(rules.len(), 2);
+        assert!(ignored("\\#notes", "#notes", false));
+    }
+
+    #[test]
+    fn negated_rule_reincludes_a_path() {
+        let gitignore = "*.log\n!keep.log\n";
+        assert!(ignored(gitignore, "debug.log", false));
+        assert!(!ignored(gitignore, "keep.log", false));
+        assert!(!ignored(gitignore, "logs/keep.log", false));
+        // The last matching rule wins.
+        assert!(ignored("!keep.log\n*.log\n", "keep.log", false));
+    }
+
+    #[test]
+    fn nested_gitignore_applies_below_its_directory() {
+        let ignores = [
+            IgnoreFile {
+                base: String::new(),
+                rules: parse_gitignore("*.tmp\n"),
+            },
+            IgnoreFile {
+                base: "web/".to_string(),
+                rules: parse_gitignore("/dist\n!important.tmp\n"),
+            },
+        ];
+        assert!(is_ignored(&ignores, "web/dist", true));
+        assert!(!is_ignored(&ignores, "dist", true));
+        assert!(is_ignored(&ignores, "cache.tmp", false));
+        assert!(!is_ignored(&ignores, "web/important.tmp", false));
+    }
+
+    #[test]
+    fn hidden_entries_are_walked_only_when_included_by_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for file in [
+            "README.md",
+            ".env",
+            ".github/workflows/ci.yml",
+            ".git/HEAD",
+            "docs/.draft.md",
+            "docs/guide.md",
+        ] {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "text").unwrap();
+        }
+        let files = |include: &[&str]| {
+            let options = WalkOptions {
+                include: include.iter().map(|pattern| glob(pattern)).collect(),
+                exclude: Vec::new(),
+                max_file_size: None,
+                gitignore: true,
+            };
+            collect_files(root, &options)
+                .unwrap()
+                .files
+                .iter()
+                .map(|path| {
+                    path.strip_prefix(root)
+                        .unwrap()
+                        .to_string_lossy()
+                        .replace('\\', "/")
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(files(&[]), ["README.md", "docs/guide.md"]);
+        assert_eq!(files(&["**/*.md"]), ["README.md", "docs/guide.md"]);
+        assert_eq!(files(&[".github/**"]), [".github/workflows/ci.yml"]);
+        assert_eq!(
+            files(&[".env", "*.md"]),
+            [".env", "README.md", "docs/guide.md"]
+        );
+        assert_eq!(files(&["docs/.*.md"]), ["docs/.draft.md"]);
+    }
+
+    #[test]
+    fn walk_honours_gitignore_and_exclude() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for file in [
+            "keep.rs",
+            "debug.log",
+            "target/out.rs",
+            "src/lib.rs",
+            "src/gen.rs",
+        ] {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "text").unwrap();
+        }
+        fs::write(root.join(".gitignore"), "*.log\ntarget/\n").unwrap();
+        fs::write(root.join("src/.gitignore"), "gen.rs\n").unwrap();
+
+        let mut options = WalkOptions {
+            include: Vec::new(),
+            exclude: vec![glob("keep.rs")],
+            max_file_size: None,
+            gitignore: true,
+        };
+        let scan = collect_files(root, &options).unwrap();
+        assert_eq!(scan.files, [root.join("src/lib.rs")]);
+
+        options.gitignore = false;
+        let scan = collect_files(root, &options).unwrap();
+        assert_eq!(scan.files.len(), 4);
+    }
+}
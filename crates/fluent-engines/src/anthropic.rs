@@ -1,9 +1,10 @@
 use std::future::Future;
 use std::path::Path;
 use std::sync::Arc;
-use fluent_core::types::{ExtractedContent, Request, Response, UpsertRequest, UpsertResponse, Usage};
-use fluent_core::traits::{AnthropicConfigProcessor, Engine, EngineConfigProcessor};
+use fluent_core::types::{ExtractedContent, Request, Response, ResponseChunk, UpsertRequest, UpsertResponse, Usage};
+use fluent_core::traits::{AnthropicConfigProcessor, Engine, EngineConfigProcessor, ResponseStream};
 use fluent_core::config::EngineConfig;
+use fluent_core::error::{EngineError, RequestBuilderExt};
 use anyhow::{Result, anyhow, Context};
 use reqwest::Client;
 use serde_json::{json, Value};
@@ -14,6 +15,9 @@ use tokio::io::AsyncReadExt;
 use fluent_core::neo4j_client::Neo4jClient;
 use base64::Engine as Base64Engine;
 use base64::engine::general_purpose::STANDARD as Base64;
+use futures::StreamExt;
+
+use crate::streaming::sse_json_events;
 
 
 pub struct AnthropicEngine {
@@ -38,6 +42,62 @@ impl AnthropicEngine {
             neo4j_client,
         })
     }
+
+    fn build_payload(&self, request: &Request) -> Result<Value> {
+        let mut payload = self.config_processor.process_config(&self.config)?;
+
+        // Add the conversation, ending with the user's request, to the messages
+        AnthropicConfigProcessor::apply_messages(&mut payload, &request.conversation());
+        if !request.tools.is_empty() {
+            payload["tools"] = AnthropicConfigProcessor::map_tools(&request.tools);
+        }
+        Ok(payload)
+    }
+
+    fn url(&self) -> String {
+        format!("{}://{}:{}{}",
+                self.config.connection.protocol,
+                self.config.connection.hostname,
+                self.config.connection.port,
+                self.config.connection.request_path
+        )
+    }
+
+    fn auth_token(&self) -> Result<&str> {
+        self.config.parameters.get("bearer_token")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow!("Bearer token not found in configuration"))
+    }
+}
+
+/// Maps an Anthropic message stream event into a `ResponseChunk`.
+fn anthropic_chunk(event: &Value) -> Result<ResponseChunk> {
+    let mut chunk = ResponseChunk::default();
+    match event["type"].as_str() {
+        Some("message_start") => {
+            let message = &event["message"];
+            chunk.model = message["model"].as_str().map(String::from);
+            chunk.usage = Some(Usage {
+                prompt_tokens: message["usage"]["input_tokens"].as_u64().unwrap_or(0) as u32,
+                completion_tokens: message["usage"]["output_tokens"].as_u64().unwrap_or(0) as u32,
+                total_tokens: 0,
+            });
+        }
+        Some("content_block_delta") => {
+            chunk.delta = event["delta"]["text"].as_str().unwrap_or_default().to_string();
+        }
+        Some("message_delta") => {
+            chunk.finish_reason = event["delta"]["stop_reason"].as_str().map(String::from);
+            chunk.usage = Some(Usage {
+                prompt_tokens: 0,
+                completion_tokens: event["usage"]["output_tokens"].as_u64().unwrap_or(0) as u32,
+                total_tokens: 0,
+            });
+        }
+        Some("error") => return Err(EngineError::from_error_body("Anthropic", event).into()),
+        _ => {}
+    }
+    Ok(chunk)
 }
 
 impl Engine for AnthropicEngine {
@@ -101,41 +161,23 @@ impl Engine for AnthropicEngine {
             let client = Client::new();
             debug!("Config: {:?}", self.config);
 
-            let mut payload = self.config_processor.process_config(&self.config)?;
-
-            // Add the user's request to the messages
-            payload["messages"][0]["content"] = json!(request.payload);
+            let payload = self.build_payload(request)?;
+            let url = self.url();
+            let auth_token = self.auth_token()?;
 
-            let url = format!("{}://{}:{}{}",
-                              self.config.connection.protocol,
-                              self.config.connection.hostname,
-                              self.config.connection.port,
-                              self.config.connection.request_path
-            );
-
-            let auth_token = self.config.parameters.get("bearer_token")
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| anyhow!("Bearer token not found in configuration"))?;
-
-            let res = client.post(&url)
+            let response_body = client.post(&url)
                 .header("x-api-key", auth_token)
                 .header("anthropic-version", "2023-06-01")
                 .header("content-type", "application/json")
                 .json(&payload)
-                .send()
+                .send_json("Anthropic")
                 .await?;
-
-            let response_body = res.json::<serde_json::Value>().await?;
             debug!("Response: {:?}", response_body);
 
-            if let Some(error) = response_body.get("error") {
-                return Err(anyhow!("Anthropic API error: {:?}", error));
+            if !response_body["content"].is_array() {
+                return Err(EngineError::malformed("Anthropic", "missing content blocks").into());
             }
-
-            let content = response_body["content"][0]["text"]
-                .as_str()
-                .ok_or_else(|| anyhow!("Failed to extract content from Anthropic response"))?
-                .to_string();
+            let (content, tool_calls) = AnthropicConfigProcessor::parse_content(&response_body);
 
             let usage = Usage {
                 prompt_tokens: response_body["usage"]["input_tokens"].as_u64().unwrap_or(0) as u32,
@@ -152,6 +194,8 @@ impl Engine for AnthropicEngine {
                 usage,
                 model,
                 finish_reason,
+                tool_calls,
+                ..Default::default()
             })
         })
     }
@@ -213,26 +257,20 @@ impl Engine for AnthropicEngine {
                 .and_then(|v| v.as_str())
                 .ok_or_else(|| anyhow!("Bearer token not found in configuration"))?;
 
-            let response = client.post(&url)
+            let response_body = client.post(&url)
                 .header("x-api-key", auth_token)
                 .header("anthropic-version", "2023-06-01")
                 .header("Content-Type", "application/json")
                 .json(&payload)
-                .send()
+                .send_json("Anthropic")
                 .await?;
 
-            let response_body = response.json::<serde_json::Value>().await?;
-
             // Debug print the response
             debug!("Anthropic Response: {:?}", response_body);
 
-            if let Some(error) = response_body.get("error") {
-                return Err(anyhow!("Anthropic API error: {:?}", error));
-            }
-
             let content = response_body["content"][0]["text"]
                 .as_str()
-                .ok_or_else(|| anyhow!("Failed to extract content from Anthropic response"))?
+                .ok_or_else(|| EngineError::malformed("Anthropic", "missing text content"))?
                 .to_string();
 
             let usage = Usage {
@@ -250,12 +288,29 @@ impl Engine for AnthropicEngine {
                 usage,
                 model,
                 finish_reason,
+                ..Default::default()
             })
         })
     }
-}
-
 
+    fn execute_stream<'a>(&'a self, request: &'a Request) -> Box<dyn Future<Output = Result<ResponseStream<'a>>> + Send + 'a> {
+        Box::new(async move {
+            let client = Client::new();
 
+            let mut payload = self.build_payload(request)?;
+            payload["stream"] = json!(true);
+            debug!("Anthropic Streaming Payload: {:#?}", payload);
 
+            let res = client.post(self.url())
+                .header("x-api-key", self.auth_token()?)
+                .header("anthropic-version", "2023-06-01")
+                .header("content-type", "application/json")
+                .json(&payload)
+                .send_checked("Anthropic")
+                .await?;
 
+            let stream = sse_json_events(res).map(|event| event.and_then(|value| anthropic_chunk(&value)));
+            Ok(Box::pin(stream) as ResponseStream<'a>)
+        })
+    }
+}
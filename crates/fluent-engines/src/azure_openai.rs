@@ -0,0 +1,327 @@
+// crates/fluent-engines/src/azure_openai.rs
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use fluent_core::config::EngineConfig;
+use fluent_core::error::{read_json, EngineError};
+use fluent_core::neo4j_client::Neo4jClient;
+use fluent_core::traits::{Engine, EngineConfigProcessor, OpenAIConfigProcessor, ResponseStream};
+use fluent_core::types::{
+    ExtractedContent, Request, Response, ResponseChunk, UpsertRequest, UpsertResponse, Usage,
+};
+use futures::StreamExt;
+use log::debug;
+use reqwest::multipart::{Form, Part};
+use reqwest::{Client, RequestBuilder};
+use serde_json::{json, Value};
+use tokio::fs;
+
+use crate::openai_compatible::{image_messages, read_image};
+use crate::streaming::{openai_chunk, sse_json_events};
+
+const PROVIDER: &str = "Azure OpenAI";
+const DEFAULT_API_VERSION: &str = "2024-10-21";
+
+/// Azure OpenAI Service. Requests go to a deployment rather than a model:
+/// `/openai/deployments/{deployment}/chat/completions?api-version=...` on the
+/// resource host, authenticated with an `api-key` header.
+pub struct AzureOpenAIEngine {
+    config: EngineConfig,
+    config_processor: OpenAIConfigProcessor,
+    client: Client,
+    neo4j_client: Option<Arc<Neo4jClient>>,
+}
+
+/// Lists the categories Azure's content filter flagged, e.g. `hate (high)`,
+/// from a `content_filter_results` or `content_filter_result` object.
+fn filtered_categories(results: &Value) -> Vec<String> {
+    results
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(_, result)| result["filtered"].as_bool().unwrap_or(false))
+        .map(|(category, result)| match result["severity"].as_str() {
+            Some(severity) => format!("{} ({})", category, severity),
+            None => category.clone(),
+        })
+        .collect()
+}
+
+fn describe_filter(message: &str, results: &Value) -> String {
+    let categories = filtered_categories(results);
+    if categories.is_empty() {
+        message.to_string()
+    } else {
+        format!("{} [{}]", message, categories.join(", "))
+    }
+}
+
+/// Classifies an Azure error body. Content filter errors come back as a 400
+/// with `code: content_filter` and the flagged categories under `innererror`.
+fn azure_error(status: reqwest::StatusCode, headers: &reqwest::header::HeaderMap, body: &str) -> EngineError {
+    let value: Value = serde_json::from_str(body).unwrap_or_default();
+    let error = &value["error"];
+    if error["code"].as_str() == Some("content_filter") {
+        let message = error["message"].as_str().unwrap_or("content filtered");
+        return EngineError::content_filtered(
+            PROVIDER,
+            describe_filter(message, &error["innererror"]["content_filter_result"]),
+        );
+    }
+    EngineError::from_response_parts(PROVIDER, status, headers, body)
+}
+
+/// A response whose choice was stopped by the content filter, which Azure
+/// reports with a success status.
+fn filtered_choice(choice: &Value) -> Option<EngineError> {
+    (choice["finish_reason"].as_str() == Some("content_filter")).then(|| {
+        EngineError::content_filtered(
+            PROVIDER,
+            describe_filter("response filtered", &choice["content_filter_results"]),
+        )
+    })
+}
+
+fn azure_chunk(value: &Value) -> Result<ResponseChunk> {
+    if value.get("error").is_some_and(|error| !error.is_null()) {
+        return Err(EngineError::from_error_body(PROVIDER, value).into());
+    }
+    if let Some(error) = filtered_choice(&value["choices"][0]) {
+        return Err(error.into());
+    }
+    Ok(openai_chunk(value))
+}
+
+impl AzureOpenAIEngine {
+    pub async fn new(mut config: EngineConfig) -> Result<Self> {
+        // The payload builder needs a model name, which Azure ignores.
+        if let Some(deployment) = config.parameters.get("deployment").cloned() {
+            config.parameters.entry("modelName".to_string()).or_insert(deployment);
+        }
+
+        let neo4j_client = if let Some(neo4j_config) = &config.neo4j {
+            Some(Arc::new(Neo4jClient::new(neo4j_config).await?))
+        } else {
+            None
+        };
+
+        Ok(Self {
+            config,
+            config_processor: OpenAIConfigProcessor,
+            client: Client::new(),
+            neo4j_client,
+        })
+    }
+
+    fn parameter(&self, name: &str) -> Option<&str> {
+        self.config.parameters.get(name).and_then(|v| v.as_str())
+    }
+
+    /// The deployment name, which defaults to `modelName` since deployments
+    /// are often named after their model.
+    fn deployment(&self) -> Result<&str> {
+        self.parameter("deployment")
+            .or_else(|| self.parameter("modelName"))
+            .ok_or_else(|| anyhow!("Azure OpenAI deployment not specified"))
+    }
+
+    fn url(&self, path: &str) -> String {
+        let connection = &self.config.connection;
+        format!(
+            "{}://{}:{}/openai{}",
+            connection.protocol, connection.hostname, connection.port, path
+        )
+    }
+
+    /// A POST with Azure auth and the `api-version` query parameter.
+    /// `api_key` is sent as the `api-key` header; a `bearer_token` (a
+    /// Microsoft Entra ID access token) is used instead when there is no key.
+    fn post(&self, path: &str) -> Result<RequestBuilder> {
+        let api_version = self.parameter("api_version").unwrap_or(DEFAULT_API_VERSION);
+        let builder = self
+            .client
+            .post(self.url(path))
+            .query(&[("api-version", api_version)]);
+
+        match (self.parameter("api_key"), self.parameter("bearer_token")) {
+            (Some(key), _) => Ok(builder.header("api-key", key)),
+            (None, Some(token)) => Ok(builder.bearer_auth(token)),
+            (None, None) => Err(anyhow!("Azure OpenAI api_key not found in configuration")),
+        }
+    }
+
+    fn chat_completions(&self) -> Result<RequestBuilder> {
+        Ok(self
+            .post(&format!("/deployments/{}/chat/completions", self.deployment()?))?
+            .header("Content-Type", "application/json"))
+    }
+
+    async fn send(&self, builder: RequestBuilder) -> Result<reqwest::Response, EngineError> {
+        let response = builder
+            .send()
+            .await
+            .map_err(|e| EngineError::transport(PROVIDER, e))?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let headers = response.headers().clone();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| EngineError::transport(PROVIDER, e))?;
+        debug!("{} error response ({}): {}", PROVIDER, status, body);
+        Err(azure_error(status, &headers, &body))
+    }
+
+    fn build_payload(&self, messages: Value, request: &Request) -> Result<Value> {
+        let mut payload = self.config_processor.process_config(&self.config)?;
+        // The deployment decides the model.
+        if let Some(payload) = payload.as_object_mut() {
+            payload.remove("model");
+        }
+        payload["messages"] = messages;
+        if !request.tools.is_empty() {
+            payload["tools"] = OpenAIConfigProcessor::map_tools(&request.tools);
+        }
+        if let Some(format) =
+            OpenAIConfigProcessor::response_format(request.response_schema.as_ref())
+        {
+            payload["response_format"] = format;
+        }
+        debug!("Azure OpenAI Payload: {:#?}", payload);
+        Ok(payload)
+    }
+
+    async fn complete(&self, payload: &Value) -> Result<Response> {
+        let response = self.send(self.chat_completions()?.json(payload)).await?;
+        let body = read_json(PROVIDER, response).await?;
+        debug!("Azure OpenAI Response: {:?}", body);
+
+        let choice = &body["choices"][0];
+        if let Some(error) = filtered_choice(choice) {
+            return Err(error.into());
+        }
+        let message = &choice["message"];
+        let tool_calls = OpenAIConfigProcessor::parse_tool_calls(message);
+        let content = match message["content"].as_str() {
+            Some(content) => content.to_string(),
+            None if !tool_calls.is_empty() => String::new(),
+            None => return Err(EngineError::malformed(PROVIDER, "missing message content").into()),
+        };
+
+        Ok(Response {
+            content,
+            usage: Usage {
+                prompt_tokens: body["usage"]["prompt_tokens"].as_u64().unwrap_or(0) as u32,
+                completion_tokens: body["usage"]["completion_tokens"].as_u64().unwrap_or(0) as u32,
+                total_tokens: body["usage"]["total_tokens"].as_u64().unwrap_or(0) as u32,
+            },
+            model: body["model"]
+                .as_str()
+                .map_or_else(|| self.deployment().unwrap_or("unknown").to_string(), String::from),
+            finish_reason: choice["finish_reason"].as_str().map(String::from),
+            tool_calls,
+            ..Default::default()
+        })
+    }
+}
+
+impl Engine for AzureOpenAIEngine {
+    fn execute<'a>(
+        &'a self,
+        request: &'a Request,
+    ) -> Box<dyn Future<Output = Result<Response>> + Send + 'a> {
+        Box::new(async move {
+            let messages = OpenAIConfigProcessor::map_messages(&request.conversation());
+            self.complete(&self.build_payload(messages, request)?).await
+        })
+    }
+
+    fn upsert<'a>(
+        &'a self,
+        _request: &'a UpsertRequest,
+    ) -> Box<dyn Future<Output = Result<UpsertResponse>> + Send + 'a> {
+        Box::new(async move {
+            Ok(UpsertResponse {
+                processed_files: vec![],
+                errors: vec![],
+            })
+        })
+    }
+
+    fn get_neo4j_client(&self) -> Option<&Arc<Neo4jClient>> {
+        self.neo4j_client.as_ref()
+    }
+
+    fn get_session_id(&self) -> Option<String> {
+        self.parameter("sessionID").map(String::from)
+    }
+
+    fn extract_content(&self, value: &Value) -> Option<ExtractedContent> {
+        value["choices"][0]["message"]["content"]
+            .as_str()
+            .map(|content| ExtractedContent {
+                main_content: content.to_string(),
+                sentiment: None,
+                clusters: None,
+                themes: None,
+                keywords: None,
+            })
+    }
+
+    fn upload_file<'a>(
+        &'a self,
+        file_path: &'a Path,
+    ) -> Box<dyn Future<Output = Result<String>> + Send + 'a> {
+        Box::new(async move {
+            let file_name = file_path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .ok_or_else(|| anyhow!("Invalid file name"))?
+                .to_string();
+            let bytes = fs::read(file_path).await.context("Failed to read file")?;
+            let form = Form::new()
+                .part("file", Part::bytes(bytes).file_name(file_name))
+                .text("purpose", "assistants");
+
+            let response = self.send(self.post("/files")?.multipart(form)).await?;
+            let body = read_json(PROVIDER, response).await?;
+            body["id"]
+                .as_str()
+                .map(String::from)
+                .ok_or_else(|| EngineError::malformed(PROVIDER, "missing file ID").into())
+        })
+    }
+
+    fn process_request_with_file<'a>(
+        &'a self,
+        request: &'a Request,
+        file_path: &'a Path,
+    ) -> Box<dyn Future<Output = Result<Response>> + Send + 'a> {
+        Box::new(async move {
+            let (image, mime) = read_image(file_path).await?;
+            let messages = image_messages(request, &image, &mime);
+            self.complete(&self.build_payload(messages, request)?).await
+        })
+    }
+
+    fn execute_stream<'a>(
+        &'a self,
+        request: &'a Request,
+    ) -> Box<dyn Future<Output = Result<ResponseStream<'a>>> + Send + 'a> {
+        Box::new(async move {
+            let messages = OpenAIConfigProcessor::map_messages(&request.conversation());
+            let mut payload = self.build_payload(messages, request)?;
+            payload["stream"] = json!(true);
+            payload["stream_options"] = json!({ "include_usage": true });
+
+            let response = self.send(self.chat_completions()?.json(&payload)).await?;
+            let stream =
+                sse_json_events(response).map(|event| event.and_then(|value| azure_chunk(&value)));
+            Ok(Box::pin(stream) as ResponseStream<'a>)
+        })
+    }
+}
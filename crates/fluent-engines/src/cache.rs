@@ -0,0 +1,311 @@
+// crates/fluent-engines/src/cache.rs
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use fluent_core::config::{CacheConfig, EngineConfig};
+use fluent_core::neo4j_client::Neo4jClient;
+use fluent_core::traits::{Engine, ResponseStream};
+use fluent_core::types::{ExtractedContent, Request, Response, UpsertRequest, UpsertResponse};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    engine: String,
+    created_at: u64,
+    expires_at: u64,
+    response: Response,
+}
+
+/// Counts reported by `fluent cache stats`.
+#[derive(Debug, Default, Clone)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub expired: usize,
+    pub total_bytes: u64,
+}
+
+/// Responses stored as one JSON file per key. A file's modification time is
+/// bumped on every hit and serves as its last-use time for LRU eviction.
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    directory: PathBuf,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Parameters that hold credentials or session bookkeeping rather than
+/// anything that changes the response.
+fn is_secret(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name == "sessionid"
+        || name.ends_with("key")
+        || name.ends_with("_token")
+        || name.contains("secret")
+        || name.contains("password")
+}
+
+impl ResponseCache {
+    pub fn new(directory: PathBuf) -> Self {
+        Self { directory }
+    }
+
+    /// `FLUENT_CACHE_DIR`, otherwise `fluent` under the user's cache directory.
+    pub fn default_directory() -> PathBuf {
+        if let Some(dir) = std::env::var_os("FLUENT_CACHE_DIR") {
+            return PathBuf::from(dir);
+        }
+        if let Some(dir) = std::env::var_os("XDG_CACHE_HOME") {
+            return PathBuf::from(dir).join("fluent");
+        }
+        match std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")) {
+            Some(home) => PathBuf::from(home).join(".cache").join("fluent"),
+            None => PathBuf::from(".fluent_cache"),
+        }
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// A hash of the engine type, the server it connects to, its parameters
+    /// without secrets, any fallback members, and the request.
+    pub fn key(engine_config: &EngineConfig, request: &Request) -> Result<String> {
+        let parameters: BTreeMap<&String, &Value> = engine_config
+            .parameters
+            .iter()
+            .filter(|(name, _)| !is_secret(name))
+            .collect();
+        let material = json!({
+            "engine": engine_config.engine,
+            "connection": engine_config.connection,
+            "parameters": parameters,
+            "fallback": engine_config.fallback.as_ref().map(|f| &f.engines),
+            "request": request,
+        });
+        let digest = Sha256::digest(serde_json::to_vec(&material)?);
+        Ok(format!("{:x}", digest))
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.directory.join(format!("{}.json", key))
+    }
+
+    fn entries(&self) -> Result<Vec<(PathBuf, fs::Metadata)>> {
+        let read_dir = match fs::read_dir(&self.directory) {
+            Ok(read_dir) => read_dir,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut entries = Vec::new();
+        for entry in read_dir {
+            let entry = entry?;
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                entries.push((path, entry.metadata()?));
+            }
+        }
+        Ok(entries)
+    }
+
+    fn read_entry(path: &Path) -> Option<CacheEntry> {
+        serde_json::from_slice(&fs::read(path).ok()?).ok()
+    }
+
+    /// Returns the cached response, dropping it if it has expired.
+    pub fn get(&self, key: &str) -> Option<Response> {
+        let path = self.path(key);
+        let entry = Self::read_entry(&path)?;
+        if entry.expires_at <= now() {
+            let _ = fs::remove_file(&path);
+            return None;
+        }
+        if let Err(e) = File::options()
+            .append(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(SystemTime::now()))
+        {
+            debug!("Failed to update cache entry access time: {}", e);
+        }
+        Some(entry.response)
+    }
+
+    /// Stores a response, then evicts the least recently used entries until
+    /// the directory fits in `max_bytes`.
+    pub fn put(
+        &self,
+        key: &str,
+        engine: &str,
+        response: &Response,
+        ttl: Duration,
+        max_bytes: u64,
+    ) -> Result<()> {
+        fs::create_dir_all(&self.directory).with_context(|| {
+            format!("Failed to create cache directory {}", self.directory.display())
+        })?;
+        let created_at = now();
+        let entry = CacheEntry {
+            engine: engine.to_string(),
+            created_at,
+            expires_at: created_at.saturating_add(ttl.as_secs()),
+            response: response.clone(),
+        };
+        // Write then rename so concurrent readers never see a partial file.
+        let path = self.path(key);
+        let temp = path.with_extension(format!("tmp{}", std::process::id()));
+        fs::write(&temp, serde_json::to_vec(&entry)?)?;
+        fs::rename(&temp, &path)?;
+        self.evict(max_bytes)
+    }
+
+    fn evict(&self, max_bytes: u64) -> Result<()> {
+        let mut entries = self.entries()?;
+        let mut total: u64 = entries.iter().map(|(_, meta)| meta.len()).sum();
+        if total <= max_bytes {
+            return Ok(());
+        }
+        entries.sort_by_key(|(_, meta)| meta.modified().unwrap_or(UNIX_EPOCH));
+        for (path, meta) in entries {
+            if total <= max_bytes {
+                break;
+            }
+            debug!("Evicting cache entry {}", path.display());
+            fs::remove_file(&path)?;
+            total = total.saturating_sub(meta.len());
+        }
+        Ok(())
+    }
+
+    pub fn stats(&self) -> Result<CacheStats> {
+        let now = now();
+        let mut stats = CacheStats::default();
+        for (path, meta) in self.entries()? {
+            stats.entries += 1;
+            stats.total_bytes += meta.len();
+            if Self::read_entry(&path).is_none_or(|entry| entry.expires_at <= now) {
+                stats.expired += 1;
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Deletes every entry, or only expired and unreadable ones, and returns
+    /// how many were removed.
+    pub fn clear(&self, expired_only: bool) -> Result<usize> {
+        let now = now();
+        let mut removed = 0;
+        for (path, _) in self.entries()? {
+            if expired_only
+                && Self::read_entry(&path).is_some_and(|entry| entry.expires_at > now)
+            {
+                continue;
+            }
+            fs::remove_file(&path)?;
+            removed += 1;
+        }
+        Ok(removed)
+    }
+}
+
+/// Wraps an engine so `execute` answers from the on-disk cache when the same
+/// request was sent to the same engine settings before.
+pub struct CachedEngine {
+    inner: Box<dyn Engine>,
+    cache: ResponseCache,
+    config: CacheConfig,
+    engine_config: EngineConfig,
+}
+
+impl CachedEngine {
+    pub fn new(inner: Box<dyn Engine>, engine_config: &EngineConfig, config: CacheConfig) -> Self {
+        Self {
+            inner,
+            cache: ResponseCache::new(ResponseCache::default_directory()),
+            config,
+            engine_config: engine_config.clone(),
+        }
+    }
+}
+
+impl Engine for CachedEngine {
+    fn execute<'a>(
+        &'a self,
+        request: &'a Request,
+    ) -> Box<dyn Future<Output = Result<Response>> + Send + 'a> {
+        Box::new(async move {
+            let key = ResponseCache::key(&self.engine_config, request)?;
+            if let Some(mut response) = self.cache.get(&key) {
+                debug!("Cache hit for {}", key);
+                // The provider is not paid again for a cached answer.
+                response.cost = Some(0.0);
+                return Ok(response);
+            }
+
+            let response = Pin::from(self.inner.execute(request)).await?;
+            if let Err(e) = self.cache.put(
+                &key,
+                &self.engine_config.name,
+                &response,
+                Duration::from_secs(self.config.ttl_seconds),
+                self.config.max_size_mb.saturating_mul(1024 * 1024),
+            ) {
+                warn!("Failed to write response cache: {}", e);
+            }
+            Ok(response)
+        })
+    }
+
+    fn upsert<'a>(
+        &'a self,
+        request: &'a UpsertRequest,
+    ) -> Box<dyn Future<Output = Result<UpsertResponse>> + Send + 'a> {
+        self.inner.upsert(request)
+    }
+
+    fn get_neo4j_client(&self) -> Option<&Arc<Neo4jClient>> {
+        self.inner.get_neo4j_client()
+    }
+
+    fn get_session_id(&self) -> Option<String> {
+        self.inner.get_session_id()
+    }
+
+    fn extract_content(&self, value: &Value) -> Option<ExtractedContent> {
+        self.inner.extract_content(value)
+    }
+
+    fn upload_file<'a>(
+        &'a self,
+        file_path: &'a Path,
+    ) -> Box<dyn Future<Output = Result<String>> + Send + 'a> {
+        self.inner.upload_file(file_path)
+    }
+
+    fn process_request_with_file<'a>(
+        &'a self,
+        request: &'a Request,
+        file_path: &'a Path,
+    ) -> Box<dyn Future<Output = Result<Response>> + Send + 'a> {
+        self.inner.process_request_with_file(request, file_path)
+    }
+
+    fn execute_stream<'a>(
+        &'a self,
+        request: &'a Request,
+    ) -> Box<dyn Future<Output = Result<ResponseStream<'a>>> + Send + 'a> {
+        self.inner.execute_stream(request)
+    }
+}
@@ -2,11 +2,14 @@ use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
 use base64::{engine::general_purpose::STANDARD, Engine as _};
 use fluent_core::config::EngineConfig;
+use fluent_core::error::{EngineError, RequestBuilderExt};
 use fluent_core::neo4j_client::Neo4jClient;
-use fluent_core::traits::Engine;
+use fluent_core::traits::{Engine, ResponseStream};
 use fluent_core::types::{
-    ExtractedContent, Request, Response, UpsertRequest, UpsertResponse, Usage,
+    ExtractedContent, Request, Response, ResponseChunk, Role, UpsertRequest, UpsertResponse,
+    Usage,
 };
+use futures::StreamExt;
 use log::debug;
 use reqwest::Client;
 use serde_json::{json, Value};
@@ -17,6 +20,8 @@ use std::sync::Arc;
 use tokio::fs::File;
 use tokio::io::AsyncReadExt;
 
+use crate::streaming::json_object_events;
+
 pub struct CohereEngine {
     config: EngineConfig,
     client: Client,
@@ -37,6 +42,110 @@ impl CohereEngine {
             neo4j_client,
         })
     }
+
+    fn url(&self) -> String {
+        format!(
+            "{}://{}:{}{}",
+            self.config.connection.protocol,
+            self.config.connection.hostname,
+            self.config.connection.port,
+            self.config.connection.request_path
+        )
+    }
+
+    fn build_payload(&self, request: &Request) -> Value {
+        // Cohere takes the latest user turn as `message` and the turns before it as
+        // `chat_history`; system turns are appended to the preamble.
+        let mut conversation = request.conversation();
+        let message = match conversation.last() {
+            Some(last) if last.role == Role::User => conversation.pop().map(|m| m.content),
+            _ => None,
+        }
+        .unwrap_or_default();
+
+        let preamble: Vec<String> = self
+            .config
+            .parameters
+            .get("preamble")
+            .and_then(|v| v.as_str())
+            .map(String::from)
+            .into_iter()
+            .chain(
+                conversation
+                    .iter()
+                    .filter(|m| m.role == Role::System)
+                    .map(|m| m.content.clone()),
+            )
+            .collect();
+
+        let history: Vec<Value> = conversation
+            .iter()
+            .filter(|m| m.role != Role::System)
+            .map(|m| {
+                json!({
+                    "role": if m.role == Role::Assistant { "CHATBOT" } else { "USER" },
+                    "message": m.content
+                })
+            })
+            .collect();
+        let chat_history = if history.is_empty() {
+            self.config.parameters.get("chat_history").cloned()
+        } else {
+            Some(Value::Array(history))
+        };
+
+        json!({
+            "message": message,
+            "model": self.config.parameters.get("modelName").and_then(|v| v.as_str()).unwrap_or("command-r-plus"),
+            "stream": self.config.parameters.get("stream").and_then(|v| v.as_bool()).unwrap_or(false),
+            "preamble": if preamble.is_empty() { None } else { Some(preamble.join("\n\n")) },
+            "chat_history": chat_history,
+            "conversation_id": self.config.parameters.get("conversation_id").and_then(|v| v.as_str()),
+            "prompt_truncation": self.config.parameters.get("prompt_truncation").and_then(|v| v.as_str()).unwrap_or("AUTO"),
+            "connectors": self.config.parameters.get("connectors"),
+            "documents": self.config.parameters.get("documents"),
+            "citation_quality": self.config.parameters.get("citation_quality").and_then(|v| v.as_str()).unwrap_or("accurate"),
+            "temperature": self.config.parameters.get("temperature").and_then(|v| v.as_f64()).unwrap_or(0.3),
+            "max_tokens": self.config.parameters.get("max_tokens").and_then(|v| v.as_u64()),
+            "k": self.config.parameters.get("k").and_then(|v| v.as_u64()).unwrap_or(0),
+            "p": self.config.parameters.get("p").and_then(|v| v.as_f64()).unwrap_or(0.75),
+            "frequency_penalty": self.config.parameters.get("frequency_penalty").and_then(|v| v.as_f64()).unwrap_or(0.0),
+            "presence_penalty": self.config.parameters.get("presence_penalty").and_then(|v| v.as_f64()).unwrap_or(0.0),
+            "tools": self.config.parameters.get("tools"),
+            "tool_results": self.config.parameters.get("tool_results"),
+        })
+    }
+}
+
+/// Maps a Cohere chat stream event into a `ResponseChunk`.
+fn cohere_chunk(event: &Value) -> Result<ResponseChunk> {
+    let mut chunk = ResponseChunk::default();
+    match event["event_type"].as_str() {
+        Some("text-generation") => {
+            chunk.delta = event["text"].as_str().unwrap_or_default().to_string();
+        }
+        Some("stream-end") => {
+            match event["finish_reason"].as_str() {
+                Some("ERROR") => return Err(EngineError::from_error_body("Cohere", &event["response"]).into()),
+                Some("ERROR_TOXIC") => {
+                    return Err(EngineError::content_filtered("Cohere", "generation stopped as toxic").into())
+                }
+                _ => {}
+            }
+            let billed_units = &event["response"]["meta"]["billed_units"];
+            let input = billed_units["input_tokens"].as_u64().unwrap_or(0) as u32;
+            let output = billed_units["output_tokens"].as_u64().unwrap_or(0) as u32;
+            chunk.usage = Some(Usage {
+                prompt_tokens: input,
+                completion_tokens: output,
+                total_tokens: input + output,
+            });
+            chunk.model = Some("cohere".to_string());
+            chunk.finish_reason = event["finish_reason"].as_str().map(String::from);
+        }
+        _ => {}
+    }
+    Ok(chunk)
 }
 
 #[async_trait]
@@ -46,34 +155,8 @@ impl Engine for CohereEngine {
         request: &'a Request,
     ) -> Box<dyn Future<Output = Result<Response>> + Send + 'a> {
         Box::new(async move {
-            let url = format!(
-                "{}://{}:{}{}",
-                self.config.connection.protocol,
-                self.config.connection.hostname,
-                self.config.connection.port,
-                self.config.connection.request_path
-            );
-
-            let payload = json!({
-                "message": &request.payload,
-                "model": self.config.parameters.get("modelName").and_then(|v| v.as_str()).unwrap_or("command-r-plus"),
-                "stream": self.config.parameters.get("stream").and_then(|v| v.as_bool()).unwrap_or(false),
-                "preamble": self.config.parameters.get("preamble").and_then(|v| v.as_str()),
-                "chat_history": self.config.parameters.get("chat_history"),
-                "conversation_id": self.config.parameters.get("conversation_id").and_then(|v| v.as_str()),
-                "prompt_truncation": self.config.parameters.get("prompt_truncation").and_then(|v| v.as_str()).unwrap_or("AUTO"),
-                "connectors": self.config.parameters.get("connectors"),
-                "documents": self.config.parameters.get("documents"),
-                "citation_quality": self.config.parameters.get("citation_quality").and_then(|v| v.as_str()).unwrap_or("accurate"),
-                "temperature": self.config.parameters.get("temperature").and_then(|v| v.as_f64()).unwrap_or(0.3),
-                "max_tokens": self.config.parameters.get("max_tokens").and_then(|v| v.as_u64()),
-                "k": self.config.parameters.get("k").and_then(|v| v.as_u64()).unwrap_or(0),
-                "p": self.config.parameters.get("p").and_then(|v| v.as_f64()).unwrap_or(0.75),
-                "frequency_penalty": self.config.parameters.get("frequency_penalty").and_then(|v| v.as_f64()).unwrap_or(0.0),
-                "presence_penalty": self.config.parameters.get("presence_penalty").and_then(|v| v.as_f64()).unwrap_or(0.0),
-                "tools": self.config.parameters.get("tools"),
-                "tool_results": self.config.parameters.get("tool_results"),
-            });
+            let url = self.url();
+            let payload = self.build_payload(request);
 
             debug!("Cohere Payload: {:?}", payload);
 
@@ -90,20 +173,14 @@ impl Engine for CohereEngine {
                 .header("Authorization", format!("Bearer {}", auth_token))
                 .header("Content-Type", "application/json")
                 .json(&payload)
-                .send()
-                .await?
-                .json::<Value>()
+                .send_json("Cohere")
                 .await?;
 
             debug!("Cohere Response: {:?}", response);
 
-            if let Some(error) = response.get("error") {
-                return Err(anyhow!("Cohere API error: {:?}", error));
-            }
-
             let content = response["text"]
                 .as_str()
-                .ok_or_else(|| anyhow!("Failed to extract content from Cohere response"))?
+                .ok_or_else(|| EngineError::malformed("Cohere", "missing content"))?
                 .to_string();
 
             let usage = Usage {
@@ -144,6 +221,7 @@ impl Engine for CohereEngine {
                 usage,
                 model,
                 finish_reason,
+                ..Default::default()
             })
         })
     }
@@ -243,20 +321,14 @@ impl Engine for CohereEngine {
                 .header("Authorization", format!("Bearer {}", auth_token))
                 .header("Content-Type", "application/json")
                 .json(&payload)
-                .send()
-                .await?
-                .json::<Value>()
+                .send_json("Cohere")
                 .await?;
 
             debug!("Cohere Response: {:?}", response);
 
-            if let Some(error) = response.get("error") {
-                return Err(anyhow!("Cohere API error: {:?}", error));
-            }
-
             let content = response["text"]
                 .as_str()
-                .ok_or_else(|| anyhow!("Failed to extract content from Cohere response"))?
+                .ok_or_else(|| EngineError::malformed("Cohere", "missing content"))?
                 .to_string();
 
             let usage = Usage {
@@ -297,7 +369,39 @@ impl Engine for CohereEngine {
                 usage,
                 model,
                 finish_reason,
+                ..Default::default()
             })
         })
     }
+
+    fn execute_stream<'a>(
+        &'a self,
+        request: &'a Request,
+    ) -> Box<dyn Future<Output = Result<ResponseStream<'a>>> + Send + 'a> {
+        Box::new(async move {
+            let mut payload = self.build_payload(request);
+            payload["stream"] = json!(true);
+            debug!("Cohere Streaming Payload: {:?}", payload);
+
+            let auth_token = self
+                .config
+                .parameters
+                .get("bearer_token")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| anyhow!("Bearer token not found in configuration"))?;
+
+            let response = self
+                .client
+                .post(self.url())
+                .header("Authorization", format!("Bearer {}", auth_token))
+                .header("Content-Type", "application/json")
+                .json(&payload)
+                .send_checked("Cohere")
+                .await?;
+
+            let stream =
+                json_object_events(response).map(|event| event.and_then(|value| cohere_chunk(&value)));
+            Ok(Box::pin(stream) as ResponseStream<'a>)
+        })
+    }
 }
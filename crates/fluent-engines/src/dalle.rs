@@ -11,6 +11,8 @@ use fluent_core::types::{ExtractedContent, Request, Response, UpsertRequest, Ups
 use fluent_core::neo4j_client::Neo4jClient;
 use fluent_core::traits::Engine;
 use fluent_core::config::EngineConfig;
+use fluent_core::error::{EngineError, RequestBuilderExt};
+use fluent_core::pricing::image_cost;
 use log::debug;
 use reqwest::Client;
 
@@ -70,27 +72,26 @@ impl Engine for DalleEngine {
             let response = self.client.post(&url)
                 .header("Authorization", format!("Bearer {}", auth_token))
                 .json(&payload)
-                .send()
-                .await?
-                .json::<Value>()
+                .send_json("DALL-E")
                 .await?;
 
             debug!("DALL-E Response: {:?}", response);
 
-            if let Some(error) = response.get("error") {
-                return Err(anyhow!("DALL-E API error: {:?}", error));
-            }
-
             let content = response["data"][0]["url"]
                 .as_str()
-                .ok_or_else(|| anyhow!("Failed to extract image URL from DALL-E response"))?
+                .ok_or_else(|| EngineError::malformed("DALL-E", "missing image URL"))?
                 .to_string();
 
+            let images = response["data"].as_array().map_or(1, Vec::len);
+            let cost = image_cost(&self.config, payload["model"].as_str().unwrap_or_default(), images);
+
             Ok(Response {
                 content,
                 usage: Usage { prompt_tokens: 0, completion_tokens: 0, total_tokens: 0 },
                 model: "dall-e".to_string(),
                 finish_reason: Some("success".to_string()),
+                cost,
+                ..Default::default()
             })
         })
     }
@@ -162,27 +163,26 @@ impl Engine for DalleEngine {
             let response = self.client.post(&url)
                 .header("Authorization", format!("Bearer {}", auth_token))
                 .json(&payload)
-                .send()
-                .await?
-                .json::<Value>()
+                .send_json("DALL-E")
                 .await?;
 
             debug!("DALL-E Response: {:?}", response);
 
-            if let Some(error) = response.get("error") {
-                return Err(anyhow!("DALL-E API error: {:?}", error));
-            }
-
             let content = response["data"][0]["url"]
                 .as_str()
-                .ok_or_else(|| anyhow!("Failed to extract image URL from DALL-E response"))?
+                .ok_or_else(|| EngineError::malformed("DALL-E", "missing image URL"))?
                 .to_string();
 
+            let images = response["data"].as_array().map_or(1, Vec::len);
+            let cost = image_cost(&self.config, payload["model"].as_str().unwrap_or_default(), images);
+
             Ok(Response {
                 content,
                 usage: Usage { prompt_tokens: 0, completion_tokens: 0, total_tokens: 0 },
                 model: "dall-e".to_string(),
                 finish_reason: Some("success".to_string()),
+                cost,
+                ..Default::default()
             })
         })
     }
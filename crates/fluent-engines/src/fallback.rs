@@ -0,0 +1,153 @@
+// crates/fluent-engines/src/fallback.rs
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use fluent_core::config::EngineConfig;
+use fluent_core::error::{EngineError, ErrorClass};
+use fluent_core::neo4j_client::Neo4jClient;
+use fluent_core::pricing::response_cost;
+use fluent_core::traits::{Engine, ResponseStream};
+use fluent_core::types::{ExtractedContent, Request, Response, UpsertRequest, UpsertResponse};
+use log::{info, warn};
+use serde_json::Value;
+
+use crate::create_engine;
+
+/// An engine that sends each request to the engines listed in its
+/// `fallback` config in order, moving to the next one when a request fails
+/// with an error in `fall_through_on`.
+///
+/// Calls that are not requests (`upsert`, `upload_file` and the Neo4j and
+/// session accessors) go to the first engine.
+pub struct FallbackEngine {
+    engines: Vec<(EngineConfig, Box<dyn Engine>)>,
+    fall_through_on: Vec<ErrorClass>,
+}
+
+impl FallbackEngine {
+    pub async fn new(config: EngineConfig) -> Result<Self> {
+        let fallback = config
+            .fallback
+            .ok_or_else(|| anyhow!("Engine '{}' has no fallback section", config.name))?;
+        if fallback.members.is_empty() {
+            return Err(anyhow!("Fallback engine '{}' lists no engines", config.name));
+        }
+
+        let mut engines = Vec::new();
+        for member in &fallback.members {
+            // Boxed because a member may itself be a fallback engine.
+            let engine = Box::pin(create_engine(member)).await?;
+            engines.push((member.clone(), engine));
+        }
+
+        Ok(Self {
+            engines,
+            fall_through_on: fallback.fall_through_on,
+        })
+    }
+
+    fn falls_through(&self, error: &anyhow::Error) -> bool {
+        EngineError::find(error).is_some_and(|error| self.fall_through_on.contains(&error.class()))
+    }
+
+    fn first(&self) -> &dyn Engine {
+        &*self.engines[0].1
+    }
+
+    /// Runs `operation` against each engine until one succeeds or fails with
+    /// an error that should not fall through.
+    async fn try_each<'a, T, F>(&'a self, operation: F) -> Result<(&'a EngineConfig, T)>
+    where
+        F: Fn(&'a dyn Engine) -> Box<dyn Future<Output = Result<T>> + Send + 'a>,
+    {
+        let last = self.engines.len() - 1;
+        for (index, (config, engine)) in self.engines.iter().enumerate() {
+            match Pin::from(operation(&**engine)).await {
+                Ok(value) => return Ok((config, value)),
+                Err(e) if index < last && self.falls_through(&e) => {
+                    warn!(
+                        "Engine '{}' failed: {}. Falling back to '{}'",
+                        config.name, e, self.engines[index + 1].0.name
+                    );
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        unreachable!("the last engine always returns")
+    }
+
+    fn answered(config: &EngineConfig, mut response: Response) -> Response {
+        info!("Response from engine '{}'", config.name);
+        // A nested chain has already recorded the engine that answered.
+        response.engine.get_or_insert_with(|| config.name.clone());
+        // Priced here because the chain's own config knows no model or prices.
+        response.cost = response_cost(config, &response);
+        response
+    }
+}
+
+impl Engine for FallbackEngine {
+    fn execute<'a>(
+        &'a self,
+        request: &'a Request,
+    ) -> Box<dyn Future<Output = Result<Response>> + Send + 'a> {
+        Box::new(async move {
+            let (config, response) = self.try_each(|engine| engine.execute(request)).await?;
+            Ok(Self::answered(config, response))
+        })
+    }
+
+    fn upsert<'a>(
+        &'a self,
+        request: &'a UpsertRequest,
+    ) -> Box<dyn Future<Output = Result<UpsertResponse>> + Send + 'a> {
+        self.first().upsert(request)
+    }
+
+    fn get_neo4j_client(&self) -> Option<&Arc<Neo4jClient>> {
+        self.first().get_neo4j_client()
+    }
+
+    fn get_session_id(&self) -> Option<String> {
+        self.first().get_session_id()
+    }
+
+    fn extract_content(&self, value: &Value) -> Option<ExtractedContent> {
+        self.first().extract_content(value)
+    }
+
+    fn upload_file<'a>(
+        &'a self,
+        file_path: &'a Path,
+    ) -> Box<dyn Future<Output = Result<String>> + Send + 'a> {
+        self.first().upload_file(file_path)
+    }
+
+    fn process_request_with_file<'a>(
+        &'a self,
+        request: &'a Request,
+        file_path: &'a Path,
+    ) -> Box<dyn Future<Output = Result<Response>> + Send + 'a> {
+        Box::new(async move {
+            let (config, response) = self
+                .try_each(|engine| engine.process_request_with_file(request, file_path))
+                .await?;
+            Ok(Self::answered(config, response))
+        })
+    }
+
+    fn execute_stream<'a>(
+        &'a self,
+        request: &'a Request,
+    ) -> Box<dyn Future<Output = Result<ResponseStream<'a>>> + Send + 'a> {
+        // Falls through only while opening the stream, before any text is shown.
+        Box::new(async move {
+            let (config, stream) = self.try_each(|engine| engine.execute_stream(request)).await?;
+            info!("Streaming from engine '{}'", config.name);
+            Ok(stream)
+        })
+    }
+}
@@ -2,6 +2,7 @@ use anyhow::{anyhow, Context, Result};
 use base64::engine::general_purpose::STANDARD as Base64;
 use base64::Engine as Base64Engine;
 use fluent_core::config::EngineConfig;
+use fluent_core::error::{EngineError, RequestBuilderExt};
 use fluent_core::neo4j_client::Neo4jClient;
 use fluent_core::traits::{Engine, EngineConfigProcessor};
 use fluent_core::types::{
@@ -195,18 +196,16 @@ impl Engine for FlowiseChainEngine {
                 self.config.connection.request_path
             );
 
-            let res = client.post(&url).json(&payload).send().await?;
-
-            let response_body = res.json::<serde_json::Value>().await?;
+            let response_body = client
+                .post(&url)
+                .json(&payload)
+                .send_json("FlowiseAI")
+                .await?;
             debug!("Response: {:?}", response_body);
 
-            if let Some(error) = response_body.get("error") {
-                return Err(anyhow!("FlowiseAI API error: {:?}", error));
-            }
-
             let content = response_body["text"]
                 .as_str()
-                .ok_or_else(|| anyhow!("Failed to extract content from FlowiseAI response"))?
+                .ok_or_else(|| EngineError::malformed("FlowiseAI", "missing content"))?
                 .to_string();
 
             // FlowiseAI doesn't provide token usage, so we'll estimate it based on content length
@@ -225,6 +224,7 @@ impl Engine for FlowiseChainEngine {
                 usage,
                 model,
                 finish_reason,
+                ..Default::default()
             })
         })
     }
@@ -297,18 +297,27 @@ impl Engine for FlowiseChainEngine {
 
             debug!("Sending request to URL: {}", url);
 
-            let response = client.post(&url).json(&payload).send().await?;
+            let response = client
+                .post(&url)
+                .json(&payload)
+                .send_checked("FlowiseAI")
+                .await?;
 
             debug!("Response status: {}", response.status());
 
-            let response_body = response.json::<serde_json::Value>().await?;
+            // An `error` field here only means the image was ignored, so it is
+            // reported below rather than failing the request.
+            let response_body = response
+                .json::<serde_json::Value>()
+                .await
+                .map_err(|e| EngineError::malformed("FlowiseAI", e.to_string()))?;
 
             debug!("FlowiseAI Response: {:?}", response_body);
 
             if response_body.get("error").is_some()
                 || response_body["text"]
                     .as_str()
-                    .map_or(false, |s| s.contains("no image provided"))
+                    .is_some_and(|s| s.contains("no image provided"))
             {
                 warn!(
                     "FlowiseAI did not process the image. Full response: {:?}",
@@ -318,7 +327,7 @@ impl Engine for FlowiseChainEngine {
 
             let content = response_body["text"]
                 .as_str()
-                .ok_or_else(|| anyhow!("Failed to extract content from FlowiseAI response"))?
+                .ok_or_else(|| EngineError::malformed("FlowiseAI", "missing content"))?
                 .to_string();
 
             // FlowiseAI doesn't provide token usage, so we'll estimate it based on content length
@@ -337,6 +346,7 @@ impl Engine for FlowiseChainEngine {
                 usage,
                 model,
                 finish_reason,
+                ..Default::default()
             })
         })
     }
@@ -8,12 +8,16 @@ use serde_json::{json, Value};
 use tokio::fs::File;
 use tokio::io::AsyncReadExt;
 use base64::{Engine as _, engine::general_purpose::STANDARD};
-use fluent_core::types::{ExtractedContent, Request, Response, UpsertRequest, UpsertResponse, Usage};
+use fluent_core::types::{ExtractedContent, Request, Response, ResponseChunk, Role, ToolCall, UpsertRequest, UpsertResponse, Usage};
 use fluent_core::neo4j_client::Neo4jClient;
-use fluent_core::traits::Engine;
+use fluent_core::traits::{Engine, ResponseStream};
 use fluent_core::config::EngineConfig;
+use fluent_core::error::{EngineError, RequestBuilderExt};
 use log::debug;
 use reqwest::Client;
+use futures::StreamExt;
+
+use crate::streaming::json_object_events;
 
 pub struct GoogleGeminiEngine {
     config: EngineConfig,
@@ -43,7 +47,7 @@ impl GoogleGeminiEngine {
         Ok(STANDARD.encode(&buffer))
     }
 
-    async fn send_gemini_request(&self, prompt: &str, encoded_image: Option<String>) -> Result<Value> {
+    fn gemini_url(&self, method: &str) -> Result<String> {
         let api_key = self.config.parameters.get("bearer_token")
             .and_then(|v| v.as_str())
             .ok_or_else(|| anyhow!("API key not found in configuration"))?;
@@ -52,28 +56,74 @@ impl GoogleGeminiEngine {
             .and_then(|v| v.as_str())
             .unwrap_or("gemini-1.5-pro-latest");
 
-        let url = format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
+        Ok(format!(
+            "https://generativelanguage.googleapis.com/v1beta/models/{}:{}?key={}",
             model,
+            method,
             api_key
-        );
+        ))
+    }
 
-        let mut content = vec![json!({
-            "parts": [{ "text": prompt }]
-        })];
+    fn build_gemini_request(&self, request: &Request, encoded_image: Option<String>) -> Value {
+        let conversation = request.conversation();
+
+        // Gemini takes system turns as a separate instruction and calls the assistant "model".
+        let system: Vec<&str> = conversation.iter()
+            .filter(|m| m.role == Role::System)
+            .map(|m| m.content.as_str())
+            .collect();
+
+        let mut content: Vec<Value> = Vec::new();
+        for m in conversation.iter().filter(|m| m.role != Role::System) {
+            match m.role {
+                Role::Tool => {
+                    let part = json!({
+                        "functionResponse": {
+                            "name": m.name,
+                            "response": { "content": m.content }
+                        }
+                    });
+                    // Responses to one model turn go back together in a single turn.
+                    match content.last_mut() {
+                        Some(last) if last["parts"][0].get("functionResponse").is_some() => {
+                            if let Some(parts) = last["parts"].as_array_mut() {
+                                parts.push(part);
+                            }
+                        }
+                        _ => content.push(json!({ "role": "user", "parts": [part] })),
+                    }
+                }
+                Role::Assistant if !m.tool_calls.is_empty() => {
+                    let mut parts = Vec::new();
+                    if !m.content.is_empty() {
+                        parts.push(json!({ "text": m.content }));
+                    }
+                    parts.extend(m.tool_calls.iter().map(|call| json!({
+                        "functionCall": { "name": call.name, "args": call.arguments }
+                    })));
+                    content.push(json!({ "role": "model", "parts": parts }));
+                }
+                _ => content.push(json!({
+                    "role": if m.role == Role::Assistant { "model" } else { "user" },
+                    "parts": [{ "text": m.content }]
+                })),
+            }
+        }
 
         if let Some(image) = encoded_image {
-            content.push(json!({
-                "parts": [{
-                    "inline_data": {
-                        "mime_type": "image/jpeg",
-                        "data": image
-                    }
-                }]
-            }));
+            let image_part = json!({
+                "inline_data": {
+                    "mime_type": "image/jpeg",
+                    "data": image
+                }
+            });
+            match content.last_mut().and_then(|last| last["parts"].as_array_mut()) {
+                Some(parts) => parts.push(image_part),
+                None => content.push(json!({ "role": "user", "parts": [image_part] })),
+            }
         }
 
-        let request_body = json!({
+        let mut request_body = json!({
             "contents": content,
             "generationConfig": {
                 "temperature": self.config.parameters.get("temperature").and_then(|v| v.as_f64()).unwrap_or(0.7),
@@ -83,38 +133,113 @@ impl GoogleGeminiEngine {
             }
         });
 
+        if !system.is_empty() {
+            request_body["systemInstruction"] = json!({
+                "parts": [{ "text": system.join("\n\n") }]
+            });
+        }
+
+        if !request.tools.is_empty() {
+            let declarations: Vec<Value> = request.tools.iter()
+                .map(|tool| json!({
+                    "name": tool.name,
+                    "description": tool.description,
+                    "parameters": tool.parameters,
+                }))
+                .collect();
+            request_body["tools"] = json!([{ "functionDeclarations": declarations }]);
+        }
+
+        if request.response_schema.is_some() {
+            request_body["generationConfig"]["responseMimeType"] = json!("application/json");
+        }
+
+        request_body
+    }
+
+    async fn send_gemini_request(&self, request: &Request, encoded_image: Option<String>) -> Result<Value> {
+        let url = self.gemini_url("generateContent")?;
+        let request_body = self.build_gemini_request(request, encoded_image);
+
         debug!("Google Gemini Request: {:?}", request_body);
 
-        let response = self.client.post(&url)
+        let response_body = self.client.post(&url)
             .header("Content-Type", "application/json")
             .json(&request_body)
-            .send()
+            .send_json("Google Gemini")
             .await?;
+        debug!("Google Gemini Response: {:?}", response_body);
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await?;
-            debug!("Error response body: {}", error_text);
-            return Err(anyhow!("Request failed with status: {}", status));
+        Ok(response_body)
+    }
+}
+
+/// Splits the first candidate's parts into text and function calls. Gemini
+/// does not always assign call ids, so the function name stands in for one.
+fn gemini_parts(response: &Value) -> (String, Vec<ToolCall>) {
+    let mut text = Vec::new();
+    let mut tool_calls = Vec::new();
+    for part in response["candidates"][0]["content"]["parts"].as_array().into_iter().flatten() {
+        if let Some(part_text) = part["text"].as_str() {
+            text.push(part_text);
+        }
+        if let Some(call) = part.get("functionCall") {
+            let name = call["name"].as_str().unwrap_or_default().to_string();
+            tool_calls.push(ToolCall {
+                id: call["id"].as_str().map(String::from).unwrap_or_else(|| name.clone()),
+                name,
+                arguments: call["args"].clone(),
+            });
         }
+    }
+    (text.concat(), tool_calls)
+}
 
-        let response_body: Value = response.json().await?;
-        debug!("Google Gemini Response: {:?}", response_body);
+/// Explains a response without any content: a blocked prompt or candidate,
+/// or otherwise a malformed response.
+fn gemini_empty_response_error(response: &Value) -> EngineError {
+    if let Some(reason) = response["promptFeedback"]["blockReason"].as_str() {
+        return EngineError::content_filtered("Google Gemini", format!("prompt blocked: {}", reason));
+    }
+    match response["candidates"][0]["finishReason"].as_str() {
+        Some(reason @ ("SAFETY" | "RECITATION" | "BLOCKLIST" | "PROHIBITED_CONTENT" | "SPII")) => {
+            EngineError::content_filtered("Google Gemini", format!("response blocked: {}", reason))
+        }
+        _ => EngineError::malformed("Google Gemini", "missing generated text"),
+    }
+}
 
-        Ok(response_body)
+/// Maps one element of a `streamGenerateContent` response into a `ResponseChunk`.
+fn gemini_chunk(value: &Value) -> Result<ResponseChunk> {
+    if value.get("error").is_some() {
+        return Err(EngineError::from_error_body("Google Gemini", value).into());
     }
+
+    let candidate = &value["candidates"][0];
+    let usage = value.get("usageMetadata").map(|usage| Usage {
+        prompt_tokens: usage["promptTokenCount"].as_u64().unwrap_or(0) as u32,
+        completion_tokens: usage["candidatesTokenCount"].as_u64().unwrap_or(0) as u32,
+        total_tokens: usage["totalTokenCount"].as_u64().unwrap_or(0) as u32,
+    });
+
+    Ok(ResponseChunk {
+        delta: candidate["content"]["parts"][0]["text"].as_str().unwrap_or_default().to_string(),
+        usage,
+        model: None,
+        finish_reason: candidate["finishReason"].as_str().map(String::from),
+    })
 }
 
 #[async_trait]
 impl Engine for GoogleGeminiEngine {
     fn execute<'a>(&'a self, request: &'a Request) -> Box<dyn Future<Output = Result<Response>> + Send + 'a> {
         Box::new(async move {
-            let response = self.send_gemini_request(&request.payload, None).await?;
+            let response = self.send_gemini_request(request, None).await?;
 
-            let generated_text = response["candidates"][0]["content"]["parts"][0]["text"]
-                .as_str()
-                .ok_or_else(|| anyhow!("Failed to extract generated text from Gemini response"))?
-                .to_string();
+            let (generated_text, tool_calls) = gemini_parts(&response);
+            if generated_text.is_empty() && tool_calls.is_empty() {
+                return Err(gemini_empty_response_error(&response).into());
+            }
 
             let usage = Usage {
                 prompt_tokens: response["usageMetadata"]["promptTokenCount"].as_u64().unwrap_or(0) as u32,
@@ -136,6 +261,8 @@ impl Engine for GoogleGeminiEngine {
                 usage,
                 model,
                 finish_reason,
+                tool_calls,
+                ..Default::default()
             })
         })
     }
@@ -178,11 +305,11 @@ impl Engine for GoogleGeminiEngine {
     fn process_request_with_file<'a>(&'a self, request: &'a Request, file_path: &'a Path) -> Box<dyn Future<Output = Result<Response>> + Send + 'a> {
         Box::new(async move {
             let encoded_image = Pin::from(self.upload_file(file_path)).await?;
-            let response = self.send_gemini_request(&request.payload, Some(encoded_image)).await?;
+            let response = self.send_gemini_request(request, Some(encoded_image)).await?;
 
             let generated_text = response["candidates"][0]["content"]["parts"][0]["text"]
                 .as_str()
-                .ok_or_else(|| anyhow!("Failed to extract generated text from Gemini response"))?
+                .ok_or_else(|| EngineError::malformed("Google Gemini", "missing generated text"))?
                 .to_string();
 
             let usage = Usage {
@@ -205,7 +332,36 @@ impl Engine for GoogleGeminiEngine {
                 usage,
                 model,
                 finish_reason,
+                ..Default::default()
             })
         })
     }
-}
\ No newline at end of file
+
+    fn execute_stream<'a>(&'a self, request: &'a Request) -> Box<dyn Future<Output = Result<ResponseStream<'a>>> + Send + 'a> {
+        Box::new(async move {
+            let url = self.gemini_url("streamGenerateContent")?;
+            let request_body = self.build_gemini_request(request, None);
+            debug!("Google Gemini Streaming Request: {:?}", request_body);
+
+            let response = self.client.post(&url)
+                .header("Content-Type", "application/json")
+                .json(&request_body)
+                .send_checked("Google Gemini")
+                .await?;
+
+            let model = self.config.parameters.get("modelName")
+                .and_then(|v| v.as_str())
+                .unwrap_or("gemini-1.5-pro-latest")
+                .to_string();
+
+            let stream = json_object_events(response).map(move |event| {
+                let chunk = gemini_chunk(&event?)?;
+                Ok(ResponseChunk {
+                    model: Some(model.clone()),
+                    ..chunk
+                })
+            });
+            Ok(Box::pin(stream) as ResponseStream<'a>)
+        })
+    }
+}
@@ -9,10 +9,14 @@ use serde_json::{json, Value};
 
 use fluent_core::types::{ExtractedContent, Request, Response, UpsertRequest, UpsertResponse, Usage};
 use fluent_core::neo4j_client::Neo4jClient;
-use fluent_core::traits::Engine;
+use fluent_core::traits::{Engine, OpenAIConfigProcessor, ResponseStream};
 use fluent_core::config::EngineConfig;
+use fluent_core::error::{EngineError, RequestBuilderExt};
 use log::debug;
 use reqwest::Client;
+use futures::StreamExt;
+
+use crate::streaming::{openai_chunk, sse_json_events};
 
 pub struct GroqLPUEngine {
     config: EngineConfig,
@@ -35,22 +39,29 @@ impl GroqLPUEngine {
         })
     }
 
-    async fn send_groq_request(&self, messages: Vec<Value>) -> Result<Value> {
-        let url = format!("{}://{}:{}{}",
-                          self.config.connection.protocol,
-                          self.config.connection.hostname,
-                          self.config.connection.port,
-                          self.config.connection.request_path
-        );
+    fn url(&self) -> String {
+        format!("{}://{}:{}{}",
+                self.config.connection.protocol,
+                self.config.connection.hostname,
+                self.config.connection.port,
+                self.config.connection.request_path
+        )
+    }
 
-        let payload = json!({
+    fn build_groq_payload(&self, messages: Value) -> Value {
+        json!({
             "model": self.config.parameters.get("model").and_then(|v| v.as_str()).unwrap_or("mixtral-8x7b-32768"),
             "messages": messages,
             "temperature": self.config.parameters.get("temperature").and_then(|v| v.as_f64()).unwrap_or(0.7),
             "max_tokens": self.config.parameters.get("max_tokens").and_then(|v| v.as_u64()).unwrap_or(1024),
             "top_p": self.config.parameters.get("top_p").and_then(|v| v.as_f64()).unwrap_or(1.0),
             "stream": self.config.parameters.get("stream").and_then(|v| v.as_bool()).unwrap_or(false),
-        });
+        })
+    }
+
+    async fn send_groq_request(&self, messages: Value) -> Result<Value> {
+        let url = self.url();
+        let payload = self.build_groq_payload(messages);
 
         debug!("GroqLPU Request: {:?}", payload);
 
@@ -58,21 +69,12 @@ impl GroqLPUEngine {
             .and_then(|v| v.as_str())
             .ok_or_else(|| anyhow!("Bearer token not found in configuration"))?;
 
-        let response = self.client.post(&url)
+        let response_body = self.client.post(&url)
             .header("Authorization", format!("Bearer {}", auth_token))
             .header("Content-Type", "application/json")
             .json(&payload)
-            .send()
+            .send_json("Groq")
             .await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await?;
-            debug!("Error response body: {}", error_text);
-            return Err(anyhow!("Request failed with status: {}", status));
-        }
-
-        let response_body: Value = response.json().await?;
         debug!("GroqLPU Response: {:?}", response_body);
 
         Ok(response_body)
@@ -83,16 +85,13 @@ impl GroqLPUEngine {
 impl Engine for GroqLPUEngine {
     fn execute<'a>(&'a self, request: &'a Request) -> Box<dyn Future<Output = Result<Response>> + Send + 'a> {
         Box::new(async move {
-            let messages = vec![json!({
-                "role": "user",
-                "content": request.payload
-            })];
+            let messages = OpenAIConfigProcessor::map_messages(&request.conversation());
 
             let response = self.send_groq_request(messages).await?;
 
             let content = response["choices"][0]["message"]["content"]
                 .as_str()
-                .ok_or_else(|| anyhow!("Failed to extract content from GroqLPU response"))?
+                .ok_or_else(|| EngineError::malformed("Groq", "missing content"))?
                 .to_string();
 
             let usage = Usage {
@@ -109,6 +108,7 @@ impl Engine for GroqLPUEngine {
                 usage,
                 model,
                 finish_reason,
+                ..Default::default()
             })
         })
     }
@@ -153,4 +153,28 @@ impl Engine for GroqLPUEngine {
             Err(anyhow!("File processing not supported for GroqLPU engine"))
         })
     }
-}
\ No newline at end of file
+
+    fn execute_stream<'a>(&'a self, request: &'a Request) -> Box<dyn Future<Output = Result<ResponseStream<'a>>> + Send + 'a> {
+        Box::new(async move {
+            let messages = OpenAIConfigProcessor::map_messages(&request.conversation());
+
+            let mut payload = self.build_groq_payload(messages);
+            payload["stream"] = json!(true);
+            debug!("GroqLPU Streaming Request: {:?}", payload);
+
+            let auth_token = self.config.parameters.get("bearer_token")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| anyhow!("Bearer token not found in configuration"))?;
+
+            let response = self.client.post(self.url())
+                .header("Authorization", format!("Bearer {}", auth_token))
+                .header("Content-Type", "application/json")
+                .json(&payload)
+                .send_checked("Groq")
+                .await?;
+
+            let stream = sse_json_events(response).map(|event| event.map(|value| openai_chunk(&value)));
+            Ok(Box::pin(stream) as ResponseStream<'a>)
+        })
+    }
+}
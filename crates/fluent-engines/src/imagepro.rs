@@ -12,6 +12,8 @@ use fluent_core::types::{ExtractedContent, Request, Response, UpsertRequest, Ups
 use fluent_core::neo4j_client::Neo4jClient;
 use fluent_core::traits::Engine;
 use fluent_core::config::EngineConfig;
+use fluent_core::pricing::image_cost;
+use fluent_core::error::{EngineError, RequestBuilderExt};
 use log::{debug, info};
 use reqwest::Client;
 use uuid::Uuid;
@@ -34,10 +36,10 @@ impl ImagineProEngine {
         };
 
         Ok(Self {
+            download_dir: config.download_dir.clone(),
             config,
             client: Client::new(),
             neo4j_client,
-            download_dir: None,
         })
     }
 
@@ -63,9 +65,7 @@ impl ImagineProEngine {
         for _ in 0..max_attempts {
             let response = self.client.get(&url)
                 .header("Authorization", format!("Bearer {}", auth_token))
-                .send()
-                .await?
-                .json::<Value>()
+                .send_json("ImaginePro")
                 .await?;
 
             match response["status"].as_str() {
@@ -87,8 +87,9 @@ impl ImagineProEngine {
     }
 
     async fn download_image(&self, uri: &str) -> Result<String> {
-        let response = self.client.get(uri).send().await?;
-        let content = response.bytes().await?;
+        let response = self.client.get(uri).send_checked("ImaginePro").await?;
+        let content = response.bytes().await
+            .map_err(|e| EngineError::transport("ImaginePro", e))?;
 
         let download_dir = self.download_dir.as_ref()
             .ok_or_else(|| anyhow!("Download directory not set"))?;
@@ -152,9 +153,7 @@ impl Engine for ImagineProEngine {
             let response = self.client.post(&url)
                 .header("Authorization", format!("Bearer {}", auth_token))
                 .json(&payload)
-                .send()
-                .await?
-                .json::<Value>()
+                .send_json("ImaginePro")
                 .await?;
 
             if !response["success"].as_bool().unwrap_or(false) {
@@ -179,6 +178,8 @@ impl Engine for ImagineProEngine {
                 },
                 model: "imaginepro-midjourney".to_string(),
                 finish_reason: Some("success".to_string()),
+                cost: image_cost(&self.config, "imaginepro-midjourney", 1),
+                ..Default::default()
             })
         })
     }
@@ -222,8 +223,8 @@ impl Engine for ImagineProEngine {
             let prompt = format!("{} {}", data_url, request.payload);
 
             let new_request = Request {
-                flowname: request.flowname.clone(),
                 payload: prompt,
+                ..request.clone()
             };
 
             // Use Box::pin to create a pinned future that can be awaited
@@ -4,6 +4,7 @@ use std::sync::Arc;
 use fluent_core::types::{ExtractedContent, Request, Response, UpsertRequest, UpsertResponse, Usage};
 use fluent_core::traits::{Engine, EngineConfigProcessor};
 use fluent_core::config::EngineConfig;
+use fluent_core::error::{EngineError, RequestBuilderExt};
 use fluent_core::neo4j_client::Neo4jClient;
 use anyhow::{Result, anyhow};
 use reqwest::Client;
@@ -103,20 +104,14 @@ impl Engine for LangflowEngine {
                               self.config.connection.request_path
             );
 
-            let res = client.post(&url)
+            let response_body = client.post(&url)
                 .json(&payload)
-                .send()
+                .send_json("Langflow")
                 .await?;
-
-            let response_body = res.json::<serde_json::Value>().await?;
             debug!("Response: {:?}", response_body);
 
-            if let Some(error) = response_body.get("error") {
-                return Err(anyhow!("Langflow API error: {:?}", error));
-            }
-
             let extracted_content = self.extract_content(&response_body)
-                .ok_or_else(|| anyhow!("Failed to extract content from Langflow response"))?;
+                .ok_or_else(|| EngineError::malformed("Langflow", "missing content"))?;
 
             let estimated_tokens = (extracted_content.main_content.len() as f32 / 4.0).ceil() as u32;
             let usage = Usage {
@@ -133,6 +128,7 @@ impl Engine for LangflowEngine {
                 usage,
                 model,
                 finish_reason,
+                ..Default::default()
             })
         })
     }
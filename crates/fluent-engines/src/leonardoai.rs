@@ -11,6 +11,8 @@ use fluent_core::types::{ExtractedContent, Request, Response, UpsertRequest, Ups
 use fluent_core::neo4j_client::Neo4jClient;
 use fluent_core::traits::Engine;
 use fluent_core::config::EngineConfig;
+use fluent_core::pricing::image_cost;
+use fluent_core::error::{EngineError, RequestBuilderExt};
 use log::debug;
 use reqwest::Client;
 
@@ -59,15 +61,13 @@ impl LeonardoAIEngine {
         let response = self.client.post(&url)
             .header("Authorization", format!("Bearer {}", auth_token))
             .json(&payload)
-            .send()
-            .await?
-            .json::<Value>()
+            .send_json("Leonardo AI")
             .await?;
 
         response["uploadInitImage"]["id"]
             .as_str()
-            .ok_or_else(|| anyhow!("Failed to extract uploaded image ID"))
             .map(String::from)
+            .ok_or_else(|| EngineError::malformed("Leonardo AI", "missing uploaded image ID").into())
     }
 
     fn create_payload(&self, request: &Request, image_id: Option<String>) -> Value {
@@ -138,20 +138,14 @@ impl Engine for LeonardoAIEngine {
             let response = self.client.post(&url)
                 .header("Authorization", format!("Bearer {}", auth_token))
                 .json(&payload)
-                .send()
-                .await?
-                .json::<Value>()
+                .send_json("Leonardo AI")
                 .await?;
 
             debug!("Leonardo AI Response: {:?}", response);
 
-            if let Some(error) = response.get("error") {
-                return Err(anyhow!("Leonardo AI API error: {:?}", error));
-            }
-
             let generation_id = response["sdGenerationJob"]["generationId"]
                 .as_str()
-                .ok_or_else(|| anyhow!("Failed to extract generation ID from Leonardo AI response"))?;
+                .ok_or_else(|| EngineError::malformed("Leonardo AI", "missing generation ID"))?;
 
             // Poll for results
             let mut image_urls = Vec::new();
@@ -167,9 +161,7 @@ impl Engine for LeonardoAIEngine {
 
                 let status_response = self.client.get(&status_url)
                     .header("Authorization", format!("Bearer {}", auth_token))
-                    .send()
-                    .await?
-                    .json::<Value>()
+                    .send_json("Leonardo AI")
                     .await?;
 
                 if status_response["generations_by_pk"]["status"].as_str() == Some("COMPLETE") {
@@ -193,6 +185,8 @@ impl Engine for LeonardoAIEngine {
                 usage: Usage { prompt_tokens: 0, completion_tokens: 0, total_tokens: 0 },
                 model: "leonardo-ai".to_string(),
                 finish_reason: Some("success".to_string()),
+                cost: image_cost(&self.config, "leonardo-ai", image_urls.len()),
+                ..Default::default()
             })
         })
     }
@@ -266,20 +260,14 @@ impl Engine for LeonardoAIEngine {
             let response = self.client.post(&url)
                 .header("Authorization", format!("Bearer {}", auth_token))
                 .json(&payload)
-                .send()
-                .await?
-                .json::<Value>()
+                .send_json("Leonardo AI")
                 .await?;
 
             debug!("Leonardo AI Response: {:?}", response);
 
-            if let Some(error) = response.get("error") {
-                return Err(anyhow!("Leonardo AI API error: {:?}", error));
-            }
-
             let generation_id = response["sdGenerationJob"]["generationId"]
                 .as_str()
-                .ok_or_else(|| anyhow!("Failed to extract generation ID from Leonardo AI response"))?;
+                .ok_or_else(|| EngineError::malformed("Leonardo AI", "missing generation ID"))?;
 
             // Poll for results
             let mut image_urls = Vec::new();
@@ -295,9 +283,7 @@ impl Engine for LeonardoAIEngine {
 
                 let status_response = self.client.get(&status_url)
                     .header("Authorization", format!("Bearer {}", auth_token))
-                    .send()
-                    .await?
-                    .json::<Value>()
+                    .send_json("Leonardo AI")
                     .await?;
 
                 if status_response["generations_by_pk"]["status"].as_str() == Some("COMPLETE") {
@@ -321,6 +307,8 @@ impl Engine for LeonardoAIEngine {
                 usage: Usage { prompt_tokens: 0, completion_tokens: 0, total_tokens: 0 },
                 model: "leonardo-ai".to_string(),
                 finish_reason: Some("success".to_string()),
+                cost: image_cost(&self.config, "leonardo-ai", image_urls.len()),
+                ..Default::default()
             })
         })
     }
@@ -1,28 +1,22 @@
-use std::str::FromStr;
-
-use anthropic::AnthropicEngine;
-use cohere::CohereEngine;
-use flowise_chain::FlowiseChainEngine;
+use anyhow::anyhow;
+use cache::CachedEngine;
 use fluent_core::{config::EngineConfig, traits::Engine};
-use google_gemini::GoogleGeminiEngine;
-use groqlpu::GroqLPUEngine;
-use imagepro::ImagineProEngine;
-use langflow::LangflowEngine;
-use leonardoai::LeonardoAIEngine;
-use mistral::MistralEngine;
-use openai::OpenAIEngine;
-use perplexity::PerplexityEngine;
+use rate_limit::{RateLimitedEngine, RateLimiter};
+use registry::global_registry;
+use replay::RecordingEngine;
+use retry::RetryEngine;
 use serde::{Deserialize, Serialize};
-use stabilityai::StabilityAIEngine;
-use strum::{Display, EnumString};
-use webhook::WebhookEngine;
+use strum::{Display, EnumIter, EnumString};
 
 extern crate core;
 
 // crates/fluent-engines/src/lib.rs
 pub mod anthropic;
+pub mod azure_openai;
+pub mod cache;
 pub mod cohere;
 pub mod dalle;
+pub mod fallback;
 pub mod flowise_chain;
 pub mod google_gemini;
 pub mod groqlpu;
@@ -31,12 +25,21 @@ pub mod langflow;
 pub mod leonardoai;
 pub mod mistral;
 pub mod openai;
+pub mod openai_compatible;
 pub mod perplexity;
 pub mod pipeline_executor;
+pub mod rate_limit;
+pub mod registry;
+pub mod replay;
+pub mod retry;
 pub mod stabilityai;
+pub mod streaming;
+pub mod structured;
+pub mod tools;
 pub mod webhook;
 
-#[derive(Debug, PartialEq, EnumString, Serialize, Deserialize, Display)]
+/// The engines built into this crate, registered by name in the engine registry.
+#[derive(Debug, PartialEq, EnumString, EnumIter, Serialize, Deserialize, Display)]
 pub enum EngineType {
     #[strum(ascii_case_insensitive, to_string = "openai")]
     OpenAI,
@@ -86,24 +89,54 @@ pub enum EngineType {
     LeonardoAI,
     #[strum(ascii_case_insensitive, to_string = "dalle")]
     Dalle,
+    #[strum(ascii_case_insensitive, to_string = "fallback")]
+    Fallback,
+    #[strum(ascii_case_insensitive, to_string = "replay")]
+    Replay,
+    #[strum(
+        ascii_case_insensitive,
+        to_string = "openai_compatible",
+        serialize = "openaicompatible"
+    )]
+    OpenAICompatible,
+    #[strum(ascii_case_insensitive, to_string = "azure_openai", serialize = "azureopenai")]
+    AzureOpenAI,
 }
 
+/// Builds the engine named by `engine_config.engine` through the global
+/// engine registry and wraps it in the layers its config asks for.
 pub async fn create_engine(engine_config: &EngineConfig) -> anyhow::Result<Box<dyn Engine>> {
-    let engine: Box<dyn Engine> = match EngineType::from_str(engine_config.engine.as_str())? {
-        EngineType::OpenAI => Box::new(OpenAIEngine::new(engine_config.clone()).await?),
-        EngineType::Anthropic => Box::new(AnthropicEngine::new(engine_config.clone()).await?),
-        EngineType::Cohere => Box::new(CohereEngine::new(engine_config.clone()).await?),
-        EngineType::GoogleGemini => Box::new(GoogleGeminiEngine::new(engine_config.clone()).await?),
-        EngineType::Perplexity => Box::new(PerplexityEngine::new(engine_config.clone()).await?),
-        EngineType::GroqLpu => Box::new(GroqLPUEngine::new(engine_config.clone()).await?),
-        EngineType::Mistral => Box::new(MistralEngine::new(engine_config.clone()).await?),
-        EngineType::FlowiseChain => Box::new(FlowiseChainEngine::new(engine_config.clone()).await?),
-        EngineType::LangflowChain => Box::new(LangflowEngine::new(engine_config.clone()).await?),
-        EngineType::Webhook => Box::new(WebhookEngine::new(engine_config.clone()).await?),
-        EngineType::StabilityAI => Box::new(StabilityAIEngine::new(engine_config.clone()).await?),
-        EngineType::ImaginePro => Box::new(ImagineProEngine::new(engine_config.clone()).await?),
-        EngineType::LeonardoAI => Box::new(LeonardoAIEngine::new(engine_config.clone()).await?),
-        EngineType::Dalle => Box::new(dalle::DalleEngine::new(engine_config.clone()).await?),
-    };
+    let factory = global_registry()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .factory(&engine_config.engine)
+        .ok_or_else(|| anyhow!("Unsupported engine: {}", engine_config.engine))?;
+    let engine = factory(engine_config.clone()).await?;
+    wrap_engine(engine, engine_config)
+}
+
+/// Wraps an engine in the rate limit, retry, cache and recording layers its
+/// config asks for. `create_engine` does this already; call it when building
+/// an engine another way.
+pub fn wrap_engine(
+    mut engine: Box<dyn Engine>,
+    engine_config: &EngineConfig,
+) -> anyhow::Result<Box<dyn Engine>> {
+    // Limits sit inside retries so every attempt waits its turn.
+    if let Some(rate_limit) = &engine_config.rate_limit {
+        let limiter = RateLimiter::shared(&engine_config.name, rate_limit);
+        engine = Box::new(RateLimitedEngine::new(engine, limiter));
+    }
+    if let Some(retry) = &engine_config.retry {
+        engine = Box::new(RetryEngine::new(engine, retry.clone()));
+    }
+    // Outermost, so a cache hit skips the limits and retries entirely.
+    if let Some(cache) = &engine_config.cache {
+        engine = Box::new(CachedEngine::new(engine, engine_config, cache.clone()));
+    }
+    // Records what callers actually received, cache hits included.
+    if let Some(cassette) = &engine_config.record {
+        engine = Box::new(RecordingEngine::new(engine, cassette)?);
+    }
     Ok(engine)
 }
@@ -9,10 +9,14 @@ use serde_json::{json, Value};
 
 use fluent_core::types::{ExtractedContent, Request, Response, UpsertRequest, UpsertResponse, Usage};
 use fluent_core::neo4j_client::Neo4jClient;
-use fluent_core::traits::Engine;
+use fluent_core::traits::{Engine, OpenAIConfigProcessor, ResponseStream};
 use fluent_core::config::EngineConfig;
+use fluent_core::error::{EngineError, RequestBuilderExt};
 use log::debug;
 use reqwest::Client;
+use futures::StreamExt;
+
+use crate::streaming::{openai_chunk, sse_json_events};
 
 pub struct MistralEngine {
     config: EngineConfig,
@@ -35,22 +39,39 @@ impl MistralEngine {
         })
     }
 
-    async fn send_mistral_request(&self, messages: Vec<Value>) -> Result<Value> {
-        let url = format!("{}://{}:{}{}",
-                          self.config.connection.protocol,
-                          self.config.connection.hostname,
-                          self.config.connection.port,
-                          self.config.connection.request_path
-        );
+    fn url(&self) -> String {
+        format!("{}://{}:{}{}",
+                self.config.connection.protocol,
+                self.config.connection.hostname,
+                self.config.connection.port,
+                self.config.connection.request_path
+        )
+    }
 
-        let payload = json!({
+    fn build_mistral_payload(&self, request: &Request) -> Value {
+        let mut payload = json!({
             "model": self.config.parameters.get("model").and_then(|v| v.as_str()).unwrap_or("mistral-7b-instruct"),
-            "messages": messages,
+            "messages": OpenAIConfigProcessor::map_messages(&request.conversation()),
             "temperature": self.config.parameters.get("temperature").and_then(|v| v.as_f64()).unwrap_or(0.7),
             "max_tokens": self.config.parameters.get("max_tokens").and_then(|v| v.as_u64()).unwrap_or(1024),
             "top_p": self.config.parameters.get("top_p").and_then(|v| v.as_f64()).unwrap_or(1.0),
             "stream": self.config.parameters.get("stream").and_then(|v| v.as_bool()).unwrap_or(false),
         });
+        // Mistral takes tools in the OpenAI format.
+        if !request.tools.is_empty() {
+            payload["tools"] = OpenAIConfigProcessor::map_tools(&request.tools);
+        }
+        if let Some(format) =
+            OpenAIConfigProcessor::response_format(request.response_schema.as_ref())
+        {
+            payload["response_format"] = format;
+        }
+        payload
+    }
+
+    async fn send_mistral_request(&self, request: &Request) -> Result<Value> {
+        let url = self.url();
+        let payload = self.build_mistral_payload(request);
 
         debug!("Mistral Request: {:?}", payload);
 
@@ -58,21 +79,12 @@ impl MistralEngine {
             .and_then(|v| v.as_str())
             .ok_or_else(|| anyhow!("Bearer token not found in configuration"))?;
 
-        let response = self.client.post(&url)
+        let response_body = self.client.post(&url)
             .header("Authorization", format!("Bearer {}", auth_token))
             .header("Content-Type", "application/json")
             .json(&payload)
-            .send()
+            .send_json("Mistral")
             .await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await?;
-            debug!("Error response body: {}", error_text);
-            return Err(anyhow!("Request failed with status: {}", status));
-        }
-
-        let response_body: Value = response.json().await?;
         debug!("Mistral Response: {:?}", response_body);
 
         Ok(response_body)
@@ -83,17 +95,15 @@ impl MistralEngine {
 impl Engine for MistralEngine {
     fn execute<'a>(&'a self, request: &'a Request) -> Box<dyn Future<Output = Result<Response>> + Send + 'a> {
         Box::new(async move {
-            let messages = vec![json!({
-                "role": "user",
-                "content": request.payload
-            })];
-
-            let response = self.send_mistral_request(messages).await?;
-
-            let content = response["choices"][0]["message"]["content"]
-                .as_str()
-                .ok_or_else(|| anyhow!("Failed to extract content from Mistral response"))?
-                .to_string();
+            let response = self.send_mistral_request(request).await?;
+
+            let message = &response["choices"][0]["message"];
+            let tool_calls = OpenAIConfigProcessor::parse_tool_calls(message);
+            let content = match message["content"].as_str() {
+                Some(content) => content.to_string(),
+                None if !tool_calls.is_empty() => String::new(),
+                None => return Err(EngineError::malformed("Mistral", "missing content").into()),
+            };
 
             let usage = Usage {
                 prompt_tokens: response["usage"]["prompt_tokens"].as_u64().unwrap_or(0) as u32,
@@ -109,6 +119,8 @@ impl Engine for MistralEngine {
                 usage,
                 model,
                 finish_reason,
+                tool_calls,
+                ..Default::default()
             })
         })
     }
@@ -153,4 +165,26 @@ impl Engine for MistralEngine {
             Err(anyhow!("File processing not supported for Mistral engine"))
         })
     }
+
+    fn execute_stream<'a>(&'a self, request: &'a Request) -> Box<dyn Future<Output = Result<ResponseStream<'a>>> + Send + 'a> {
+        Box::new(async move {
+            let mut payload = self.build_mistral_payload(request);
+            payload["stream"] = json!(true);
+            debug!("Mistral Streaming Request: {:?}", payload);
+
+            let auth_token = self.config.parameters.get("bearer_token")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| anyhow!("Bearer token not found in configuration"))?;
+
+            let response = self.client.post(self.url())
+                .header("Authorization", format!("Bearer {}", auth_token))
+                .header("Content-Type", "application/json")
+                .json(&payload)
+                .send_checked("Mistral")
+                .await?;
+
+            let stream = sse_json_events(response).map(|event| event.map(|value| openai_chunk(&value)));
+            Ok(Box::pin(stream) as ResponseStream<'a>)
+        })
+    }
 }
@@ -2,11 +2,13 @@ use anyhow::{anyhow, Context, Result};
 use base64::engine::general_purpose::STANDARD as Base64;
 use base64::Engine as Base64Engine;
 use fluent_core::config::EngineConfig;
+use fluent_core::error::{EngineError, RequestBuilderExt};
 use fluent_core::neo4j_client::Neo4jClient;
-use fluent_core::traits::{Engine, EngineConfigProcessor, OpenAIConfigProcessor};
+use fluent_core::traits::{Engine, EngineConfigProcessor, OpenAIConfigProcessor, ResponseStream};
 use fluent_core::types::{
     ExtractedContent, Request, Response, UpsertRequest, UpsertResponse, Usage,
 };
+use futures::StreamExt;
 use log::debug;
 use reqwest::multipart::{Form, Part};
 use reqwest::Client;
@@ -18,6 +20,8 @@ use tokio::fs::File;
 use tokio::io::AsyncReadExt;
 use tokio_util::codec::{BytesCodec, FramedRead};
 
+use crate::streaming::{openai_chunk, sse_json_events};
+
 pub struct OpenAIEngine {
     config: EngineConfig,
     config_processor: OpenAIConfigProcessor,
@@ -38,6 +42,41 @@ impl OpenAIEngine {
             neo4j_client,
         })
     }
+
+    fn build_payload(&self, request: &Request) -> Result<Value> {
+        let mut payload = self.config_processor.process_config(&self.config)?;
+        debug!("OpenAI Processed Config Payload: {:#?}", payload);
+
+        // Add the conversation, ending with the user's request, to the messages
+        payload["messages"] = OpenAIConfigProcessor::map_messages(&request.conversation());
+        if !request.tools.is_empty() {
+            payload["tools"] = OpenAIConfigProcessor::map_tools(&request.tools);
+        }
+        if let Some(format) =
+            OpenAIConfigProcessor::response_format(request.response_schema.as_ref())
+        {
+            payload["response_format"] = format;
+        }
+        Ok(payload)
+    }
+
+    fn url(&self) -> String {
+        format!(
+            "{}://{}:{}{}",
+            self.config.connection.protocol,
+            self.config.connection.hostname,
+            self.config.connection.port,
+            self.config.connection.request_path
+        )
+    }
+
+    fn auth_token(&self) -> Result<&str> {
+        self.config
+            .parameters
+            .get("bearer_token")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow!("Bearer token not found in configuration"))
+    }
 }
 
 impl Engine for OpenAIEngine {
@@ -91,51 +130,29 @@ impl Engine for OpenAIEngine {
             let client = Client::new();
             debug!("Config: {:?}", self.config);
 
-            let mut payload = self.config_processor.process_config(&self.config)?;
-            debug!("OpenAI Processed Config Payload: {:#?}", payload);
-
-            // Add the user's request to the messages
-            payload["messages"] = json!([
-                {
-                    "role": "user",
-                    "content": request.payload
-                }
-            ]);
-
-            let url = format!(
-                "{}://{}:{}{}",
-                self.config.connection.protocol,
-                self.config.connection.hostname,
-                self.config.connection.port,
-                self.config.connection.request_path
-            );
-
-            let auth_token = self
-                .config
-                .parameters
-                .get("bearer_token")
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| anyhow!("Bearer token not found in configuration"))?;
+            let payload = self.build_payload(request)?;
+            let url = self.url();
+            let auth_token = self.auth_token()?;
 
-            let res = client
+            let response_body = client
                 .post(&url)
                 .header("Authorization", format!("Bearer {}", auth_token))
                 .header("Content-Type", "application/json")
                 .json(&payload)
-                .send()
+                .send_json("OpenAI")
                 .await?;
-
-            let response_body = res.json::<serde_json::Value>().await?;
             debug!("Response: {:?}", response_body);
 
-            if let Some(error) = response_body.get("error") {
-                return Err(anyhow!("OpenAI API error: {:?}", error));
-            }
-
-            let content = response_body["choices"][0]["message"]["content"]
-                .as_str()
-                .ok_or_else(|| anyhow!("Failed to extract content from OpenAI response"))?
-                .to_string();
+            let message = &response_body["choices"][0]["message"];
+            let tool_calls = OpenAIConfigProcessor::parse_tool_calls(message);
+            // Content is null when the model only calls tools.
+            let content = match message["content"].as_str() {
+                Some(content) => content.to_string(),
+                None if !tool_calls.is_empty() => String::new(),
+                None => {
+                    return Err(EngineError::malformed("OpenAI", "missing message content").into())
+                }
+            };
 
             let usage = Usage {
                 prompt_tokens: response_body["usage"]["prompt_tokens"]
@@ -160,6 +177,8 @@ impl Engine for OpenAIEngine {
                 usage,
                 model,
                 finish_reason,
+                tool_calls,
+                ..Default::default()
             })
         })
     }
@@ -194,19 +213,17 @@ impl Engine for OpenAIEngine {
                 .and_then(|v| v.as_str())
                 .ok_or_else(|| anyhow!("Bearer token not found in configuration"))?;
 
-            let response = client
+            let response_body = client
                 .post(url)
                 .header("Authorization", format!("Bearer {}", auth_token))
                 .multipart(form)
-                .send()
+                .send_json("OpenAI")
                 .await?;
 
-            let response_body = response.json::<serde_json::Value>().await?;
-
             response_body["id"]
                 .as_str()
-                .ok_or_else(|| anyhow!("Failed to extract file ID from OpenAI response"))
                 .map(|id| id.to_string())
+                .ok_or_else(|| EngineError::malformed("OpenAI", "missing file ID").into())
         })
     }
 
@@ -262,26 +279,20 @@ impl Engine for OpenAIEngine {
                 .and_then(|v| v.as_str())
                 .ok_or_else(|| anyhow!("Bearer token not found in configuration"))?;
 
-            let response = client
+            let response_body = client
                 .post(&url)
                 .header("Authorization", format!("Bearer {}", auth_token))
                 .header("Content-Type", "application/json")
                 .json(&payload)
-                .send()
+                .send_json("OpenAI")
                 .await?;
 
-            let response_body = response.json::<serde_json::Value>().await?;
-
             // Debug print the response
             debug!("OpenAI Response: {:?}", response_body);
 
-            if let Some(error) = response_body.get("error") {
-                return Err(anyhow!("OpenAI API error: {:?}", error));
-            }
-
             let content = response_body["choices"][0]["message"]["content"]
                 .as_str()
-                .ok_or_else(|| anyhow!("Failed to extract content from OpenAI response"))?
+                .ok_or_else(|| EngineError::malformed("OpenAI", "missing message content"))?
                 .to_string();
 
             let usage = Usage {
@@ -307,7 +318,33 @@ impl Engine for OpenAIEngine {
                 usage,
                 model,
                 finish_reason,
+                ..Default::default()
             })
         })
     }
+
+    fn execute_stream<'a>(
+        &'a self,
+        request: &'a Request,
+    ) -> Box<dyn Future<Output = Result<ResponseStream<'a>>> + Send + 'a> {
+        Box::new(async move {
+            let client = Client::new();
+
+            let mut payload = self.build_payload(request)?;
+            payload["stream"] = json!(true);
+            payload["stream_options"] = json!({ "include_usage": true });
+            debug!("OpenAI Streaming Payload: {:#?}", payload);
+
+            let res = client
+                .post(self.url())
+                .header("Authorization", format!("Bearer {}", self.auth_token()?))
+                .header("Content-Type", "application/json")
+                .json(&payload)
+                .send_checked("OpenAI")
+                .await?;
+
+            let stream = sse_json_events(res).map(|event| event.map(|value| openai_chunk(&value)));
+            Ok(Box::pin(stream) as ResponseStream<'a>)
+        })
+    }
 }
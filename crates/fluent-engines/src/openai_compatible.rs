@@ -0,0 +1,454 @@
+// crates/fluent-engines/src/openai_compatible.rs
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use base64::engine::general_purpose::STANDARD as Base64;
+use base64::Engine as Base64Engine;
+use fluent_core::config::EngineConfig;
+use fluent_core::error::{EngineError, RequestBuilderExt};
+use fluent_core::neo4j_client::Neo4jClient;
+use fluent_core::traits::{Engine, EngineConfigProcessor, OpenAIConfigProcessor, ResponseStream};
+use fluent_core::types::{
+    ExtractedContent, Message, Request, Response, ResponseChunk, Role, ToolCall, UpsertRequest,
+    UpsertResponse, Usage,
+};
+use futures::StreamExt;
+use log::debug;
+use reqwest::{Client, RequestBuilder};
+use serde_json::{json, Map, Value};
+use tokio::fs;
+
+use crate::streaming::{json_object_events, openai_chunk, sse_json_events};
+
+/// Which HTTP API the server speaks, from the `api` parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Api {
+    /// `/v1/chat/completions`, as served by vLLM, llama.cpp, LM Studio and Ollama.
+    OpenAI,
+    /// Ollama's native `/api/chat`.
+    Ollama,
+}
+
+/// An engine for self-hosted servers. Unlike `OpenAIEngine` the bearer token
+/// is optional, extra headers come from the `headers` parameter, and missing
+/// usage figures are tolerated.
+pub struct OpenAICompatibleEngine {
+    config: EngineConfig,
+    client: Client,
+    api: Api,
+    neo4j_client: Option<Arc<Neo4jClient>>,
+}
+
+/// Accepts numbers given as strings, as the config files often do.
+fn numeric(value: &Value) -> Value {
+    value
+        .as_str()
+        .and_then(|s| s.parse::<f64>().ok())
+        .and_then(|n| serde_json::Number::from_f64(n).map(Value::Number))
+        .unwrap_or_else(|| value.clone())
+}
+
+fn openai_usage(usage: &Value) -> Usage {
+    let prompt_tokens = usage["prompt_tokens"].as_u64().unwrap_or(0) as u32;
+    let completion_tokens = usage["completion_tokens"].as_u64().unwrap_or(0) as u32;
+    Usage {
+        prompt_tokens,
+        completion_tokens,
+        total_tokens: usage["total_tokens"]
+            .as_u64()
+            .map(|total| total as u32)
+            .unwrap_or(prompt_tokens + completion_tokens),
+    }
+}
+
+fn ollama_usage(body: &Value) -> Usage {
+    let prompt_tokens = body["prompt_eval_count"].as_u64().unwrap_or(0) as u32;
+    let completion_tokens = body["eval_count"].as_u64().unwrap_or(0) as u32;
+    Usage {
+        prompt_tokens,
+        completion_tokens,
+        total_tokens: prompt_tokens + completion_tokens,
+    }
+}
+
+/// Reads an image file as base64, with its MIME type guessed from the
+/// extension.
+pub(crate) async fn read_image(file_path: &Path) -> Result<(String, mime_guess::Mime)> {
+    let image = Base64.encode(fs::read(file_path).await.context("Failed to read file")?);
+    let mime = mime_guess::from_path(file_path).first_or(mime_guess::mime::IMAGE_PNG);
+    Ok((image, mime))
+}
+
+/// Chat completions `messages` for a request sent with an image: the
+/// earlier turns, then a user turn with the prompt and the image as a data URL.
+pub(crate) fn image_messages(request: &Request, image: &str, mime: &mime_guess::Mime) -> Value {
+    let mut messages = OpenAIConfigProcessor::map_messages(&request.messages);
+    let image_turn = json!({
+        "role": "user",
+        "content": [
+            { "type": "text", "text": request.payload },
+            {
+                "type": "image_url",
+                "image_url": { "url": format!("data:{};base64,{}", mime, image) },
+            },
+        ],
+    });
+    match messages.as_array_mut() {
+        Some(messages) => messages.push(image_turn),
+        None => messages = json!([image_turn]),
+    }
+    messages
+}
+
+fn ollama_messages(messages: &[Message]) -> Vec<Value> {
+    messages
+        .iter()
+        .map(|m| match m.role {
+            Role::Assistant if !m.tool_calls.is_empty() => json!({
+                "role": "assistant",
+                "content": m.content,
+                "tool_calls": m.tool_calls.iter().map(|call| json!({
+                    "function": { "name": call.name, "arguments": call.arguments },
+                })).collect::<Vec<_>>(),
+            }),
+            _ => json!({ "role": m.role.as_str(), "content": m.content }),
+        })
+        .collect()
+}
+
+/// Ollama does not give tool calls an ID, so the function name stands in.
+fn ollama_tool_calls(message: &Value) -> Vec<ToolCall> {
+    message["tool_calls"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|call| {
+            let name = call["function"]["name"].as_str().unwrap_or_default().to_string();
+            let arguments = match &call["function"]["arguments"] {
+                Value::String(raw) => {
+                    serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.clone()))
+                }
+                arguments => arguments.clone(),
+            };
+            ToolCall {
+                id: call["id"].as_str().map_or_else(|| name.clone(), String::from),
+                name,
+                arguments,
+            }
+        })
+        .collect()
+}
+
+fn ollama_chunk(value: &Value) -> Result<ResponseChunk> {
+    if value.get("error").is_some() {
+        return Err(EngineError::from_error_body("Ollama", value).into());
+    }
+    let done = value["done"].as_bool().unwrap_or(false);
+    Ok(ResponseChunk {
+        delta: value["message"]["content"].as_str().unwrap_or_default().to_string(),
+        usage: done.then(|| ollama_usage(value)),
+        model: value["model"].as_str().map(String::from),
+        finish_reason: value["done_reason"].as_str().map(String::from),
+    })
+}
+
+impl OpenAICompatibleEngine {
+    pub async fn new(config: EngineConfig) -> Result<Self> {
+        let api = match config.parameters.get("api").and_then(|v| v.as_str()) {
+            None | Some("openai") => Api::OpenAI,
+            Some("ollama") => Api::Ollama,
+            Some(other) => return Err(anyhow!("Unknown api '{}': expected openai or ollama", other)),
+        };
+
+        let neo4j_client = if let Some(neo4j_config) = &config.neo4j {
+            Some(Arc::new(Neo4jClient::new(neo4j_config).await?))
+        } else {
+            None
+        };
+
+        Ok(Self {
+            config,
+            client: Client::new(),
+            api,
+            neo4j_client,
+        })
+    }
+
+    fn provider(&self) -> &'static str {
+        match self.api {
+            Api::OpenAI => "OpenAI-compatible server",
+            Api::Ollama => "Ollama",
+        }
+    }
+
+    fn model(&self) -> Result<&str> {
+        self.config
+            .parameters
+            .get("modelName")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow!("Model not specified or not a string"))
+    }
+
+    fn url(&self) -> String {
+        let connection = &self.config.connection;
+        let path = match (connection.request_path.as_str(), self.api) {
+            ("", Api::OpenAI) => "/v1/chat/completions",
+            ("", Api::Ollama) => "/api/chat",
+            (path, _) => path,
+        };
+        format!(
+            "{}://{}:{}{}",
+            connection.protocol, connection.hostname, connection.port, path
+        )
+    }
+
+    /// A POST to the server with the optional bearer token and any
+    /// configured extra headers.
+    fn post(&self) -> RequestBuilder {
+        let mut builder = self
+            .client
+            .post(self.url())
+            .header("Content-Type", "application/json");
+
+        if let Some(token) = self
+            .config
+            .parameters
+            .get("bearer_token")
+            .and_then(|v| v.as_str())
+            .filter(|token| !token.is_empty())
+        {
+            builder = builder.bearer_auth(token);
+        }
+        if let Some(headers) = self.config.parameters.get("headers").and_then(|v| v.as_object()) {
+            for (name, value) in headers {
+                let value = value.as_str().map_or_else(|| value.to_string(), String::from);
+                builder = builder.header(name.as_str(), value);
+            }
+        }
+        builder
+    }
+
+    fn openai_payload(&self, messages: Value, request: &Request) -> Result<Value> {
+        let mut payload = OpenAIConfigProcessor.process_config(&self.config)?;
+        payload["messages"] = messages;
+        if !request.tools.is_empty() {
+            payload["tools"] = OpenAIConfigProcessor::map_tools(&request.tools);
+        }
+        if let Some(format) =
+            OpenAIConfigProcessor::response_format(request.response_schema.as_ref())
+        {
+            payload["response_format"] = format;
+        }
+        Ok(payload)
+    }
+
+    fn ollama_payload(&self, messages: Vec<Value>, request: &Request, stream: bool) -> Result<Value> {
+        let mut options = Map::new();
+        for (param, option) in [
+            ("temperature", "temperature"),
+            ("top_p", "top_p"),
+            ("top_k", "top_k"),
+            ("max_tokens", "num_predict"),
+            ("num_ctx", "num_ctx"),
+            ("seed", "seed"),
+            ("stop", "stop"),
+        ] {
+            if let Some(value) = self.config.parameters.get(param) {
+                options.insert(option.to_string(), numeric(value));
+            }
+        }
+
+        let mut payload = json!({
+            "model": self.model()?,
+            "messages": messages,
+            "options": options,
+            "stream": stream,
+        });
+        if !request.tools.is_empty() {
+            payload["tools"] = OpenAIConfigProcessor::map_tools(&request.tools);
+        }
+        if request.response_schema.is_some() {
+            payload["format"] = json!("json");
+        }
+        Ok(payload)
+    }
+
+    async fn send(&self, payload: &Value) -> Result<Value> {
+        debug!("{} payload: {:#?}", self.provider(), payload);
+        let body = self.post().json(payload).send_json(self.provider()).await?;
+        debug!("{} response: {:?}", self.provider(), body);
+        Ok(body)
+    }
+
+    fn parse_openai(&self, body: &Value) -> Result<Response> {
+        let choice = &body["choices"][0];
+        let message = &choice["message"];
+        let tool_calls = OpenAIConfigProcessor::parse_tool_calls(message);
+        // Older completion-style servers put the text in `text`.
+        let content = match message["content"].as_str().or_else(|| choice["text"].as_str()) {
+            Some(content) => content.to_string(),
+            None if !tool_calls.is_empty() => String::new(),
+            None => {
+                return Err(EngineError::malformed(self.provider(), "missing message content").into())
+            }
+        };
+
+        Ok(Response {
+            content,
+            usage: openai_usage(&body["usage"]),
+            model: body["model"]
+                .as_str()
+                .map_or_else(|| self.model().unwrap_or("unknown").to_string(), String::from),
+            finish_reason: choice["finish_reason"].as_str().map(String::from),
+            tool_calls,
+            ..Default::default()
+        })
+    }
+
+    fn parse_ollama(&self, body: &Value) -> Result<Response> {
+        let message = &body["message"];
+        if !message.is_object() {
+            return Err(EngineError::malformed("Ollama", "missing message").into());
+        }
+        Ok(Response {
+            content: message["content"].as_str().unwrap_or_default().to_string(),
+            usage: ollama_usage(body),
+            model: body["model"]
+                .as_str()
+                .map_or_else(|| self.model().unwrap_or("unknown").to_string(), String::from),
+            finish_reason: body["done_reason"].as_str().map(String::from),
+            tool_calls: ollama_tool_calls(message),
+            ..Default::default()
+        })
+    }
+}
+
+impl Engine for OpenAICompatibleEngine {
+    fn execute<'a>(
+        &'a self,
+        request: &'a Request,
+    ) -> Box<dyn Future<Output = Result<Response>> + Send + 'a> {
+        Box::new(async move {
+            let conversation = request.conversation();
+            match self.api {
+                Api::OpenAI => {
+                    let messages = OpenAIConfigProcessor::map_messages(&conversation);
+                    let body = self.send(&self.openai_payload(messages, request)?).await?;
+                    self.parse_openai(&body)
+                }
+                Api::Ollama => {
+                    let messages = ollama_messages(&conversation);
+                    let body = self.send(&self.ollama_payload(messages, request, false)?).await?;
+                    self.parse_ollama(&body)
+                }
+            }
+        })
+    }
+
+    fn upsert<'a>(
+        &'a self,
+        _request: &'a UpsertRequest,
+    ) -> Box<dyn Future<Output = Result<UpsertResponse>> + Send + 'a> {
+        Box::new(async move {
+            Ok(UpsertResponse {
+                processed_files: vec![],
+                errors: vec![],
+            })
+        })
+    }
+
+    fn get_neo4j_client(&self) -> Option<&Arc<Neo4jClient>> {
+        self.neo4j_client.as_ref()
+    }
+
+    fn get_session_id(&self) -> Option<String> {
+        self.config
+            .parameters
+            .get("sessionID")
+            .and_then(|v| v.as_str())
+            .map(String::from)
+    }
+
+    fn extract_content(&self, value: &Value) -> Option<ExtractedContent> {
+        value["choices"][0]["message"]["content"]
+            .as_str()
+            .or_else(|| value["message"]["content"].as_str())
+            .map(|content| ExtractedContent {
+                main_content: content.to_string(),
+                sentiment: None,
+                clusters: None,
+                themes: None,
+                keywords: None,
+            })
+    }
+
+    fn upload_file<'a>(
+        &'a self,
+        _file_path: &'a Path,
+    ) -> Box<dyn Future<Output = Result<String>> + Send + 'a> {
+        Box::new(async move {
+            Err(anyhow!(
+                "File upload is not supported by {}; send images with the request instead",
+                self.provider()
+            ))
+        })
+    }
+
+    fn process_request_with_file<'a>(
+        &'a self,
+        request: &'a Request,
+        file_path: &'a Path,
+    ) -> Box<dyn Future<Output = Result<Response>> + Send + 'a> {
+        Box::new(async move {
+            let (image, mime) = read_image(file_path).await?;
+
+            match self.api {
+                Api::OpenAI => {
+                    let messages = image_messages(request, &image, &mime);
+                    let body = self.send(&self.openai_payload(messages, request)?).await?;
+                    self.parse_openai(&body)
+                }
+                Api::Ollama => {
+                    let mut messages = ollama_messages(&request.messages);
+                    messages.push(json!({
+                        "role": "user",
+                        "content": request.payload,
+                        "images": [image],
+                    }));
+                    let body = self.send(&self.ollama_payload(messages, request, false)?).await?;
+                    self.parse_ollama(&body)
+                }
+            }
+        })
+    }
+
+    fn execute_stream<'a>(
+        &'a self,
+        request: &'a Request,
+    ) -> Box<dyn Future<Output = Result<ResponseStream<'a>>> + Send + 'a> {
+        Box::new(async move {
+            let conversation = request.conversation();
+            match self.api {
+                Api::OpenAI => {
+                    let messages = OpenAIConfigProcessor::map_messages(&conversation);
+                    let mut payload = self.openai_payload(messages, request)?;
+                    payload["stream"] = json!(true);
+                    payload["stream_options"] = json!({ "include_usage": true });
+                    let response = self.post().json(&payload).send_checked(self.provider()).await?;
+                    let stream =
+                        sse_json_events(response).map(|event| event.map(|value| openai_chunk(&value)));
+                    Ok(Box::pin(stream) as ResponseStream<'a>)
+                }
+                Api::Ollama => {
+                    let payload = self.ollama_payload(ollama_messages(&conversation), request, true)?;
+                    let response = self.post().json(&payload).send_checked("Ollama").await?;
+                    let stream = json_object_events(response)
+                        .map(|event| event.and_then(|value| ollama_chunk(&value)));
+                    Ok(Box::pin(stream) as ResponseStream<'a>)
+                }
+            }
+        })
+    }
+}
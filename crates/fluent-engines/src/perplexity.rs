@@ -7,8 +7,9 @@ use serde_json::{json, Value};
 
 use fluent_core::types::{ExtractedContent, Request, Response, UpsertRequest, UpsertResponse, Usage};
 use fluent_core::neo4j_client::Neo4jClient;
-use fluent_core::traits::Engine;
+use fluent_core::traits::{Engine, OpenAIConfigProcessor};
 use fluent_core::config::EngineConfig;
+use fluent_core::error::{EngineError, RequestBuilderExt};
 use log::debug;
 use reqwest::Client;
 
@@ -33,7 +34,7 @@ impl PerplexityEngine {
         })
     }
 
-    async fn send_perplexity_request(&self, messages: Vec<Value>) -> Result<Value> {
+    async fn send_perplexity_request(&self, messages: Value) -> Result<Value> {
         let url = format!("{}://{}:{}{}",
                           self.config.connection.protocol,
                           self.config.connection.hostname,
@@ -56,21 +57,12 @@ impl PerplexityEngine {
             .and_then(|v| v.as_str())
             .ok_or_else(|| anyhow!("Bearer token not found in configuration"))?;
 
-        let response = self.client.post(&url)
+        let response_body = self.client.post(&url)
             .header("Authorization", format!("Bearer {}", auth_token))
             .header("Content-Type", "application/json")
             .json(&payload)
-            .send()
+            .send_json("Perplexity")
             .await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await?;
-            debug!("Error response body: {}", error_text);
-            return Err(anyhow!("Request failed with status: {}", status));
-        }
-
-        let response_body: Value = response.json().await?;
         debug!("Perplexity Response: {:?}", response_body);
 
         Ok(response_body)
@@ -81,16 +73,13 @@ impl PerplexityEngine {
 impl Engine for PerplexityEngine {
     fn execute<'a>(&'a self, request: &'a Request) -> Box<dyn Future<Output = Result<Response>> + Send + 'a> {
         Box::new(async move {
-            let messages = vec![json!({
-                "role": "user",
-                "content": request.payload
-            })];
+            let messages = OpenAIConfigProcessor::map_messages(&request.conversation());
 
             let response = self.send_perplexity_request(messages).await?;
 
             let content = response["choices"][0]["message"]["content"]
                 .as_str()
-                .ok_or_else(|| anyhow!("Failed to extract content from Perplexity response"))?
+                .ok_or_else(|| EngineError::malformed("Perplexity", "missing content"))?
                 .to_string();
 
             let usage = Usage {
@@ -107,6 +96,7 @@ impl Engine for PerplexityEngine {
                 usage,
                 model,
                 finish_reason,
+                ..Default::default()
             })
         })
     }
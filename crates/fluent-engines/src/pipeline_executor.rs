@@ -13,9 +13,9 @@ use tempfile;
 
 use anyhow::{anyhow, Error};
 use async_trait::async_trait;
+use fluent_core::pricing::{append_cost, take_logged_cost, COST_LOG_ENV};
 use log::{debug, error, info, warn};
 use std::sync::Arc;
-use tokio::process::Command;
 
 use tokio::task::JoinSet;
 use tokio::time::timeout;
@@ -111,6 +111,13 @@ pub struct PipelineState {
     pub data: HashMap<String, String>,
     pub run_id: String,
     pub start_time: u64,
+    /// US dollars spent on priced requests so far in this run.
+    #[serde(default)]
+    pub cost: f64,
+    /// The file this run's `fluent` commands log request costs to. It is set
+    /// for each run and not saved.
+    #[serde(skip)]
+    pub cost_log: Option<PathBuf>,
 }
 
 #[async_trait]
@@ -157,6 +164,8 @@ impl<S: StateStore + Clone + std::marker::Sync + std::marker::Send> PipelineExec
                 data: HashMap::new(),
                 run_id: run_id.clone(),
                 start_time: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+                cost: 0.0,
+                cost_log: None,
             }
         } else {
             match self.state_store.load_state(&state_key).await? {
@@ -174,6 +183,8 @@ impl<S: StateStore + Clone + std::marker::Sync + std::marker::Send> PipelineExec
                         data: HashMap::new(),
                         run_id: run_id.clone(),
                         start_time: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+                        cost: 0.0,
+                        cost_log: None,
                     }
                 }
             }
@@ -186,6 +197,44 @@ impl<S: StateStore + Clone + std::marker::Sync + std::marker::Send> PipelineExec
         }
         state.data.insert("run_id".to_string(), run_id.clone());
 
+        // Requests made by the steps' `fluent` commands append their cost to
+        // this log. A nested pipeline's total is passed up to the parent log.
+        let cost_log = std::env::temp_dir().join(format!("fluent-cost-{}.log", state_key));
+        if force_fresh {
+            let _ = std::fs::remove_file(&cost_log);
+        }
+        let parent_cost_log = std::env::var_os(COST_LOG_ENV);
+        state.cost_log = Some(cost_log.clone());
+        self.execute_steps(pipeline, &state_key, &mut state, &cost_log)
+            .await?;
+        if let Some(parent) = parent_cost_log {
+            append_cost(Path::new(&parent), state.cost)?;
+        }
+
+        let end_time = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let runtime = end_time - state.start_time;
+
+        let output = serde_json::json!({
+            "pipeline_name": pipeline.name,
+            "run_id": run_id,
+            "current_step": state.current_step,
+            "start_time": state.start_time,
+            "end_time": end_time,
+            "runtime_seconds": runtime,
+            "cost": state.cost,
+            "data": state.data,
+        });
+
+        Ok(serde_json::to_string_pretty(&output)?)
+    }
+
+    async fn execute_steps(
+        &self,
+        pipeline: &Pipeline,
+        state_key: &str,
+        state: &mut PipelineState,
+        cost_log: &Path,
+    ) -> Result<(), Error> {
         for (index, step) in pipeline.steps.iter().enumerate().skip(state.current_step) {
             debug!("Processing step {} (index {})", step.name(), index);
 
@@ -194,33 +243,22 @@ impl<S: StateStore + Clone + std::marker::Sync + std::marker::Send> PipelineExec
                 .insert("step".to_string(), step.name().to_string());
             state.current_step = index;
 
-            match self.execute_step(step, &mut state).await {
+            let result = self.execute_step(step, state).await;
+            state.cost += take_logged_cost(cost_log)?;
+            match result {
                 Ok(step_result) => {
                     info!("Step {} completed successfully", step.name());
                     state.data.extend(step_result);
-                    self.state_store.save_state(&state_key, &state).await?;
+                    self.state_store.save_state(state_key, state).await?;
                 }
                 Err(e) => {
                     error!("Error executing step {}: {:?}", step.name(), e);
+                    self.state_store.save_state(state_key, state).await?;
                     return Err(e);
                 }
             }
         }
-
-        let end_time = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
-        let runtime = end_time - state.start_time;
-
-        let output = serde_json::json!({
-            "pipeline_name": pipeline.name,
-            "run_id": run_id,
-            "current_step": state.current_step,
-            "start_time": state.start_time,
-            "end_time": end_time,
-            "runtime_seconds": runtime,
-            "data": state.data,
-        });
-
-        Ok(serde_json::to_string_pretty(&output)?)
+        Ok(())
     }
 
     fn execute_step<'a>(
@@ -239,8 +277,13 @@ impl<S: StateStore + Clone + std::marker::Sync + std::marker::Send> PipelineExec
                     debug!("Executing Command step: {}", name);
                     debug!("Command: {}", command);
                     let expanded_command = self.expand_variables(command, &state.data).await?;
-                    self.execute_command(&expanded_command, save_output, retry)
-                        .await
+                    self.execute_command(
+                        &expanded_command,
+                        save_output,
+                        retry,
+                        state.cost_log.as_deref(),
+                    )
+                    .await
                 }
 
                 PipelineStep::ShellCommand {
@@ -252,8 +295,13 @@ impl<S: StateStore + Clone + std::marker::Sync + std::marker::Send> PipelineExec
                     debug!("Executing ShellCommand step: {}", name);
                     debug!("Command: {}", command);
                     let expanded_command = self.expand_variables(command, &state.data).await?;
-                    self.execute_shell_command(&expanded_command, save_output, retry)
-                        .await
+                    self.execute_shell_command(
+                        &expanded_command,
+                        save_output,
+                        retry,
+                        state.cost_log.as_deref(),
+                    )
+                    .await
                 }
 
                 PipelineStep::Condition {
@@ -265,16 +313,29 @@ impl<S: StateStore + Clone + std::marker::Sync + std::marker::Send> PipelineExec
                     debug!("Evaluating Condition step: {}", name);
                     debug!("Condition: {}", condition);
                     let expanded_condition = self.expand_variables(condition, &state.data).await?;
-                    if self.evaluate_condition(&expanded_condition).await? {
+                    if self
+                        .evaluate_condition(&expanded_condition, state.cost_log.as_deref())
+                        .await?
+                    {
                         debug!("Condition is true, executing: {}", if_true);
                         let expanded_command = self.expand_variables(if_true, &state.data).await?;
-                        self.execute_shell_command(&expanded_command, &None, &None)
-                            .await
+                        self.execute_shell_command(
+                            &expanded_command,
+                            &None,
+                            &None,
+                            state.cost_log.as_deref(),
+                        )
+                        .await
                     } else {
                         debug!("Condition is false, executing: {}", if_false);
                         let expanded_command = self.expand_variables(if_false, &state.data).await?;
-                        self.execute_shell_command(&expanded_command, &None, &None)
-                            .await
+                        self.execute_shell_command(
+                            &expanded_command,
+                            &None,
+                            &None,
+                            state.cost_log.as_deref(),
+                        )
+                        .await
                     }
                 }
 
@@ -307,7 +368,12 @@ impl<S: StateStore + Clone + std::marker::Sync + std::marker::Send> PipelineExec
                         let item_command = expanded_command.replace("${ITEM}", item);
                         debug!("Executing command: {}", item_command);
                         match self
-                            .execute_shell_command(&item_command, &None, &None)
+                            .execute_shell_command(
+                                &item_command,
+                                &None,
+                                &None,
+                                state.cost_log.as_deref(),
+                            )
                             .await
                         {
                             Ok(output) => {
@@ -361,7 +427,10 @@ impl<S: StateStore + Clone + std::marker::Sync + std::marker::Send> PipelineExec
 
                         let expanded_condition =
                             self.expand_variables(condition, &state.data).await?;
-                        if self.evaluate_condition(&expanded_condition).await? {
+                        if self
+                            .evaluate_condition(&expanded_condition, state.cost_log.as_deref())
+                            .await?
+                        {
                             break;
                         }
                     }
@@ -576,7 +645,11 @@ impl<S: StateStore + Clone + std::marker::Sync + std::marker::Send> PipelineExec
                     save_output,
                     retry: _,
                 } => {
-                    let output = Command::new("sh").arg("-c").arg(command).output().await?;
+                    let output = step_command("sh", state.cost_log.as_deref())
+                        .arg("-c")
+                        .arg(command)
+                        .output()
+                        .await?;
 
                     let stdout = String::from_utf8(output.stdout)?;
                     let mut result = HashMap::new();
@@ -591,7 +664,11 @@ impl<S: StateStore + Clone + std::marker::Sync + std::marker::Send> PipelineExec
                     save_output,
                     retry: _,
                 } => {
-                    let output = Command::new("sh").arg("-c").arg(command).output().await?;
+                    let output = step_command("sh", state.cost_log.as_deref())
+                        .arg("-c")
+                        .arg(command)
+                        .output()
+                        .await?;
 
                     let stdout = String::from_utf8(output.stdout)?;
                     let mut result = HashMap::new();
@@ -606,7 +683,7 @@ impl<S: StateStore + Clone + std::marker::Sync + std::marker::Send> PipelineExec
                     if_true,
                     if_false,
                 } => {
-                    let condition_result = Command::new("sh")
+                    let condition_result = step_command("sh", state.cost_log.as_deref())
                         .arg("-c")
                         .arg(condition)
                         .status()
@@ -614,7 +691,7 @@ impl<S: StateStore + Clone + std::marker::Sync + std::marker::Send> PipelineExec
                         .success();
 
                     let command_to_run = if condition_result { if_true } else { if_false };
-                    let output = Command::new("sh")
+                    let output = step_command("sh", state.cost_log.as_deref())
                         .arg("-c")
                         .arg(command_to_run)
                         .output()
@@ -639,7 +716,7 @@ impl<S: StateStore + Clone + std::marker::Sync + std::marker::Send> PipelineExec
                             state.data.extend(step_result);
                         }
 
-                        let condition_result = Command::new("sh")
+                        let condition_result = step_command("sh", state.cost_log.as_deref())
                             .arg("-c")
                             .arg(condition)
                             .status()
@@ -741,6 +818,7 @@ impl<S: StateStore + Clone + std::marker::Sync + std::marker::Send> PipelineExec
         command: &str,
         save_output: &Option<String>,
         retry: &Option<RetryConfig>,
+        cost_log: Option<&Path>,
     ) -> Result<HashMap<String, String>, Error> {
         debug!("Executing command: {}", command);
         let retry_config = retry.clone().unwrap_or(RetryConfig {
@@ -751,7 +829,7 @@ impl<S: StateStore + Clone + std::marker::Sync + std::marker::Send> PipelineExec
 
         loop {
             debug!("Attempt {} to execute command", attempts + 1);
-            match self.run_command(command, save_output).await {
+            match self.run_command(command, save_output, cost_log).await {
                 Ok(output) => {
                     debug!("Command executed successfully");
                     return Ok(output);
@@ -778,9 +856,10 @@ impl<S: StateStore + Clone + std::marker::Sync + std::marker::Send> PipelineExec
         &self,
         command: &str,
         save_output: &Option<String>,
+        cost_log: Option<&Path>,
     ) -> Result<HashMap<String, String>, Error> {
         debug!("Running command: {}", command);
-        let output = TokioCommand::new("sh")
+        let output = step_command("sh", cost_log)
             .arg("-c")
             .arg(command)
             .output()
@@ -815,6 +894,7 @@ impl<S: StateStore + Clone + std::marker::Sync + std::marker::Send> PipelineExec
         command: &str,
         save_output: &Option<String>,
         retry: &Option<RetryConfig>,
+        cost_log: Option<&Path>,
     ) -> Result<HashMap<String, String>, Error> {
         debug!("Executing shell command: {}", command);
 
@@ -830,7 +910,7 @@ impl<S: StateStore + Clone + std::marker::Sync + std::marker::Send> PipelineExec
 
         loop {
             debug!("Attempt {} to execute shell command", attempts + 1);
-            match self.run_shell_command(temp_file.path()).await {
+            match self.run_shell_command(temp_file.path(), cost_log).await {
                 Ok(output) => {
                     debug!("Shell command executed successfully: {:?}", output);
                     let mut result = HashMap::new();
@@ -859,9 +939,13 @@ impl<S: StateStore + Clone + std::marker::Sync + std::marker::Send> PipelineExec
         }
     }
 
-    async fn run_shell_command(&self, script_path: &Path) -> Result<String, Error> {
+    async fn run_shell_command(
+        &self,
+        script_path: &Path,
+        cost_log: Option<&Path>,
+    ) -> Result<String, Error> {
         debug!("Running shell command from file: {:?}", script_path);
-        let output = TokioCommand::new("bash")
+        let output = step_command("bash", cost_log)
             .arg(script_path)
             .output()
             .await
@@ -884,13 +968,17 @@ impl<S: StateStore + Clone + std::marker::Sync + std::marker::Send> PipelineExec
         Ok(stdout.trim().to_string())
     }
 
-    async fn evaluate_condition(&self, condition: &str) -> Result<bool, Error> {
+    async fn evaluate_condition(
+        &self,
+        condition: &str,
+        cost_log: Option<&Path>,
+    ) -> Result<bool, Error> {
         let expanded_condition = self
             .expand_variables(condition, &Default::default())
             .await?;
         debug!("Evaluating expanded condition: {}", expanded_condition);
 
-        let output = TokioCommand::new("bash")
+        let output = step_command("bash", cost_log)
             .arg("-c")
             .arg(format!(
                 "if {}; then exit 0; else exit 1; fi",
@@ -916,6 +1004,16 @@ impl<S: StateStore + Clone + std::marker::Sync + std::marker::Send> PipelineExec
     }
 }
 
+/// A command for a step. Requests made by `fluent` commands it runs append
+/// their cost to `cost_log`.
+fn step_command(program: &str, cost_log: Option<&Path>) -> TokioCommand {
+    let mut command = TokioCommand::new(program);
+    if let Some(cost_log) = cost_log {
+        command.env(COST_LOG_ENV, cost_log);
+    }
+    command
+}
+
 impl PipelineStep {
     fn name(&self) -> &str {
         match self {
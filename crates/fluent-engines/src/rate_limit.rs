@@ -0,0 +1,243 @@
+// crates/fluent-engines/src/rate_limit.rs
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use fluent_core::config::RateLimitConfig;
+use fluent_core::neo4j_client::Neo4jClient;
+use fluent_core::traits::{Engine, ResponseStream};
+use fluent_core::types::{ExtractedContent, Request, Response, UpsertRequest, UpsertResponse};
+use futures::StreamExt;
+use log::debug;
+use serde_json::Value;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// A bucket holding up to a minute's allowance, refilled continuously.
+struct TokenBucket {
+    capacity: f64,
+    per_second: f64,
+    available: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn per_minute(limit: u32) -> Self {
+        let capacity = f64::from(limit.max(1));
+        Self {
+            capacity,
+            per_second: capacity / 60.0,
+            available: capacity,
+            updated: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.available = (self.available + elapsed * self.per_second).min(self.capacity);
+        self.updated = now;
+    }
+
+    /// How long until `amount` is available. Amounts over the capacity only
+    /// wait for a full bucket, so an oversized request still gets through.
+    fn wait_for(&mut self, amount: f64) -> Duration {
+        self.refill();
+        let missing = amount.min(self.capacity) - self.available;
+        if missing <= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(missing / self.per_second)
+        }
+    }
+
+    /// Takes `amount`, which may leave the bucket in debt.
+    fn take(&mut self, amount: f64) {
+        self.available = (self.available - amount).min(self.capacity);
+    }
+}
+
+/// Request, token and concurrency limits for one configured engine.
+pub struct RateLimiter {
+    requests: Option<Mutex<TokenBucket>>,
+    tokens: Option<Mutex<TokenBucket>>,
+    in_flight: Option<Arc<Semaphore>>,
+}
+
+static LIMITERS: OnceLock<Mutex<HashMap<String, Arc<RateLimiter>>>> = OnceLock::new();
+
+impl RateLimiter {
+    pub fn new(config: &RateLimitConfig) -> Self {
+        Self {
+            requests: config.requests_per_minute.map(|rpm| Mutex::new(TokenBucket::per_minute(rpm))),
+            tokens: config.tokens_per_minute.map(|tpm| Mutex::new(TokenBucket::per_minute(tpm))),
+            in_flight: config.max_in_flight.map(|max| Arc::new(Semaphore::new(max.max(1)))),
+        }
+    }
+
+    /// The process-wide limiter for the engine called `name`, created from
+    /// `config` on first use.
+    pub fn shared(name: &str, config: &RateLimitConfig) -> Arc<RateLimiter> {
+        let mut limiters = LIMITERS
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        limiters
+            .entry(name.to_string())
+            .or_insert_with(|| Arc::new(RateLimiter::new(config)))
+            .clone()
+    }
+
+    /// Waits for a free slot and for enough request and token allowance,
+    /// then takes them. The returned permit holds the slot until dropped.
+    pub async fn acquire(&self, estimated_tokens: u32) -> Option<OwnedSemaphorePermit> {
+        let permit = match &self.in_flight {
+            Some(semaphore) => Some(
+                semaphore
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .expect("rate limit semaphore is never closed"),
+            ),
+            None => None,
+        };
+
+        loop {
+            let wait = {
+                let mut requests = self.requests.as_ref().map(lock);
+                let mut tokens = self.tokens.as_ref().map(lock);
+                let wait = requests
+                    .as_mut()
+                    .map_or(Duration::ZERO, |bucket| bucket.wait_for(1.0))
+                    .max(
+                        tokens
+                            .as_mut()
+                            .map_or(Duration::ZERO, |bucket| bucket.wait_for(f64::from(estimated_tokens))),
+                    );
+                if wait.is_zero() {
+                    if let Some(bucket) = requests.as_mut() {
+                        bucket.take(1.0);
+                    }
+                    if let Some(bucket) = tokens.as_mut() {
+                        bucket.take(f64::from(estimated_tokens));
+                    }
+                    return permit;
+                }
+                wait
+            };
+            debug!("Rate limit reached, waiting {:.2}s", wait.as_secs_f64());
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Corrects the token allowance once the provider reports actual usage.
+    pub fn record_usage(&self, estimated_tokens: u32, used_tokens: u32) {
+        if used_tokens == 0 {
+            return;
+        }
+        if let Some(tokens) = &self.tokens {
+            lock(tokens).take(f64::from(used_tokens) - f64::from(estimated_tokens));
+        }
+    }
+}
+
+fn lock(bucket: &Mutex<TokenBucket>) -> std::sync::MutexGuard<'_, TokenBucket> {
+    bucket.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Rough prompt size in tokens, at about four characters per token.
+fn estimate_tokens(request: &Request) -> u32 {
+    let chars: usize = request
+        .conversation()
+        .iter()
+        .map(|message| message.content.len())
+        .sum();
+    (chars / 4) as u32 + 1
+}
+
+/// Wraps an engine so every request waits for the limits in its
+/// `RateLimitConfig`, which are shared with all engines of the same name.
+pub struct RateLimitedEngine {
+    inner: Box<dyn Engine>,
+    limiter: Arc<RateLimiter>,
+}
+
+impl RateLimitedEngine {
+    pub fn new(inner: Box<dyn Engine>, limiter: Arc<RateLimiter>) -> Self {
+        Self { inner, limiter }
+    }
+}
+
+impl Engine for RateLimitedEngine {
+    fn execute<'a>(
+        &'a self,
+        request: &'a Request,
+    ) -> Box<dyn Future<Output = Result<Response>> + Send + 'a> {
+        Box::new(async move {
+            let estimate = estimate_tokens(request);
+            let _permit = self.limiter.acquire(estimate).await;
+            let response = Pin::from(self.inner.execute(request)).await?;
+            self.limiter.record_usage(estimate, response.usage.total_tokens);
+            Ok(response)
+        })
+    }
+
+    fn upsert<'a>(
+        &'a self,
+        request: &'a UpsertRequest,
+    ) -> Box<dyn Future<Output = Result<UpsertResponse>> + Send + 'a> {
+        self.inner.upsert(request)
+    }
+
+    fn get_neo4j_client(&self) -> Option<&Arc<Neo4jClient>> {
+        self.inner.get_neo4j_client()
+    }
+
+    fn get_session_id(&self) -> Option<String> {
+        self.inner.get_session_id()
+    }
+
+    fn extract_content(&self, value: &Value) -> Option<ExtractedContent> {
+        self.inner.extract_content(value)
+    }
+
+    fn upload_file<'a>(
+        &'a self,
+        file_path: &'a Path,
+    ) -> Box<dyn Future<Output = Result<String>> + Send + 'a> {
+        self.inner.upload_file(file_path)
+    }
+
+    fn process_request_with_file<'a>(
+        &'a self,
+        request: &'a Request,
+        file_path: &'a Path,
+    ) -> Box<dyn Future<Output = Result<Response>> + Send + 'a> {
+        Box::new(async move {
+            let estimate = estimate_tokens(request);
+            let _permit = self.limiter.acquire(estimate).await;
+            let response = Pin::from(self.inner.process_request_with_file(request, file_path)).await?;
+            self.limiter.record_usage(estimate, response.usage.total_tokens);
+            Ok(response)
+        })
+    }
+
+    fn execute_stream<'a>(
+        &'a self,
+        request: &'a Request,
+    ) -> Box<dyn Future<Output = Result<ResponseStream<'a>>> + Send + 'a> {
+        Box::new(async move {
+            let permit = self.limiter.acquire(estimate_tokens(request)).await;
+            let stream = Pin::from(self.inner.execute_stream(request)).await?;
+            // The slot stays taken until the stream is dropped.
+            let stream: ResponseStream<'a> = Box::pin(stream.map(move |chunk| {
+                let _held = &permit;
+                chunk
+            }));
+            Ok(stream)
+        })
+    }
+}
@@ -0,0 +1,160 @@
+// crates/fluent-engines/src/registry.rs
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use anyhow::{anyhow, Result};
+use fluent_core::config::EngineConfig;
+use fluent_core::traits::Engine;
+use strum::IntoEnumIterator;
+
+use crate::anthropic::AnthropicEngine;
+use crate::azure_openai::AzureOpenAIEngine;
+use crate::cohere::CohereEngine;
+use crate::dalle::DalleEngine;
+use crate::fallback::FallbackEngine;
+use crate::flowise_chain::FlowiseChainEngine;
+use crate::google_gemini::GoogleGeminiEngine;
+use crate::groqlpu::GroqLPUEngine;
+use crate::imagepro::ImagineProEngine;
+use crate::langflow::LangflowEngine;
+use crate::leonardoai::LeonardoAIEngine;
+use crate::mistral::MistralEngine;
+use crate::openai::OpenAIEngine;
+use crate::openai_compatible::OpenAICompatibleEngine;
+use crate::perplexity::PerplexityEngine;
+use crate::replay::ReplayEngine;
+use crate::stabilityai::StabilityAIEngine;
+use crate::webhook::WebhookEngine;
+use crate::EngineType;
+
+pub type EngineFuture = Pin<Box<dyn Future<Output = Result<Box<dyn Engine>>> + Send>>;
+
+/// Builds an engine from its config.
+pub type EngineFactory = Arc<dyn Fn(EngineConfig) -> EngineFuture + Send + Sync>;
+
+/// Engine factories by the name used in the `engine` field of a config.
+///
+/// The process-wide registry starts with the built-in engines; other crates
+/// add their own with `register_engine` before calling `create_engine`.
+#[derive(Clone, Default)]
+pub struct EngineRegistry {
+    factories: HashMap<String, EngineFactory>,
+}
+
+fn boxed<E, Fut>(future: Fut) -> EngineFuture
+where
+    E: Engine + 'static,
+    Fut: Future<Output = Result<E>> + Send + 'static,
+{
+    Box::pin(async move { Ok(Box::new(future.await?) as Box<dyn Engine>) })
+}
+
+fn builtin_factory(engine_type: EngineType) -> EngineFactory {
+    match engine_type {
+        EngineType::OpenAI => Arc::new(|config| boxed(OpenAIEngine::new(config))),
+        EngineType::Anthropic => Arc::new(|config| boxed(AnthropicEngine::new(config))),
+        EngineType::GoogleGemini => Arc::new(|config| boxed(GoogleGeminiEngine::new(config))),
+        EngineType::Cohere => Arc::new(|config| boxed(CohereEngine::new(config))),
+        EngineType::GroqLpu => Arc::new(|config| boxed(GroqLPUEngine::new(config))),
+        EngineType::Mistral => Arc::new(|config| boxed(MistralEngine::new(config))),
+        EngineType::Perplexity => Arc::new(|config| boxed(PerplexityEngine::new(config))),
+        EngineType::FlowiseChain => Arc::new(|config| boxed(FlowiseChainEngine::new(config))),
+        EngineType::LangflowChain => Arc::new(|config| boxed(LangflowEngine::new(config))),
+        EngineType::Webhook => Arc::new(|config| boxed(WebhookEngine::new(config))),
+        EngineType::StabilityAI => Arc::new(|config| boxed(StabilityAIEngine::new(config))),
+        EngineType::ImaginePro => Arc::new(|config| boxed(ImagineProEngine::new(config))),
+        EngineType::LeonardoAI => Arc::new(|config| boxed(LeonardoAIEngine::new(config))),
+        EngineType::Dalle => Arc::new(|config| boxed(DalleEngine::new(config))),
+        EngineType::Fallback => Arc::new(|config| boxed(FallbackEngine::new(config))),
+        EngineType::Replay => Arc::new(|config| boxed(ReplayEngine::new(config))),
+        EngineType::OpenAICompatible => {
+            Arc::new(|config| boxed(OpenAICompatibleEngine::new(config)))
+        }
+        EngineType::AzureOpenAI => Arc::new(|config| boxed(AzureOpenAIEngine::new(config))),
+    }
+}
+
+impl EngineRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry holding every engine in `EngineType`.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        for engine_type in EngineType::iter() {
+            registry
+                .factories
+                .insert(engine_type.to_string(), builtin_factory(engine_type));
+        }
+        registry
+    }
+
+    /// Adds a factory under `name`, replacing any engine already registered
+    /// with that name. Names are matched case-insensitively.
+    pub fn register<F, Fut, E>(&mut self, name: &str, factory: F) -> &mut Self
+    where
+        F: Fn(EngineConfig) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<E>> + Send + 'static,
+        E: Engine + 'static,
+    {
+        let factory: EngineFactory = Arc::new(move |config| boxed(factory(config)));
+        self.factories.insert(name.to_lowercase(), factory);
+        self
+    }
+
+    /// Finds the factory for an engine name, accepting the alternate
+    /// spellings of built-in names (e.g. `googlegemini`).
+    pub fn factory(&self, name: &str) -> Option<EngineFactory> {
+        let name = name.to_lowercase();
+        self.factories
+            .get(&name)
+            .or_else(|| {
+                let canonical = EngineType::from_str(&name).ok()?.to_string();
+                self.factories.get(&canonical)
+            })
+            .cloned()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.factory(name).is_some()
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.factories.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Builds the engine named by `config.engine`, without the layers that
+    /// `create_engine` adds.
+    pub async fn create(&self, config: &EngineConfig) -> Result<Box<dyn Engine>> {
+        let factory = self
+            .factory(&config.engine)
+            .ok_or_else(|| anyhow!("Unsupported engine: {}", config.engine))?;
+        factory(config.clone()).await
+    }
+}
+
+/// The process-wide registry used by `create_engine`.
+pub fn global_registry() -> &'static RwLock<EngineRegistry> {
+    static REGISTRY: OnceLock<RwLock<EngineRegistry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(EngineRegistry::with_builtins()))
+}
+
+/// Registers an engine in the process-wide registry so configs can use it
+/// by name, e.g. `register_engine("my_llm", MyEngine::new)`.
+pub fn register_engine<F, Fut, E>(name: &str, factory: F)
+where
+    F: Fn(EngineConfig) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<E>> + Send + 'static,
+    E: Engine + 'static,
+{
+    global_registry()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .register(name, factory);
+}
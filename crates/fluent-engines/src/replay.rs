@@ -0,0 +1,419 @@
+// crates/fluent-engines/src/replay.rs
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Context, Result};
+use fluent_core::config::EngineConfig;
+use fluent_core::neo4j_client::Neo4jClient;
+use fluent_core::traits::Engine;
+use fluent_core::types::{ExtractedContent, Request, Response, UpsertRequest, UpsertResponse};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// One recorded call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: Request,
+    /// The file sent with `process_request_with_file`, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    pub response: Response,
+}
+
+/// A JSON file of recorded request/response pairs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read cassette {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid cassette {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write cassette {}", path.display()))
+    }
+}
+
+/// Requests are matched on everything but `flowname`, which the CLI sets to
+/// the engine name and so differs between recording and replay.
+fn same_request(recorded: &Request, request: &Request) -> bool {
+    let strip = |request: &Request| {
+        let mut value = serde_json::to_value(request).unwrap_or_default();
+        if let Some(object) = value.as_object_mut() {
+            object.remove("flowname");
+        }
+        value
+    };
+    strip(recorded) == strip(request)
+}
+
+fn file_name(file_path: Option<&Path>) -> Option<String> {
+    file_path.map(|path| path.display().to_string())
+}
+
+/// Serves responses from a cassette instead of calling a provider.
+///
+/// Configured as `"engine": "replay"` with the cassette path in
+/// `parameters.cassette`. Identical requests recorded more than once are
+/// answered in recorded order, repeating the last answer once they run out;
+/// a request that was never recorded is an error.
+pub struct ReplayEngine {
+    cassette_path: PathBuf,
+    interactions: Vec<Interaction>,
+    served: Mutex<Vec<usize>>,
+}
+
+impl ReplayEngine {
+    pub async fn new(config: EngineConfig) -> Result<Self> {
+        let cassette_path = config
+            .parameters
+            .get("cassette")
+            .and_then(|v| v.as_str())
+            .map(PathBuf::from)
+            .ok_or_else(|| anyhow!("Replay engine '{}' needs a cassette parameter", config.name))?;
+        let cassette = Cassette::load(&cassette_path)?;
+
+        Ok(Self {
+            served: Mutex::new(vec![0; cassette.interactions.len()]),
+            interactions: cassette.interactions,
+            cassette_path,
+        })
+    }
+
+    fn replay(&self, request: &Request, file_path: Option<&Path>) -> Result<Response> {
+        let file = file_name(file_path);
+        let matches: Vec<usize> = self
+            .interactions
+            .iter()
+            .enumerate()
+            .filter(|(_, i)| i.file == file && same_request(&i.request, request))
+            .map(|(index, _)| index)
+            .collect();
+
+        let mut served = self.served.lock().unwrap_or_else(|e| e.into_inner());
+        let index = matches
+            .iter()
+            .copied()
+            .find(|&index| served[index] == 0)
+            .or_else(|| matches.last().copied())
+            .ok_or_else(|| {
+                anyhow!(
+                    "No recorded response in {} for request: {}",
+                    self.cassette_path.display(),
+                    serde_json::to_string(request).unwrap_or_default()
+                )
+            })?;
+        served[index] += 1;
+        debug!("Replaying interaction {} from {}", index, self.cassette_path.display());
+        Ok(self.interactions[index].response.clone())
+    }
+}
+
+impl Engine for ReplayEngine {
+    fn execute<'a>(
+        &'a self,
+        request: &'a Request,
+    ) -> Box<dyn Future<Output = Result<Response>> + Send + 'a> {
+        Box::new(async move { self.replay(request, None) })
+    }
+
+    fn upsert<'a>(
+        &'a self,
+        _request: &'a UpsertRequest,
+    ) -> Box<dyn Future<Output = Result<UpsertResponse>> + Send + 'a> {
+        Box::new(async move { Err(anyhow!("Upsert is not supported by the replay engine")) })
+    }
+
+    fn get_neo4j_client(&self) -> Option<&Arc<Neo4jClient>> {
+        None
+    }
+
+    fn get_session_id(&self) -> Option<String> {
+        None
+    }
+
+    fn extract_content(&self, _value: &Value) -> Option<ExtractedContent> {
+        None
+    }
+
+    fn upload_file<'a>(
+        &'a self,
+        _file_path: &'a Path,
+    ) -> Box<dyn Future<Output = Result<String>> + Send + 'a> {
+        Box::new(async move { Err(anyhow!("File upload is not supported by the replay engine")) })
+    }
+
+    fn process_request_with_file<'a>(
+        &'a self,
+        request: &'a Request,
+        file_path: &'a Path,
+    ) -> Box<dyn Future<Output = Result<Response>> + Send + 'a> {
+        Box::new(async move { self.replay(request, Some(file_path)) })
+    }
+}
+
+/// Wraps an engine and appends every successful request/response pair to a
+/// cassette that a `replay` engine can serve later.
+///
+/// Streaming requests are recorded too, but arrive as a single chunk while
+/// recording.
+pub struct RecordingEngine {
+    inner: Box<dyn Engine>,
+    cassette_path: PathBuf,
+    cassette: Mutex<Cassette>,
+}
+
+impl RecordingEngine {
+    /// Appends to the cassette at `cassette_path` if it already exists.
+    pub fn new(inner: Box<dyn Engine>, cassette_path: impl Into<PathBuf>) -> Result<Self> {
+        let cassette_path = cassette_path.into();
+        let cassette = if cassette_path.exists() {
+            Cassette::load(&cassette_path)?
+        } else {
+            Cassette::default()
+        };
+        Ok(Self {
+            inner,
+            cassette_path,
+            cassette: Mutex::new(cassette),
+        })
+    }
+
+    fn record(&self, request: &Request, file_path: Option<&Path>, response: &Response) -> Result<()> {
+        let mut cassette = self.cassette.lock().unwrap_or_else(|e| e.into_inner());
+        cassette.interactions.push(Interaction {
+            request: request.clone(),
+            file: file_name(file_path),
+            response: response.clone(),
+        });
+        cassette.save(&self.cassette_path)
+    }
+}
+
+impl Engine for RecordingEngine {
+    fn execute<'a>(
+        &'a self,
+        request: &'a Request,
+    ) -> Box<dyn Future<Output = Result<Response>> + Send + 'a> {
+        Box::new(async move {
+            let response = Pin::from(self.inner.execute(request)).await?;
+            self.record(request, None, &response)?;
+            Ok(response)
+        })
+    }
+
+    fn upsert<'a>(
+        &'a self,
+        request: &'a UpsertRequest,
+    ) -> Box<dyn Future<Output = Result<UpsertResponse>> + Send + 'a> {
+        self.inner.upsert(request)
+    }
+
+    fn get_neo4j_client(&self) -> Option<&Arc<Neo4jClient>> {
+        self.inner.get_neo4j_client()
+    }
+
+    fn get_session_id(&self) -> Option<String> {
+        self.inner.get_session_id()
+    }
+
+    fn extract_content(&self, value: &Value) -> Option<ExtractedContent> {
+        self.inner.extract_content(value)
+    }
+
+    fn upload_file<'a>(
+        &'a self,
+        file_path: &'a Path,
+    ) -> Box<dyn Future<Output = Result<String>> + Send + 'a> {
+        self.inner.upload_file(file_path)
+    }
+
+    fn process_request_with_file<'a>(
+        &'a self,
+        request: &'a Request,
+        file_path: &'a Path,
+    ) -> Box<dyn Future<Output = Result<Response>> + Send + 'a> {
+        Box::new(async move {
+            let response = Pin::from(self.inner.process_request_with_file(request, file_path)).await?;
+            self.record(request, Some(file_path), &response)?;
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn request(payload: &str) -> Request {
+        Request {
+            flowname: "test".to_string(),
+            payload: payload.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn response(content: &str) -> Response {
+        Response {
+            content: content.to_string(),
+            model: "test-model".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn replay_config(cassette: &Path) -> EngineConfig {
+        serde_json::from_value(json!({
+            "name": "replay",
+            "engine": "replay",
+            "parameters": { "cassette": cassette.display().to_string() },
+        }))
+        .unwrap()
+    }
+
+    async fn execute(engine: &dyn Engine, payload: &str) -> Result<Response> {
+        Pin::from(engine.execute(&request(payload))).await
+    }
+
+    /// Answers with the request payload in upper case.
+    struct Shout;
+
+    impl Engine for Shout {
+        fn execute<'a>(
+            &'a self,
+            request: &'a Request,
+        ) -> Box<dyn Future<Output = Result<Response>> + Send + 'a> {
+            Box::new(async move { Ok(response(&request.payload.to_uppercase())) })
+        }
+
+        fn upsert<'a>(
+            &'a self,
+            _request: &'a UpsertRequest,
+        ) -> Box<dyn Future<Output = Result<UpsertResponse>> + Send + 'a> {
+            Box::new(async move { Err(anyhow!("unsupported")) })
+        }
+
+        fn get_neo4j_client(&self) -> Option<&Arc<Neo4jClient>> {
+            None
+        }
+
+        fn get_session_id(&self) -> Option<String> {
+            None
+        }
+
+        fn extract_content(&self, _value: &Value) -> Option<ExtractedContent> {
+            None
+        }
+
+        fn upload_file<'a>(
+            &'a self,
+            _file_path: &'a Path,
+        ) -> Box<dyn Future<Output = Result<String>> + Send + 'a> {
+            Box::new(async move { Err(anyhow!("unsupported")) })
+        }
+
+        fn process_request_with_file<'a>(
+            &'a self,
+            request: &'a Request,
+            _file_path: &'a Path,
+        ) -> Box<dyn Future<Output = Result<Response>> + Send + 'a> {
+            self.execute(request)
+        }
+    }
+
+    #[tokio::test]
+    async fn replays_matching_requests_in_recorded_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cassette.json");
+        let interaction = |payload: &str, content: &str| Interaction {
+            request: request(payload),
+            file: None,
+            response: response(content),
+        };
+        Cassette {
+            interactions: vec![
+                interaction("hello", "first"),
+                interaction("other", "unrelated"),
+                interaction("hello", "second"),
+            ],
+        }
+        .save(&path)
+        .unwrap();
+
+        let engine = ReplayEngine::new(replay_config(&path)).await.unwrap();
+        let mut replayed = request("hello");
+        // The CLI sets flowname to the engine name, so it is not matched on.
+        replayed.flowname = "replay".to_string();
+        let first = Pin::from(engine.execute(&replayed)).await.unwrap();
+        assert_eq!(first.content, "first");
+        assert_eq!(first.model, "test-model");
+        assert_eq!(execute(&engine, "hello").await.unwrap().content, "second");
+        assert_eq!(execute(&engine, "hello").await.unwrap().content, "second");
+        assert_eq!(
+            execute(&engine, "other").await.unwrap().content,
+            "unrelated"
+        );
+    }
+
+    #[tokio::test]
+    async fn unrecorded_request_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cassette.json");
+        Cassette {
+            interactions: vec![Interaction {
+                request: request("hello"),
+                file: Some("notes.txt".to_string()),
+                response: response("recorded"),
+            }],
+        }
+        .save(&path)
+        .unwrap();
+
+        let engine = ReplayEngine::new(replay_config(&path)).await.unwrap();
+        let error = execute(&engine, "goodbye").await.unwrap_err().to_string();
+        assert!(error.starts_with("No recorded response in"), "{}", error);
+        assert!(error.contains("goodbye"), "{}", error);
+        // The same request without the recorded file does not match either.
+        assert!(execute(&engine, "hello").await.is_err());
+        let with_file =
+            Pin::from(engine.process_request_with_file(&request("hello"), Path::new("notes.txt")))
+                .await
+                .unwrap();
+        assert_eq!(with_file.content, "recorded");
+    }
+
+    #[tokio::test]
+    async fn recorded_cassette_replays() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("cassette.json");
+
+        let recorder = RecordingEngine::new(Box::new(Shout), &path).unwrap();
+        assert_eq!(execute(&recorder, "one").await.unwrap().content, "ONE");
+        assert_eq!(execute(&recorder, "two").await.unwrap().content, "TWO");
+        drop(recorder);
+
+        // Recording again appends to the existing cassette.
+        let recorder = RecordingEngine::new(Box::new(Shout), &path).unwrap();
+        assert_eq!(execute(&recorder, "three").await.unwrap().content, "THREE");
+        assert_eq!(Cassette::load(&path).unwrap().interactions.len(), 3);
+
+        let replay = ReplayEngine::new(replay_config(&path)).await.unwrap();
+        for (payload, content) in [("two", "TWO"), ("one", "ONE"), ("three", "THREE")] {
+            assert_eq!(execute(&replay, payload).await.unwrap().content, content);
+        }
+        assert!(execute(&replay, "four").await.is_err());
+    }
+}
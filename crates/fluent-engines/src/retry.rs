@@ -0,0 +1,134 @@
+// crates/fluent-engines/src/retry.rs
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use fluent_core::config::RetryConfig;
+use fluent_core::error::EngineError;
+use fluent_core::neo4j_client::Neo4jClient;
+use fluent_core::traits::{Engine, ResponseStream};
+use fluent_core::types::{ExtractedContent, Request, Response, UpsertRequest, UpsertResponse};
+use log::warn;
+use serde_json::Value;
+
+/// Wraps an engine and retries `execute`, `process_request_with_file` and
+/// the opening of a stream when they fail with one of the configured error
+/// classes. Other calls are passed straight through.
+pub struct RetryEngine {
+    inner: Box<dyn Engine>,
+    config: RetryConfig,
+}
+
+impl RetryEngine {
+    pub fn new(inner: Box<dyn Engine>, config: RetryConfig) -> Self {
+        Self { inner, config }
+    }
+
+    fn should_retry(&self, error: &anyhow::Error) -> bool {
+        EngineError::find(error).is_some_and(|error| self.config.retry_on.contains(&error.class()))
+    }
+
+    /// The wait before retry number `retry` (starting at 1): the provider's
+    /// `Retry-After` when it sent one, otherwise exponential backoff with
+    /// "equal jitter" so concurrent callers spread out. Either is capped at
+    /// `max_delay_ms`.
+    fn delay(&self, retry: u32, error: &anyhow::Error) -> Duration {
+        let max_delay = Duration::from_millis(self.config.max_delay_ms);
+        if let Some(delay) = EngineError::find(error).and_then(EngineError::retry_after) {
+            return delay.min(max_delay);
+        }
+        let backoff = self
+            .config
+            .base_delay_ms
+            .saturating_mul(1u64 << (retry - 1).min(32))
+            .min(self.config.max_delay_ms);
+        let half = backoff / 2;
+        Duration::from_millis(half + random_below(backoff - half + 1))
+    }
+
+    async fn with_retries<'a, T, F>(&self, operation: F) -> Result<T>
+    where
+        F: Fn() -> Box<dyn Future<Output = Result<T>> + Send + 'a>,
+    {
+        let mut attempt = 1;
+        loop {
+            match Pin::from(operation()).await {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt < self.config.max_attempts && self.should_retry(&e) => {
+                    let delay = self.delay(attempt, &e);
+                    warn!(
+                        "Attempt {} of {} failed: {}. Retrying in {:.1}s",
+                        attempt,
+                        self.config.max_attempts,
+                        e,
+                        delay.as_secs_f64()
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// A random number in `0..bound`, good enough for jitter.
+fn random_below(bound: u64) -> u64 {
+    RandomState::new().build_hasher().finish() % bound.max(1)
+}
+
+impl Engine for RetryEngine {
+    fn execute<'a>(
+        &'a self,
+        request: &'a Request,
+    ) -> Box<dyn Future<Output = Result<Response>> + Send + 'a> {
+        Box::new(self.with_retries(move || self.inner.execute(request)))
+    }
+
+    fn upsert<'a>(
+        &'a self,
+        request: &'a UpsertRequest,
+    ) -> Box<dyn Future<Output = Result<UpsertResponse>> + Send + 'a> {
+        self.inner.upsert(request)
+    }
+
+    fn get_neo4j_client(&self) -> Option<&Arc<Neo4jClient>> {
+        self.inner.get_neo4j_client()
+    }
+
+    fn get_session_id(&self) -> Option<String> {
+        self.inner.get_session_id()
+    }
+
+    fn extract_content(&self, value: &Value) -> Option<ExtractedContent> {
+        self.inner.extract_content(value)
+    }
+
+    fn upload_file<'a>(
+        &'a self,
+        file_path: &'a Path,
+    ) -> Box<dyn Future<Output = Result<String>> + Send + 'a> {
+        self.inner.upload_file(file_path)
+    }
+
+    fn process_request_with_file<'a>(
+        &'a self,
+        request: &'a Request,
+        file_path: &'a Path,
+    ) -> Box<dyn Future<Output = Result<Response>> + Send + 'a> {
+        Box::new(self.with_retries(move || self.inner.process_request_with_file(request, file_path)))
+    }
+
+    fn execute_stream<'a>(
+        &'a self,
+        request: &'a Request,
+    ) -> Box<dyn Future<Output = Result<ResponseStream<'a>>> + Send + 'a> {
+        // Only opening the stream is retried; text already printed can't be taken back.
+        Box::new(self.with_retries(move || self.inner.execute_stream(request)))
+    }
+}
@@ -10,6 +10,8 @@ use fluent_core::types::{ExtractedContent, Request, Response, UpsertRequest, Ups
 use fluent_core::neo4j_client::Neo4jClient;
 use fluent_core::traits::Engine;
 use fluent_core::config::EngineConfig;
+use fluent_core::error::{read_json, EngineError, RequestBuilderExt};
+use fluent_core::pricing::image_cost;
 use log::debug;
 use reqwest::Client;
 use reqwest::multipart::{Form, Part};
@@ -33,10 +35,10 @@ impl StabilityAIEngine {
         };
 
         Ok(Self {
+            download_dir: config.download_dir.clone(),
             config,
             client: Client::new(),
             neo4j_client,
-            download_dir: None, // Initialize as None
         })
     }
 
@@ -94,24 +96,21 @@ impl Engine for StabilityAIEngine {
                 .header("Authorization", format!("Bearer {}", auth_token))
                 .header("Accept", accept_header)  // Add Accept header
                 .multipart(form)
-                .send()
+                .send_checked("Stability AI")
                 .await?;
 
-            // Check for successful response (200 OK)
-            if !response.status().is_success() {
-                return Err(anyhow!("Stability AI API request failed: {}", response.status()));
-            }
-
             let response_content = if accept_header == "application/json" {
                 // Parse JSON response and extract base64 image data
-                let json_response: Value = response.json().await?;
+                let json_response = read_json("Stability AI", response).await?;
                 let base64_image = json_response["artifacts"][0]["base64"]
                     .as_str()
-                    .ok_or_else(|| anyhow!("Failed to extract base64 image data from JSON response"))?;
+                    .ok_or_else(|| EngineError::malformed("Stability AI", "missing base64 image data"))?;
                 Base64.encode(base64_image).into_bytes()
             } else {
                 // Get image bytes directly
-                response.bytes().await?.to_vec()
+                response.bytes().await
+                    .map_err(|e| EngineError::transport("Stability AI", e))?
+                    .to_vec()
             };
 
             // Get the download directory
@@ -137,6 +136,8 @@ impl Engine for StabilityAIEngine {
                 },
                 model: "stabilityai-ultra".to_string(),
                 finish_reason: Some("success".to_string()),
+                cost: image_cost(&self.config, "stabilityai-ultra", 1),
+                ..Default::default()
             })
         })
     }
@@ -221,21 +222,15 @@ impl Engine for StabilityAIEngine {
             let response = self.client.post(&url)
                 .header("Authorization", format!("Bearer {}", auth_token))
                 .json(&payload)
-                .send()
-                .await?
-                .json::<Value>()
+                .send_json("Stability AI")
                 .await?;
 
             debug!("Stability AI Response: {:?}", response);
 
-            if let Some(error) = response.get("error") {
-                return Err(anyhow!("Stability AI API error: {:?}", error));
-            }
-
             // Extract image URLs from the response
             let image_urls: Vec<String> = response["artifacts"]
                 .as_array()
-                .ok_or_else(|| anyhow!("Failed to extract artifacts from Stability AI response"))?
+                .ok_or_else(|| EngineError::malformed("Stability AI", "missing artifacts"))?
                 .iter()
                 .filter_map(|artifact| artifact["base64"].as_str().map(|base64| format!("data:image/png;base64,{}", base64)))
                 .collect();
@@ -243,8 +238,10 @@ impl Engine for StabilityAIEngine {
             Ok(Response {
                 content: image_urls.join("\n"),
                 usage: Usage { prompt_tokens: 0, completion_tokens: 0, total_tokens: 0 },
-                model: "stability-ai".to_string(), // Extract the model name from the response if available
+                model: "stabilityai-ultra".to_string(),
                 finish_reason: Some("success".to_string()),
+                cost: image_cost(&self.config, "stabilityai-ultra", image_urls.len()),
+                ..Default::default()
             })
         })
     }
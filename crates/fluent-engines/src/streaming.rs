@@ -0,0 +1,264 @@
+// crates/fluent-engines/src/streaming.rs
+use std::collections::VecDeque;
+
+use anyhow::{anyhow, Result};
+use fluent_core::traits::ResponseStream;
+use fluent_core::types::{Response, ResponseChunk, Usage};
+use futures::stream::{self, Stream, StreamExt};
+use log::debug;
+use serde_json::Value;
+
+/// Splits a raw byte stream into complete frames.
+trait FrameSplitter {
+    fn feed(&mut self, bytes: &[u8]) -> Vec<String>;
+    fn finish(&mut self) -> Vec<String>;
+}
+
+/// Server-sent events: collects `data:` lines and emits them on each blank line.
+#[derive(Default)]
+struct SseSplitter {
+    line: Vec<u8>,
+    data: Vec<String>,
+}
+
+impl SseSplitter {
+    fn dispatch(&mut self) -> Option<String> {
+        if self.data.is_empty() {
+            None
+        } else {
+            Some(std::mem::take(&mut self.data).join("\n"))
+        }
+    }
+
+    fn process_line(&mut self) -> Option<String> {
+        let line = String::from_utf8_lossy(&self.line)
+            .trim_end_matches('\r')
+            .to_string();
+        self.line.clear();
+
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        if let Some(data) = line.strip_prefix("data:") {
+            self.data.push(data.strip_prefix(' ').unwrap_or(data).to_string());
+        }
+        // `event:`, `id:`, `retry:` and comment lines carry nothing we need.
+        None
+    }
+}
+
+impl FrameSplitter for SseSplitter {
+    fn feed(&mut self, bytes: &[u8]) -> Vec<String> {
+        let mut frames = Vec::new();
+        for &byte in bytes {
+            if byte == b'\n' {
+                frames.extend(self.process_line());
+            } else {
+                self.line.push(byte);
+            }
+        }
+        frames
+    }
+
+    fn finish(&mut self) -> Vec<String> {
+        let mut frames = Vec::new();
+        if !self.line.is_empty() {
+            frames.extend(self.process_line());
+        }
+        frames.extend(self.dispatch());
+        frames
+    }
+}
+
+/// Chunked JSON: emits every top-level object, whether the body is a JSON
+/// array streamed element by element (Gemini) or newline-delimited (Cohere).
+#[derive(Default)]
+struct JsonObjectSplitter {
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    current: Vec<u8>,
+}
+
+impl FrameSplitter for JsonObjectSplitter {
+    fn feed(&mut self, bytes: &[u8]) -> Vec<String> {
+        let mut frames = Vec::new();
+        for &byte in bytes {
+            if self.depth == 0 {
+                // Skip array brackets, commas and whitespace between objects.
+                if byte == b'{' {
+                    self.depth = 1;
+                    self.current.push(byte);
+                }
+                continue;
+            }
+
+            self.current.push(byte);
+            if self.in_string {
+                if self.escaped {
+                    self.escaped = false;
+                } else if byte == b'\\' {
+                    self.escaped = true;
+                } else if byte == b'"' {
+                    self.in_string = false;
+                }
+                continue;
+            }
+
+            match byte {
+                b'"' => self.in_string = true,
+                b'{' | b'[' => self.depth += 1,
+                b'}' | b']' => {
+                    self.depth -= 1;
+                    if self.depth == 0 {
+                        let frame = std::mem::take(&mut self.current);
+                        frames.push(String::from_utf8_lossy(&frame).to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+        frames
+    }
+
+    fn finish(&mut self) -> Vec<String> {
+        if !self.current.is_empty() {
+            debug!(
+                "Discarding incomplete JSON frame: {}",
+                String::from_utf8_lossy(&self.current)
+            );
+        }
+        Vec::new()
+    }
+}
+
+fn frames<S>(response: reqwest::Response, splitter: S) -> impl Stream<Item = Result<String>> + Send
+where
+    S: FrameSplitter + Send + 'static,
+{
+    let bytes = response.bytes_stream().boxed();
+    stream::unfold(
+        (bytes, splitter, VecDeque::new(), false),
+        |(mut bytes, mut splitter, mut pending, mut finished)| async move {
+            loop {
+                if let Some(frame) = pending.pop_front() {
+                    return Some((Ok(frame), (bytes, splitter, pending, finished)));
+                }
+                if finished {
+                    return None;
+                }
+                match bytes.next().await {
+                    Some(Ok(chunk)) => pending.extend(splitter.feed(&chunk)),
+                    Some(Err(e)) => {
+                        finished = true;
+                        return Some((
+                            Err(anyhow!("Error reading response stream: {}", e)),
+                            (bytes, splitter, pending, finished),
+                        ));
+                    }
+                    None => {
+                        finished = true;
+                        pending.extend(splitter.finish());
+                    }
+                }
+            }
+        },
+    )
+}
+
+/// Parses a server-sent event body into JSON payloads, stopping at `[DONE]`.
+pub fn sse_json_events(response: reqwest::Response) -> impl Stream<Item = Result<Value>> + Send {
+    frames(response, SseSplitter::default())
+        .take_while(|frame| {
+            let done = matches!(frame, Ok(data) if data.trim() == "[DONE]");
+            async move { !done }
+        })
+        .map(|frame| {
+            let data = frame?;
+            debug!("SSE event: {}", data);
+            serde_json::from_str(&data).map_err(|e| anyhow!("Invalid JSON in stream event: {}", e))
+        })
+}
+
+/// Parses a chunked JSON body into its top-level objects.
+pub fn json_object_events(response: reqwest::Response) -> impl Stream<Item = Result<Value>> + Send {
+    frames(response, JsonObjectSplitter::default()).map(|frame| {
+        let data = frame?;
+        debug!("JSON stream object: {}", data);
+        serde_json::from_str(&data).map_err(|e| anyhow!("Invalid JSON in stream object: {}", e))
+    })
+}
+
+/// Maps a chat completion chunk in the OpenAI format, which Mistral, Groq and
+/// Perplexity share, into a `ResponseChunk`.
+pub fn openai_chunk(value: &Value) -> ResponseChunk {
+    let choice = &value["choices"][0];
+    // Groq reports usage under `x_groq` on the final chunk.
+    let usage = [&value["usage"], &value["x_groq"]["usage"]]
+        .into_iter()
+        .find(|usage| usage.is_object())
+        .map(|usage| Usage {
+            prompt_tokens: usage["prompt_tokens"].as_u64().unwrap_or(0) as u32,
+            completion_tokens: usage["completion_tokens"].as_u64().unwrap_or(0) as u32,
+            total_tokens: usage["total_tokens"].as_u64().unwrap_or(0) as u32,
+        });
+
+    ResponseChunk {
+        delta: choice["delta"]["content"].as_str().unwrap_or_default().to_string(),
+        usage,
+        model: value["model"].as_str().map(String::from),
+        finish_reason: choice["finish_reason"].as_str().map(String::from),
+    }
+}
+
+/// Drains a response stream, calling `on_delta` for each piece of text, and
+/// assembles the final `Response`.
+///
+/// Usage counts are merged across chunks because some providers report prompt
+/// and completion tokens in different events.
+pub async fn collect_stream(
+    mut stream: ResponseStream<'_>,
+    mut on_delta: impl FnMut(&str),
+) -> Result<Response> {
+    let mut content = String::new();
+    let mut usage = Usage::default();
+    let mut model = None;
+    let mut finish_reason = None;
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        if !chunk.delta.is_empty() {
+            on_delta(&chunk.delta);
+            content.push_str(&chunk.delta);
+        }
+        if let Some(chunk_usage) = chunk.usage {
+            if chunk_usage.prompt_tokens > 0 {
+                usage.prompt_tokens = chunk_usage.prompt_tokens;
+            }
+            if chunk_usage.completion_tokens > 0 {
+                usage.completion_tokens = chunk_usage.completion_tokens;
+            }
+            if chunk_usage.total_tokens > 0 {
+                usage.total_tokens = chunk_usage.total_tokens;
+            }
+        }
+        if chunk.model.is_some() {
+            model = chunk.model;
+        }
+        if chunk.finish_reason.is_some() {
+            finish_reason = chunk.finish_reason;
+        }
+    }
+
+    if usage.total_tokens == 0 {
+        usage.total_tokens = usage.prompt_tokens + usage.completion_tokens;
+    }
+
+    Ok(Response {
+        content,
+        usage,
+        model: model.unwrap_or_else(|| "unknown".to_string()),
+        finish_reason,
+        ..Default::default()
+    })
+}
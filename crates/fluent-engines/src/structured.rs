@@ -0,0 +1,138 @@
+// crates/fluent-engines/src/structured.rs
+use std::fmt;
+use std::pin::Pin;
+
+use anyhow::{anyhow, Result};
+use fluent_core::traits::Engine;
+use fluent_core::types::{Message, Request, Response, Role, Usage};
+use log::{debug, warn};
+use serde_json::Value;
+
+/// Number of times `execute_structured` re-prompts after an invalid reply.
+pub const DEFAULT_MAX_REPAIRS: usize = 2;
+
+/// A reply that parsed and validated against the requested schema.
+#[derive(Debug, Clone)]
+pub struct StructuredResponse {
+    pub value: Value,
+    /// The final engine response, with usage summed over every attempt.
+    pub response: Response,
+}
+
+/// Returned when the model never produced JSON matching the schema.
+#[derive(Debug, Clone)]
+pub struct SchemaValidationError {
+    pub attempts: usize,
+    /// Problems with the last reply: a parse error or the schema violations.
+    pub errors: Vec<String>,
+    pub last_content: String,
+}
+
+impl fmt::Display for SchemaValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Response did not match the JSON schema after {} attempt(s): {}",
+            self.attempts,
+            self.errors.join("; ")
+        )
+    }
+}
+
+impl std::error::Error for SchemaValidationError {}
+
+/// Parses a reply as JSON, tolerating a surrounding markdown code fence.
+fn parse_json(content: &str) -> Result<Value, String> {
+    let trimmed = content.trim();
+    let unfenced = trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|rest| rest.strip_suffix("```"))
+        .unwrap_or(trimmed);
+    serde_json::from_str(unfenced.trim()).map_err(|e| format!("invalid JSON: {}", e))
+}
+
+/// The request as `execute_structured` sends it: asking for JSON that
+/// matches `schema` in a system message and through the engine's JSON mode.
+pub fn with_schema(request: &Request, schema: &Value) -> Result<Request> {
+    let mut request = request.clone();
+    request.response_schema = Some(schema.clone());
+    request.messages.insert(
+        0,
+        Message::new(
+            Role::System,
+            format!(
+                "Respond only with JSON that conforms to this JSON Schema, without any other text:\n{}",
+                serde_json::to_string_pretty(schema)?
+            ),
+        ),
+    );
+    Ok(request)
+}
+
+/// Executes `request` asking for JSON that matches `schema`, using the
+/// engine's native JSON mode where it has one. Replies that fail to parse or
+/// validate are sent back with the errors, up to `max_repairs` times, before
+/// failing with a `SchemaValidationError`.
+///
+/// A request already built with `with_schema`, for example to fit it to the
+/// context window first, is sent as it is.
+pub async fn execute_structured(
+    engine: &dyn Engine,
+    request: &Request,
+    schema: &Value,
+    max_repairs: usize,
+) -> Result<StructuredResponse> {
+    let validator = jsonschema::validator_for(schema)
+        .map_err(|e| anyhow!("Invalid JSON schema: {}", e))?;
+
+    let mut request = if request.response_schema.as_ref() == Some(schema) {
+        request.clone()
+    } else {
+        with_schema(request, schema)?
+    };
+
+    let mut usage = Usage::default();
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let mut response = Pin::from(engine.execute(&request)).await?;
+        usage.prompt_tokens += response.usage.prompt_tokens;
+        usage.completion_tokens += response.usage.completion_tokens;
+        usage.total_tokens += response.usage.total_tokens;
+
+        let errors = match parse_json(&response.content) {
+            Ok(value) => {
+                let errors: Vec<String> = validator
+                    .iter_errors(&value)
+                    .map(|e| format!("{} at '{}'", e, e.instance_path))
+                    .collect();
+                if errors.is_empty() {
+                    response.usage = usage;
+                    return Ok(StructuredResponse { value, response });
+                }
+                errors
+            }
+            Err(e) => vec![e],
+        };
+
+        debug!("Attempt {} failed validation: {:?}", attempt, errors);
+        if attempt > max_repairs {
+            return Err(SchemaValidationError {
+                attempts: attempt,
+                errors,
+                last_content: response.content,
+            }
+            .into());
+        }
+        warn!("Response did not match the schema, asking the model to repair it");
+
+        // Fold the payload into the history so the repair prompt follows the reply.
+        request.messages = request.conversation();
+        request.payload = format!(
+            "Your reply did not match the JSON schema:\n- {}\nReply again with only the corrected JSON.",
+            errors.join("\n- ")
+        );
+        request.messages.push(Message::new(Role::Assistant, response.content));
+    }
+}
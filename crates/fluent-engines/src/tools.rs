@@ -0,0 +1,210 @@
+// crates/fluent-engines/src/tools.rs
+use std::fs;
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+
+use anyhow::{anyhow, Context, Result};
+use fluent_core::traits::Engine;
+use fluent_core::types::{Message, Request, Response, ToolCall, ToolDefinition, Usage};
+use log::{debug, info};
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::process::Command;
+
+/// Number of model turns `execute_with_tools` allows before giving up.
+pub const DEFAULT_MAX_TOOL_ROUNDS: usize = 10;
+
+/// A tool the model can call, together with the code that runs it.
+pub trait Tool: Send + Sync {
+    fn definition(&self) -> &ToolDefinition;
+
+    fn call<'a>(&'a self, arguments: &'a Value) -> Box<dyn Future<Output = Result<String>> + Send + 'a>;
+}
+
+/// A tool implemented by a Rust closure.
+pub struct FnTool<F> {
+    definition: ToolDefinition,
+    handler: F,
+}
+
+impl<F> FnTool<F>
+where
+    F: Fn(&Value) -> Result<String> + Send + Sync,
+{
+    pub fn new(definition: ToolDefinition, handler: F) -> Self {
+        Self { definition, handler }
+    }
+}
+
+impl<F> Tool for FnTool<F>
+where
+    F: Fn(&Value) -> Result<String> + Send + Sync,
+{
+    fn definition(&self) -> &ToolDefinition {
+        &self.definition
+    }
+
+    fn call<'a>(&'a self, arguments: &'a Value) -> Box<dyn Future<Output = Result<String>> + Send + 'a> {
+        Box::new(async move { (self.handler)(arguments) })
+    }
+}
+
+/// A tool that runs a shell command and returns its stdout.
+///
+/// The call's arguments are passed as JSON in `FLUENT_TOOL_ARGS`, and each
+/// top-level scalar argument also as `FLUENT_ARG_<NAME>` (upper-cased).
+#[derive(Debug, Deserialize, Clone)]
+pub struct ShellTool {
+    #[serde(flatten)]
+    pub definition: ToolDefinition,
+    pub command: String,
+}
+
+impl Tool for ShellTool {
+    fn definition(&self) -> &ToolDefinition {
+        &self.definition
+    }
+
+    fn call<'a>(&'a self, arguments: &'a Value) -> Box<dyn Future<Output = Result<String>> + Send + 'a> {
+        Box::new(async move {
+            let mut command = Command::new("sh");
+            command
+                .arg("-c")
+                .arg(&self.command)
+                .env("FLUENT_TOOL_ARGS", arguments.to_string());
+
+            for (name, value) in arguments.as_object().into_iter().flatten() {
+                let value = match value {
+                    Value::String(s) => s.clone(),
+                    Value::Number(_) | Value::Bool(_) => value.to_string(),
+                    _ => continue,
+                };
+                command.env(format!("FLUENT_ARG_{}", name.to_uppercase()), value);
+            }
+
+            debug!("Running tool {}: {}", self.definition.name, self.command);
+            let output = command.output().await?;
+            if !output.status.success() {
+                return Err(anyhow!(
+                    "Command exited with {}: {}",
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+            Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        })
+    }
+}
+
+/// The set of tools offered to the model, looked up by name when it calls one.
+#[derive(Default)]
+pub struct ToolRegistry {
+    tools: Vec<Box<dyn Tool>>,
+}
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads shell tools from a JSON file holding an array of
+    /// `{ "name", "description", "parameters", "command" }` objects.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read tools file {}", path.display()))?;
+        let tools: Vec<ShellTool> = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid tools file {}", path.display()))?;
+
+        let mut registry = Self::new();
+        for tool in tools {
+            registry.register(tool);
+        }
+        Ok(registry)
+    }
+
+    /// Adds a tool, replacing any registered tool with the same name.
+    pub fn register(&mut self, tool: impl Tool + 'static) -> &mut Self {
+        let name = tool.definition().name.clone();
+        self.tools.retain(|t| t.definition().name != name);
+        self.tools.push(Box::new(tool));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+
+    pub fn definitions(&self) -> Vec<ToolDefinition> {
+        self.tools.iter().map(|t| t.definition().clone()).collect()
+    }
+
+    /// Adds the registry's tools to a request, skipping any it already has.
+    pub fn add_to(&self, request: &mut Request) {
+        for definition in self.definitions() {
+            if !request.tools.iter().any(|tool| tool.name == definition.name) {
+                request.tools.push(definition);
+            }
+        }
+    }
+
+    /// Runs a tool call. Failures are returned as text so the model can see
+    /// them and recover instead of aborting the conversation.
+    pub async fn call(&self, call: &ToolCall) -> String {
+        let tool = match self.tools.iter().find(|t| t.definition().name == call.name) {
+            Some(tool) => tool,
+            None => return format!("Error: unknown tool '{}'", call.name),
+        };
+
+        match Pin::from(tool.call(&call.arguments)).await {
+            Ok(output) => output,
+            Err(e) => format!("Error: {}", e),
+        }
+    }
+}
+
+/// Executes `request` with the registry's tools, running each tool call the
+/// model makes and sending the results back until it answers without calling
+/// a tool. Usage is summed over all rounds.
+pub async fn execute_with_tools(
+    engine: &dyn Engine,
+    request: &Request,
+    registry: &ToolRegistry,
+    max_rounds: usize,
+) -> Result<Response> {
+    let mut request = request.clone();
+    registry.add_to(&mut request);
+
+    let mut usage = Usage::default();
+    for round in 1..=max_rounds {
+        let mut response = Pin::from(engine.execute(&request)).await?;
+        usage.prompt_tokens += response.usage.prompt_tokens;
+        usage.completion_tokens += response.usage.completion_tokens;
+        usage.total_tokens += response.usage.total_tokens;
+
+        if response.tool_calls.is_empty() {
+            response.usage = usage;
+            return Ok(response);
+        }
+
+        // Fold the payload into the history so later turns keep their order.
+        request.messages = request.conversation();
+        request.payload.clear();
+        request.messages.push(Message::assistant_tool_calls(
+            response.content.clone(),
+            response.tool_calls.clone(),
+        ));
+
+        for call in &response.tool_calls {
+            info!("Round {}: calling tool {} with {}", round, call.name, call.arguments);
+            let output = registry.call(call).await;
+            debug!("Tool {} returned: {}", call.name, output);
+            request.messages.push(Message::tool_result(call, output));
+        }
+    }
+
+    Err(anyhow!(
+        "Model was still calling tools after {} rounds",
+        max_rounds
+    ))
+}
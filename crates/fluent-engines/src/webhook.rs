@@ -12,6 +12,7 @@ use fluent_core::types::{ExtractedContent, Request, Response, UpsertRequest, Ups
 use fluent_core::neo4j_client::Neo4jClient;
 use fluent_core::traits::Engine;
 use fluent_core::config::EngineConfig;
+use fluent_core::error::RequestBuilderExt;
 use log::debug;
 use reqwest::Client;
 
@@ -97,17 +98,11 @@ impl Engine for WebhookEngine {
                 .header("Authorization", format!("Bearer {}", auth_token))
                 .header("Content-Type", "application/json")
                 .json(&payload)
-                .send()
-                .await?
-                .json::<Value>()
+                .send_json("Webhook")
                 .await?;
 
             debug!("Webhook Response: {:?}", response);
 
-            if let Some(error) = response.get("error") {
-                return Err(anyhow!("Webhook error: {:?}", error));
-            }
-
             let content = serde_json::to_string(&response)
                 .context("Failed to serialize webhook response")?;
 
@@ -120,6 +115,7 @@ impl Engine for WebhookEngine {
                 },
                 model: self.config.name.clone(),
                 finish_reason: Some("webhook_complete".to_string()),
+                ..Default::default()
             })
         })
     }
@@ -190,17 +186,11 @@ impl Engine for WebhookEngine {
                 .header("Authorization", format!("Bearer {}", auth_token))
                 .header("Content-Type", "application/json")
                 .json(&payload)
-                .send()
-                .await?
-                .json::<Value>()
+                .send_json("Webhook")
                 .await?;
 
             debug!("Webhook Response: {:?}", response);
 
-            if let Some(error) = response.get("error") {
-                return Err(anyhow!("Webhook error: {:?}", error));
-            }
-
             let content = serde_json::to_string(&response)
                 .context("Failed to serialize webhook response")?;
 
@@ -213,6 +203,7 @@ impl Engine for WebhookEngine {
                 },
                 model: self.config.name.clone(),
                 finish_reason: Some("webhook_complete".to_string()),
+                ..Default::default()
             })
         })
     }
@@ -1,15 +1,19 @@
 use anyhow::anyhow;
 use fluent_core::config::load_engine_config;
+use fluent_core::pricing::response_cost;
+use fluent_core::tokenizer::fit_request;
 use fluent_engines::create_engine;
+use fluent_engines::structured::{execute_structured, DEFAULT_MAX_REPAIRS};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::{collections::HashMap, pin::Pin};
+use std::{collections::HashMap, pin::Pin, str::FromStr};
 use strum::{Display, EnumString};
 
 pub async fn run(request: Request) -> anyhow::Result<Response> {
+    // Any engine in config.json can be used; known templates also accept their aliases.
     let engine_name = request
         .engine
-        .map(|t| t.to_string())
+        .map(|name| Template::from_str(&name).map_or(name, |t| t.to_string()))
         .ok_or_else(|| anyhow!("Engine is required"))?;
 
     let config_content = include_str!("config.json");
@@ -34,29 +38,31 @@ pub async fn run(request: Request) -> anyhow::Result<Response> {
 
     let engine_config = load_engine_config(config_content, &engine_name, &overrides, &credentials)?;
 
-    let max_tokens = engine_config
-        .parameters
-        .get("max_tokens")
-        .and_then(|v| v.as_i64());
-
     //TODO: Add support for other extended input
-    let mut combined_request = user_prompt;
-
-    if let Some(max_tokens) = max_tokens {
-        if combined_request.len() > max_tokens as usize {
-            combined_request.truncate(max_tokens as usize);
-            combined_request += "... [truncated]";
-        }
-    }
+    let mut fluent_request = fluent_core::types::Request {
+        flowname: engine_name,
+        payload: user_prompt,
+        messages: request.messages.unwrap_or_default(),
+        tools: request.tools.unwrap_or_default(),
+        ..Default::default()
+    };
+    fit_request(&mut fluent_request, &engine_config);
 
     let engine = create_engine(&engine_config).await?;
 
-    let fluent_request = fluent_core::types::Request {
-        flowname: engine_name,
-        payload: combined_request,
+    let mut fluent_response = match &request.json_schema {
+        Some(schema) => {
+            let max_repairs = request.max_repairs.unwrap_or(DEFAULT_MAX_REPAIRS);
+            let structured =
+                execute_structured(&*engine, &fluent_request, schema, max_repairs).await?;
+            fluent_core::types::Response {
+                content: structured.value.to_string(),
+                ..structured.response
+            }
+        }
+        None => Pin::from(engine.execute(&fluent_request)).await?,
     };
-
-    let fluent_response = Pin::from(engine.execute(&fluent_request)).await?;
+    fluent_response.cost = response_cost(&engine_config, &fluent_response);
 
     //TODO: Add support for other extended output
 
@@ -65,6 +71,7 @@ pub async fn run(request: Request) -> anyhow::Result<Response> {
     })
 }
 
+/// Engines in config.json with alternate spellings callers may use.
 #[derive(Debug, PartialEq, EnumString, Serialize, Deserialize, Display)]
 pub enum Template {
     #[strum(ascii_case_insensitive, to_string = "openai")]
@@ -192,8 +199,8 @@ pub enum Template {
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Request {
-    // The template to use (openai or anthropic)
-    engine: Option<Template>,
+    // The name of the engine in config.json to use (e.g. openai or sonnet3.5)
+    engine: Option<String>,
 
     // The credentials to be used on the request
     credentials: Option<Vec<KeyValue>>,
@@ -204,6 +211,18 @@ pub struct Request {
     // The user prompt to process
     request: Option<String>,
 
+    // Prior conversation turns, sent before the user prompt
+    messages: Option<Vec<fluent_core::types::Message>>,
+
+    // Tools the model may call; calls are returned in the response for the caller to run
+    tools: Option<Vec<fluent_core::types::ToolDefinition>>,
+
+    // JSON Schema the response must match; the content is then the validated JSON
+    json_schema: Option<Value>,
+
+    // Times to re-prompt when the response does not match the schema
+    max_repairs: Option<usize>,
+
     // Parse and display code blocks from the output
     parse_code: Option<bool>,
 }
@@ -1,15 +1,10 @@
-use std::process;
 use fluent_cli::cli;
 
 #[tokio::main]
-async fn main() -> anyhow::Result<()> {
+async fn main() {
     env_logger::init();
     if let Err(e) = cli::run().await {
         eprintln!("Error: {}", e);
-        process::exit(1);
+        std::process::exit(cli::exit_code(&e));
     }
-    // Ensure the program exits even if run() completes without error
-    process::exit(0);
 }
-
-